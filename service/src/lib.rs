@@ -33,6 +33,14 @@ pub enum Error {
 pub enum Request {
     /// Attach a window to the tiler.
     Attach(WindowID),
+    /// Attach a window to a specific branch of a fork.
+    AttachToFork {
+        window: WindowID,
+        fork: usize,
+        side: Side,
+    },
+    /// Apply each of these commands, responding once with their combined events.
+    Batch(Vec<Command>),
     /// Detach a window from the tiler.
     Detach(WindowID),
     /// Insert or update the dimensions of a display.
@@ -73,6 +81,8 @@ pub enum Request {
     Resize(usize, u32),
     /// Swap the positions of two windows.
     Swap(WindowID, WindowID),
+    /// Swap the position of the active window with this window.
+    SwapActive(WindowID),
     /// Switch to a different workspace.
     WorkspaceSwitch(u32),
     /// Associate a workspace with a display.
@@ -97,21 +107,32 @@ impl<T: 'static> Service<T> {
         input: Request,
         t: &'a mut TCellOwner<T>,
     ) -> impl Iterator<Item = Event> + 'a {
-        let tiler = &mut self.tiler;
+        let batch = match input {
+            Request::Batch(commands) => self.tiler.apply_batch(&commands, t),
+            input => {
+                self.apply(input, t);
+                Vec::new()
+            }
+        };
 
-        let window_from_id = |window: WindowID| tiler.windows.get(&window).cloned();
+        batch.into_iter().chain(self.tiler.events(t))
+    }
+
+    fn apply(&mut self, input: Request, t: &mut TCellOwner<T>) {
+        let tiler = &mut self.tiler;
 
         match input {
-            Request::Attach(window) => {
-                if let Some(window) = window_from_id(window) {
-                    tiler.attach(&window, t)
-                }
+            Request::Attach(window) => tiler.attach_by_id(window, t),
+
+            Request::AttachToFork { window, fork, side } => {
+                let _ = tiler.attach_to_fork_branch(window, fork, side, t);
             }
 
+            // Batches are applied by `handle`, which retrieves their events at once.
+            Request::Batch(_) => unreachable!("batches are applied by `Service::handle`"),
+
             Request::Detach(window) => {
-                if let Some(window) = window_from_id(window) {
-                    tiler.detach(&window, t);
-                }
+                let _ = tiler.detach_by_id(window, t);
             }
 
             Request::DisplayUpdate {
@@ -124,9 +145,7 @@ impl<T: 'static> Service<T> {
             Request::DisplayDetach(display_id) => tiler.display_detach(display_id, t),
 
             Request::Focus(window) => {
-                if let Some(window) = window_from_id(window) {
-                    tiler.focus(&window, t);
-                }
+                let _ = tiler.focus_by_id(window, t);
             }
 
             Request::FocusAbove => tiler.focus_above(t),
@@ -145,9 +164,11 @@ impl<T: 'static> Service<T> {
             Request::Resize(fork, handle) => tiler.fork_resize(fork, handle, t),
 
             Request::Swap(a, b) => {
-                if let Some((a, b)) = window_from_id(a).zip(window_from_id(b)) {
-                    tiler.swap(&a, &b, t);
-                }
+                let _ = tiler.swap_by_id(a, b, t);
+            }
+
+            Request::SwapActive(window) => {
+                tiler.swap_active_with_id(window, t);
             }
 
             Request::ToggleOrientation => tiler.toggle_orientation(t),
@@ -162,8 +183,6 @@ impl<T: 'static> Service<T> {
                 tiler.workspace_update(workspace, display, t);
             }
         }
-
-        self.tiler.events(t)
     }
 }
 
@@ -0,0 +1,81 @@
+// Copyright 2021 System76 <info@system76.com>
+// SPDX-License-Identifier: MPL-2.0
+
+use pop_tiler::*;
+use pop_tiler_service::{Request, Service};
+
+#[test]
+fn focus_unknown_window() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut service = Service::default();
+
+    let setup = vec![
+        Request::DisplayUpdate {
+            display: 0,
+            dimensions: Rect::new(0, 0, 1000, 800),
+        },
+        Request::WorkspaceUpdate {
+            workspace: 0,
+            display: 0,
+        },
+        Request::WorkspaceSwitch(0),
+        Request::Attach(WindowID(0, 0)),
+        Request::Attach(WindowID(0, 1)),
+        Request::Focus(WindowID(0, 1)),
+    ];
+
+    for request in setup {
+        let _ = service.handle(request, t).count();
+    }
+
+    // A window that was never attached is not focused, nor made known to the tiler.
+    let response: Vec<Event> = service.handle(Request::Focus(WindowID(0, 2)), t).collect();
+    assert!(response.is_empty(), "{:?}", response);
+
+    // And the service goes on to handle requests for windows that are.
+    let response: Vec<Event> = service.handle(Request::Focus(WindowID(0, 0)), t).collect();
+    assert!(response
+        .iter()
+        .any(|event| matches!(event, Event::Focus(WindowID(0, 0), _))));
+}
+
+#[test]
+fn attach_to_unknown_fork() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut service = Service::default();
+
+    let setup = vec![
+        Request::DisplayUpdate {
+            display: 0,
+            dimensions: Rect::new(0, 0, 1000, 800),
+        },
+        Request::WorkspaceUpdate {
+            workspace: 0,
+            display: 0,
+        },
+        Request::WorkspaceSwitch(0),
+        Request::Attach(WindowID(0, 0)),
+    ];
+
+    for request in setup {
+        let _ = service.handle(request, t).count();
+    }
+
+    let response: Vec<Event> = service
+        .handle(
+            Request::AttachToFork {
+                window: WindowID(0, 1),
+                fork: 99,
+                side: Side::Left,
+            },
+            t,
+        )
+        .collect();
+    assert!(response.is_empty(), "{:?}", response);
+
+    // The window that could not be placed was not made known to the tiler.
+    let response: Vec<Event> = service.handle(Request::Focus(WindowID(0, 1)), t).collect();
+    assert!(response.is_empty(), "{:?}", response);
+}
@@ -4,8 +4,8 @@
 use crate::fork::ForkPtr;
 use crate::stack::StackPtr;
 use crate::window::WindowPtr;
-use crate::Rect;
 use crate::Tiler;
+use crate::{Orientation, Rect};
 use qcell::TCellOwner;
 use std::rc::Rc;
 
@@ -41,6 +41,56 @@ impl<'a, T: 'static> Clone for BranchRef<'a, T> {
 impl<'a, T: 'static> Copy for BranchRef<'a, T> {}
 
 impl<T: 'static> Branch<T> {
+    /// Associate this branch with a new parent fork.
+    pub fn parent_set(&self, parent: &ForkPtr<T>, t: &mut TCellOwner<T>) {
+        match self {
+            Branch::Fork(ptr) => ptr.rw(t).parent = Some(parent.clone()),
+            Branch::Window(ptr) => ptr.fork_set(parent.clone(), t),
+            Branch::Stack(ptr) => {
+                ptr.rw(t).parent = parent.clone();
+                for window in ptr.ro(t).windows.clone() {
+                    window.fork_set(parent.clone(), t);
+                }
+            }
+        }
+    }
+
+    /// The least length that the windows in this branch ask for along this orientation.
+    pub fn min_length(&self, orientation: Orientation, t: &TCellOwner<T>) -> Option<u32> {
+        let length = |window: &WindowPtr<T>| {
+            window
+                .ro(t)
+                .min_size
+                .map(|(width, height)| match orientation {
+                    Orientation::Horizontal => width,
+                    Orientation::Vertical => height,
+                })
+        };
+
+        match self {
+            Branch::Window(window) => length(window),
+            Branch::Stack(stack) => stack.ro(t).windows.iter().filter_map(length).max(),
+            Branch::Fork(fork) => {
+                let fork = fork.ro(t);
+                let left = fork.left.min_length(orientation, t);
+                let right = fork
+                    .right
+                    .as_ref()
+                    .and_then(|r| r.min_length(orientation, t));
+
+                // Branches beside each other along the orientation need room for both.
+                if fork.orientation == orientation {
+                    match (left, right) {
+                        (None, None) => None,
+                        (left, right) => Some(left.unwrap_or(0) + right.unwrap_or(0)),
+                    }
+                } else {
+                    left.max(right)
+                }
+            }
+        }
+    }
+
     pub fn work_area_update(&self, tiler: &mut Tiler<T>, area: Rect, t: &mut TCellOwner<T>) {
         match self {
             Branch::Fork(ptr) => ptr.work_area_update(tiler, area, t),
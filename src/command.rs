@@ -0,0 +1,65 @@
+// Copyright 2021 System76 <info@system76.com>
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::{Rect, Side, WindowID};
+
+/// An operation of the tiler which addresses windows, forks, and displays by their IDs,
+/// as applied with [`crate::Tiler::apply`] and [`crate::Tiler::apply_batch`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    /// Attach a window to the tiler.
+    Attach(WindowID),
+    /// Attach a window to a specific branch of a fork.
+    AttachToFork {
+        window: WindowID,
+        fork: usize,
+        side: Side,
+    },
+    /// Detach a window from the tiler.
+    Detach(WindowID),
+    /// Insert or update the dimensions of a display.
+    DisplayUpdate { display: u32, dimensions: Rect },
+    /// Remove a display from the tree.
+    DisplayDetach(u32),
+    /// Make this window the actively-focused window.
+    Focus(WindowID),
+    /// Focus the window above the active window.
+    FocusAbove,
+    /// Focus the window below the active window.
+    FocusBelow,
+    /// Focus the display above the active one.
+    FocusDisplayAbove,
+    /// Focus the display below the active one.
+    FocusDisplayBelow,
+    /// Focus the display to the left of the active one.
+    FocusDisplayLeft,
+    /// Focus the display to the right of the active one.
+    FocusDisplayRight,
+    /// Focus the window to the left of the active one.
+    FocusLeft,
+    /// Focus the window to the right of the active one.
+    FocusRight,
+    /// Move the active window above.
+    MoveAbove,
+    /// Move the active window below.
+    MoveBelow,
+    /// Move the active window to the left.
+    MoveLeft,
+    /// Move the active window to the right.
+    MoveRight,
+    /// Toggle the orientation of the fork the window is attached to.
+    ToggleOrientation,
+    /// Toggle the stackability of the window.
+    ToggleStack,
+    /// Resize a fork with an updated split.
+    Resize(usize, u32),
+    /// Swap the positions of two windows.
+    Swap(WindowID, WindowID),
+    /// Swap the position of the active window with this window.
+    SwapActive(WindowID),
+    /// Switch to a different workspace.
+    WorkspaceSwitch(u32),
+    /// Associate a workspace with a display.
+    WorkspaceUpdate { workspace: u32, display: u32 },
+}
@@ -2,12 +2,28 @@
 // SPDX-License-Identifier: MPL-2.0
 
 use crate::workspace::WorkspacePtr;
-use crate::{Rect, Tiler};
+use crate::{Rect, RoundingMode, Tiler};
 use qcell::{TCell, TCellOwner};
 use std::collections::HashMap;
 use std::fmt::{self, Debug};
 use std::rc::Rc;
 
+/// How the contents of a display are rotated from the area reported by the compositor.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Transform {
+    Normal,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::Normal
+    }
+}
+
 /// A physical display, which has physical dimensions, and may have multiple workspaces associated with it.
 #[derive(Deref, DerefMut)]
 pub(crate) struct DisplayPtr<T: 'static>(Rc<TCell<T, Display<T>>>);
@@ -23,6 +39,10 @@ pub(crate) struct Display<T: 'static> {
     pub area: Rect,
     pub active: Option<u32>,
     pub workspaces: HashMap<u32, WorkspacePtr<T>>,
+    /// The logical pixel grid that placements are aligned to, and how they are aligned.
+    pub rounding: Option<(u32, RoundingMode)>,
+    /// The rotation of the display from its reported area.
+    pub transform: Transform,
 }
 
 impl<T: 'static> DisplayPtr<T> {
@@ -31,11 +51,22 @@ impl<T: 'static> DisplayPtr<T> {
             area,
             active: None,
             workspaces: HashMap::new(),
+            rounding: None,
+            transform: Transform::Normal,
         })))
     }
 
+    /// The area that windows are laid out in, with the transform of the display applied.
     pub fn area(&self, t: &TCellOwner<T>) -> Rect {
-        self.ro(t).area
+        let this = self.ro(t);
+        let area = this.area;
+
+        match this.transform {
+            Transform::Normal | Transform::Rotate180 => area,
+            Transform::Rotate90 | Transform::Rotate270 => {
+                Rect::new(area.x, area.y, area.height, area.width)
+            }
+        }
     }
 
     /// Assign a workspace to this display, removing the previous parent association of
@@ -103,6 +134,11 @@ impl<T: 'static> DisplayPtr<T> {
         }
     }
 
+    /// Recalculates the work area of every workspace attached to this display.
+    pub fn work_area_refresh(&self, tiler: &mut Tiler<T>, t: &mut TCellOwner<T>) {
+        self.work_area_update(tiler, self.ro(t).area, t);
+    }
+
     /// Updates the work area of every workspace attached to this display.
     pub fn work_area_update(&self, tiler: &mut Tiler<T>, area: Rect, t: &mut TCellOwner<T>) {
         // Update the area of this display.
@@ -115,7 +151,7 @@ impl<T: 'static> DisplayPtr<T> {
         // Apply the update to all forks in each workspace.
         for workspace in workspaces.values() {
             if let Some(ref fork) = workspace.fork(t) {
-                fork.work_area_update(tiler, area, t);
+                fork.work_area_update(tiler, workspace.area(t), t);
             }
         }
 
@@ -151,6 +187,7 @@ impl<'a, T> Debug for DisplayDebug<'a, T> {
             .collect();
         fmt.debug_struct("Display")
             .field("area", &info.area)
+            .field("transform", &info.transform)
             .field("active", &info.active)
             .field("workspaces", &workspaces)
             .finish()
@@ -2,27 +2,61 @@
 // SPDX-License-Identifier: MPL-2.0
 
 use crate::fork::ForkPtr;
-use crate::stack::{StackMovement, StackPtr};
-use crate::window::WindowPtr;
-use crate::{Orientation, Rect, WindowID};
+use crate::stack::{StackMovement, StackPtr, TabBarPosition};
+use crate::window::{Decoration, WindowPtr};
+use crate::{EdgeSet, Orientation, Rect, WindowID};
 use qcell::TCellOwner;
 use std::collections::{BTreeMap, HashMap};
-use std::rc::Rc;
 
 /// Instructs where to place a tiling component entity.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Placement {
     pub area: Rect,
     pub workspace: u32,
+    /// Where the entity was placed before, if previous rects are being emitted.
+    pub from: Option<Rect>,
+    /// Whether the placement only changes the gaps around the entity, such that it may be
+    /// animated from `from` if gap animation is enabled.
+    pub gap_transition: bool,
+}
+
+impl Placement {
+    /// The placement of an entity at `area` which was at `previous`, superseding the
+    /// placement queued for it since the last dispatch, if any.
+    ///
+    /// The rect from before the first placement since the dispatch is kept, to be reported
+    /// if previous rects are emitted or the placements include a gap transition.
+    pub(crate) fn superseding(
+        queued: Option<&Placement>,
+        area: Rect,
+        workspace: u32,
+        previous: Rect,
+        gap_transition: bool,
+    ) -> Self {
+        Placement {
+            area,
+            workspace,
+            from: queued.map_or(Some(previous), |placement| placement.from),
+            gap_transition: gap_transition
+                || queued.map_or(false, |placement| placement.gap_transition),
+        }
+    }
 }
 
 /// An event for the window manager to act upon.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug)]
 pub enum Event {
-    /// Focus this window.
-    Focus(WindowID),
+    /// Focus this window, with the serial of this focus change if serials are enabled, or
+    /// the serial that the window manager supplied when it focused the window itself.
+    Focus(WindowID, Option<u64>),
+
+    /// Highlight this region of the focused window.
+    FocusRect(Rect),
+
+    /// Where the next window attached would be placed, to be indicated until it is.
+    InsertPreview(Rect),
 
     /// Focus this workspace ID.
     FocusWorkspace(u32),
@@ -42,8 +76,12 @@ pub enum Event {
     /// Destroy the stack associated with this ID.
     StackDestroy(usize),
 
-    /// Alter the dimensions of an existing stack.
-    StackPlace(usize, Placement),
+    /// Where to show a copy of a stack on a display which mirrors the display of its
+    /// workspace, scaled from its placement on that display.
+    StackMirror(usize, u32, Rect),
+
+    /// Alter the dimensions of an existing stack, and where its tab bar is drawn.
+    StackPlace(usize, Placement, TabBarPosition),
 
     /// Raise this window of a stack to the top.
     /// Other windows in this stack should be hidden.
@@ -55,6 +93,19 @@ pub enum Event {
     // Change the visibility of a stack.
     StackVisibility(usize, bool),
 
+    /// On which edges of this window to draw its border.
+    WindowBorders(WindowID, EdgeSet),
+
+    /// Whether to draw a border around this window.
+    WindowDecoration(WindowID, Decoration),
+
+    /// Whether this window is demanding attention.
+    WindowUrgency(WindowID, bool),
+
+    /// Where to show a copy of a window on a display which mirrors the display of its
+    /// workspace, scaled from its placement on that display.
+    WindowMirror(WindowID, u32, Rect),
+
     /// Alter the dimensions of a window actor.
     WindowPlace(WindowID, Placement),
 
@@ -69,7 +120,7 @@ pub enum Event {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct ForkUpdate {
     /// On what workspace the fork resides.
     pub workspace: u32,
@@ -81,6 +132,68 @@ pub struct ForkUpdate {
     pub handle: u32,
 }
 
+/// How much the events of the tiler are coalesced before they are retrieved.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EventCompaction {
+    /// Every placement of a fork, stack, or window is reported, including those which were
+    /// superseded before the events were retrieved.
+    None,
+    /// Only the latest placement of each fork, stack, or window is reported.
+    PerFrame,
+    /// As with `PerFrame`, but placements and visibility changes which do not differ from
+    /// what was last reported are left out.
+    NetState,
+}
+
+impl Default for EventCompaction {
+    fn default() -> Self {
+        EventCompaction::PerFrame
+    }
+}
+
+/// The last reported placement and visibility of a stack.
+type StackState = (Option<(Placement, TabBarPosition)>, Option<bool>);
+
+/// The last reported placement and visibility of a window.
+type WindowState = (Option<Placement>, Option<bool>);
+
+/// The state of the forks, stacks, and windows, as it was last reported.
+#[derive(Default)]
+pub(crate) struct NetState {
+    forks: BTreeMap<usize, ForkUpdate>,
+    stacks: BTreeMap<usize, StackState>,
+    windows: HashMap<WindowID, WindowState>,
+}
+
+/// The placements of the stacks and windows, as they were last reported.
+#[derive(Default)]
+pub(crate) struct Placed {
+    stacks: BTreeMap<usize, (Placement, TabBarPosition)>,
+    windows: HashMap<WindowID, Placement>,
+}
+
+/// Whether a placement moves no edge of the last one reported further than `epsilon`,
+/// such that it may be left out.
+fn placement_within(last: Option<&Placement>, next: &Placement, epsilon: u32) -> bool {
+    let within = |a: u32, b: u32| (i64::from(a) - i64::from(b)).abs() <= i64::from(epsilon);
+
+    last.map_or(false, |last| {
+        last.workspace == next.workspace
+            && within(last.area.x, next.area.x)
+            && within(last.area.y, next.area.y)
+            && within(last.area.width, next.area.width)
+            && within(last.area.height, next.area.height)
+    })
+}
+
+/// Whether a placement differs from the last one reported, ignoring where it moved from.
+fn placement_changed(last: Option<&Placement>, next: &Placement) -> bool {
+    last.map_or(true, |last| {
+        last.area != next.area || last.workspace != next.workspace
+    })
+}
+
 #[derive(Default)]
 pub struct ForkEvents {
     pub destroy: bool,
@@ -89,7 +202,10 @@ pub struct ForkEvents {
 
 #[derive(Default)]
 pub struct WindowEvents {
+    pub borders: Option<EdgeSet>,
+    pub decoration: Option<Decoration>,
     pub place: Option<Placement>,
+    pub urgency: Option<bool>,
     pub visibility: Option<bool>,
 }
 
@@ -97,7 +213,7 @@ pub struct WindowEvents {
 pub struct StackEvents {
     pub destroy: bool,
     pub assignments: BTreeMap<WindowID, bool>,
-    pub place: Option<Placement>,
+    pub place: Option<(Placement, TabBarPosition)>,
     pub visibility: Option<bool>,
     pub raise: Option<WindowID>,
 }
@@ -107,6 +223,13 @@ pub(crate) struct EventQueue<T: 'static> {
     pub(crate) windows: HashMap<WindowID, WindowEvents>,
     pub(crate) stacks: BTreeMap<usize, StackEvents>,
     pub(crate) events: Vec<Event>,
+    pub(crate) compaction: EventCompaction,
+    net: NetState,
+    /// Placements which differ from the last reported by no more than this are left out.
+    pub(crate) placement_epsilon: u32,
+    placed: Placed,
+    /// Placements which were replaced before they were retrieved.
+    pub(crate) superseded: Vec<Event>,
     _marker: std::marker::PhantomData<T>,
 }
 
@@ -117,6 +240,11 @@ impl<T: 'static> Default for EventQueue<T> {
             windows: HashMap::new(),
             stacks: BTreeMap::new(),
             events: Vec::new(),
+            compaction: EventCompaction::default(),
+            net: NetState::default(),
+            placement_epsilon: 0,
+            placed: Placed::default(),
+            superseded: Vec::new(),
             _marker: std::marker::PhantomData,
         }
     }
@@ -124,15 +252,36 @@ impl<T: 'static> Default for EventQueue<T> {
 
 impl<T: 'static> EventQueue<T> {
     pub fn consume_events(&mut self) -> impl Iterator<Item = Event> + '_ {
+        let net = self.compaction == EventCompaction::NetState;
+        let mut net_forks = Some(&mut self.net.forks).filter(|_| net);
+        let mut net_stacks = Some(&mut self.net.stacks).filter(|_| net);
+        let mut net_windows = Some(&mut self.net.windows).filter(|_| net);
+
+        let epsilon = self.placement_epsilon;
+        let mut placed_stacks = Some(&mut self.placed.stacks).filter(|_| epsilon > 0);
+        let mut placed_windows = Some(&mut self.placed.windows).filter(|_| epsilon > 0);
+
         let fork_events = {
             let mut forks = BTreeMap::new();
             std::mem::swap(&mut self.forks, &mut forks);
 
-            forks.into_iter().filter_map(|(id, event)| {
+            forks.into_iter().filter_map(move |(id, event)| {
                 if event.destroy {
+                    if let Some(net) = net_forks.as_mut() {
+                        net.remove(&id);
+                    }
+
                     Some(Event::ForkDestroy(id))
                 } else {
-                    event.update.map(|update| Event::Fork(id, update))
+                    let update = event.update?;
+
+                    if let Some(net) = net_forks.as_mut() {
+                        if net.insert(id, update) == Some(update) {
+                            return None;
+                        }
+                    }
+
+                    Some(Event::Fork(id, update))
                 }
             })
         };
@@ -141,7 +290,49 @@ impl<T: 'static> EventQueue<T> {
             let mut stacks = BTreeMap::new();
             std::mem::swap(&mut self.stacks, &mut stacks);
 
-            stacks.into_iter().flat_map(|(a, events)| {
+            stacks.into_iter().flat_map(move |(a, mut events)| {
+                if let Some(placed) = placed_stacks.as_mut() {
+                    if events.destroy {
+                        placed.remove(&a);
+                    } else if let Some(next) = events.place {
+                        let last = placed.get(&a);
+                        if last.map_or(false, |last| last.1 == next.1)
+                            && placement_within(last.map(|last| &last.0), &next.0, epsilon)
+                        {
+                            events.place = None;
+                        } else {
+                            placed.insert(a, next);
+                        }
+                    }
+                }
+
+                if let Some(net) = net_stacks.as_mut() {
+                    if events.destroy {
+                        net.remove(&a);
+                    } else {
+                        let (place, visibility) = net.entry(a).or_default();
+
+                        if let Some(next) = events.place {
+                            let last = place.as_ref();
+                            if last.map_or(false, |last| last.1 == next.1)
+                                && !placement_changed(last.map(|last| &last.0), &next.0)
+                            {
+                                events.place = None;
+                            } else {
+                                *place = Some(next);
+                            }
+                        }
+
+                        if events.visibility.is_some() {
+                            if *visibility == events.visibility {
+                                events.visibility = None;
+                            } else {
+                                *visibility = events.visibility;
+                            }
+                        }
+                    }
+                }
+
                 let attachments = events.assignments.into_iter().map(move |(id, attached)| {
                     if attached {
                         Event::StackAssign(a, id)
@@ -156,7 +347,7 @@ impl<T: 'static> EventQueue<T> {
                     let placement = events
                         .place
                         .into_iter()
-                        .map(move |p| Event::StackPlace(a, p));
+                        .map(move |(p, tab_bar)| Event::StackPlace(a, p, tab_bar));
 
                     let visibility = events
                         .visibility
@@ -166,7 +357,12 @@ impl<T: 'static> EventQueue<T> {
                     Box::new(placement.chain(visibility))
                 };
 
-                iterator.chain(attachments)
+                let raise = events
+                    .raise
+                    .filter(|_| !events.destroy)
+                    .map(move |id| Event::StackRaise(a, id));
+
+                iterator.chain(attachments).chain(raise)
             })
         };
 
@@ -174,7 +370,37 @@ impl<T: 'static> EventQueue<T> {
             let mut windows = HashMap::new();
             std::mem::swap(&mut self.windows, &mut windows);
 
-            windows.into_iter().flat_map(|(a, events)| {
+            windows.into_iter().flat_map(move |(a, mut events)| {
+                if let Some(placed) = placed_windows.as_mut() {
+                    if let Some(next) = events.place {
+                        if placement_within(placed.get(&a), &next, epsilon) {
+                            events.place = None;
+                        } else {
+                            placed.insert(a, next);
+                        }
+                    }
+                }
+
+                if let Some(net) = net_windows.as_mut() {
+                    let (place, visibility) = net.entry(a).or_default();
+
+                    if let Some(next) = events.place {
+                        if placement_changed(place.as_ref(), &next) {
+                            *place = Some(next);
+                        } else {
+                            events.place = None;
+                        }
+                    }
+
+                    if events.visibility.is_some() {
+                        if *visibility == events.visibility {
+                            events.visibility = None;
+                        } else {
+                            *visibility = events.visibility;
+                        }
+                    }
+                }
+
                 let placement = events
                     .place
                     .into_iter()
@@ -185,31 +411,72 @@ impl<T: 'static> EventQueue<T> {
                     .into_iter()
                     .map(move |v| Event::WindowVisibility(a, v));
 
-                placement.chain(visibility)
+                let borders = events
+                    .borders
+                    .into_iter()
+                    .map(move |b| Event::WindowBorders(a, b));
+
+                let decoration = events
+                    .decoration
+                    .into_iter()
+                    .map(move |d| Event::WindowDecoration(a, d));
+
+                let urgency = events
+                    .urgency
+                    .into_iter()
+                    .map(move |u| Event::WindowUrgency(a, u));
+
+                placement
+                    .chain(visibility)
+                    .chain(borders)
+                    .chain(decoration)
+                    .chain(urgency)
             })
         };
 
-        fork_events
+        self.superseded
+            .drain(..)
+            .chain(fork_events)
             .chain(stack_events)
             .chain(window_events)
             .chain(self.events.drain(..))
     }
 
+    /// Forgets what was last reported, such that every placement and visibility which is
+    /// queued next is reported, even if it does not differ.
+    pub fn reported_reset(&mut self) {
+        self.net = NetState::default();
+        self.placed = Placed::default();
+    }
+
     /// Instruct the window manager that this fork was destroyed.
-    pub fn fork_destroy(&mut self, fork: &ForkPtr<T>) {
-        tracing::debug!("destroying Fork({:?})", Rc::as_ptr(fork));
-        self.forks
-            .entry(Rc::as_ptr(fork) as usize)
-            .or_default()
-            .destroy = true;
+    pub fn fork_destroy(&mut self, fork: &ForkPtr<T>, t: &TCellOwner<T>) {
+        let id = fork.id(t);
+        tracing::debug!("destroying Fork({})", id);
+        self.forks.entry(id).or_default().destroy = true;
+        self.net.forks.remove(&id);
+    }
+
+    /// Forgets a window which is no longer managed, along with the events queued for it,
+    /// such that a window attached later with the same ID is reported in full.
+    pub fn window_forget(&mut self, id: WindowID) {
+        self.windows.remove(&id);
+        self.net.windows.remove(&id);
+        self.placed.windows.remove(&id);
     }
 
     /// Instruct the window manager about this fork's dimensions and split handle.
     pub fn fork_update(&mut self, fork: &ForkPtr<T>, t: &TCellOwner<T>) {
-        self.forks
-            .entry(Rc::as_ptr(fork) as usize)
-            .or_default()
-            .update = Some({
+        let id = fork.id(t);
+        let events = self.forks.entry(id).or_default();
+
+        if self.compaction == EventCompaction::None {
+            if let Some(update) = events.update {
+                self.superseded.push(Event::Fork(id, update));
+            }
+        }
+
+        events.update = Some({
             let fork = fork.ro(t);
             ForkUpdate {
                 workspace: fork.workspace,
@@ -224,7 +491,7 @@ impl<T: 'static> EventQueue<T> {
     pub fn stack_assign(&mut self, stack: &StackPtr<T>, window: &WindowPtr<T>, t: &TCellOwner<T>) {
         *self
             .stacks
-            .entry(Rc::as_ptr(stack) as usize)
+            .entry(stack.id(t))
             .or_default()
             .assignments
             .entry(window.id(t))
@@ -235,7 +502,7 @@ impl<T: 'static> EventQueue<T> {
     pub fn stack_detach(&mut self, stack: &StackPtr<T>, window: &WindowPtr<T>, t: &TCellOwner<T>) {
         *self
             .stacks
-            .entry(Rc::as_ptr(stack) as usize)
+            .entry(stack.id(t))
             .or_default()
             .assignments
             .entry(window.id(t))
@@ -243,11 +510,11 @@ impl<T: 'static> EventQueue<T> {
     }
 
     /// Instruct the window manager that this stack was destroyed.
-    pub fn stack_destroy(&mut self, stack: &StackPtr<T>) {
-        self.stacks
-            .entry(Rc::as_ptr(stack) as usize)
-            .or_default()
-            .destroy = true;
+    pub fn stack_destroy(&mut self, stack: &StackPtr<T>, t: &TCellOwner<T>) {
+        let id = stack.id(t);
+        self.stacks.entry(id).or_default().destroy = true;
+        self.net.stacks.remove(&id);
+        self.placed.stacks.remove(&id);
     }
 
     /// Instruct the window manager to ensure that this window should be the visible one in the stack.
@@ -257,26 +524,48 @@ impl<T: 'static> EventQueue<T> {
         window: &WindowPtr<T>,
         t: &TCellOwner<T>,
     ) {
-        self.stacks
-            .entry(Rc::as_ptr(stack) as usize)
-            .or_default()
-            .raise = Some(window.id(t))
+        self.stacks.entry(stack.id(t)).or_default().raise = Some(window.id(t))
     }
 
-    pub fn stack_movement(&mut self, stack: &StackPtr<T>, movement: StackMovement) {
+    pub fn stack_movement(
+        &mut self,
+        stack: &StackPtr<T>,
+        movement: StackMovement,
+        t: &TCellOwner<T>,
+    ) {
         self.events
-            .push(Event::StackMovement(Rc::as_ptr(stack) as usize, movement));
+            .push(Event::StackMovement(stack.id(t), movement));
     }
 
-    /// Instruct the window manager about a placement of a stack.
-    pub fn stack_update(&mut self, stack: &StackPtr<T>, t: &TCellOwner<T>) {
+    /// Instruct the window manager about a placement of a stack, which was at `previous`,
+    /// tagged as a gap transition if it only changes the gaps around the stack.
+    pub fn stack_update(
+        &mut self,
+        stack: &StackPtr<T>,
+        tab_bar: TabBarPosition,
+        previous: Rect,
+        gap_transition: bool,
+        t: &TCellOwner<T>,
+    ) {
         let stack_ = stack.ro(t);
-        self.stacks
-            .entry(Rc::as_ptr(stack) as usize)
-            .or_default()
-            .place = Some(Placement {
-            area: stack_.area,
-            workspace: stack_.workspace,
-        });
+        let id = stack.id(t);
+        let events = self.stacks.entry(id).or_default();
+
+        if self.compaction == EventCompaction::None {
+            if let Some((placement, tab_bar)) = events.place {
+                self.superseded
+                    .push(Event::StackPlace(id, placement, tab_bar));
+            }
+        }
+
+        let placement = Placement::superseding(
+            events.place.as_ref().map(|(placement, _)| placement),
+            stack_.area,
+            stack_.workspace,
+            previous,
+            gap_transition,
+        );
+
+        events.place = Some((placement, tab_bar));
     }
 }
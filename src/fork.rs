@@ -5,7 +5,7 @@ pub(crate) use debug::ForkDebug;
 
 use super::branch::{Branch, BranchRef};
 use super::window::WindowPtr;
-use crate::{Rect, Tiler};
+use crate::{GapConfig, Rect, Tiler, WindowID};
 use either::Either;
 use qcell::{TCell, TCellOwner};
 use std::rc::Rc;
@@ -18,6 +18,54 @@ pub enum Orientation {
     Vertical,
 }
 
+impl Orientation {
+    /// Half of the length of an area along this orientation, where an even split lies.
+    pub(crate) fn half(self, area: Rect) -> u32 {
+        match self {
+            Orientation::Horizontal => area.width / 2,
+            Orientation::Vertical => area.height / 2,
+        }
+    }
+}
+
+/// A direct child of a fork, identified as it is in events.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ForkChild {
+    Window(WindowID),
+    Stack(usize),
+    Fork(usize),
+}
+
+impl ForkChild {
+    pub(crate) fn new<T: 'static>(branch: &Branch<T>, t: &TCellOwner<T>) -> Self {
+        match branch {
+            Branch::Window(window) => ForkChild::Window(window.id(t)),
+            Branch::Stack(stack) => ForkChild::Stack(stack.id(t)),
+            Branch::Fork(fork) => ForkChild::Fork(fork.id(t)),
+        }
+    }
+}
+
+/// A branch of a fork.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+/// A window could not be placed where it was requested.
+#[derive(Copy, Clone, Debug, Display, PartialEq, Eq)]
+pub enum PlacementError {
+    #[display(fmt = "fork {} does not exist", _0)]
+    ForkNotFound(usize),
+    #[display(fmt = "{:?} is already attached to a tree", _0)]
+    WindowInTree(WindowID),
+}
+
+impl std::error::Error for PlacementError {}
+
 #[derive(Deref, DerefMut, From)]
 pub(crate) struct ForkPtr<T: 'static>(Rc<TCell<T, Fork<T>>>);
 impl<T: 'static> Clone for ForkPtr<T> {
@@ -31,6 +79,24 @@ impl<T: 'static> ForkPtr<T> {
         Self(Rc::new(TCell::new(fork)))
     }
 
+    /// The ID that the tiler assigned to this fork.
+    pub fn id(&self, t: &TCellOwner<T>) -> usize {
+        self.ro(t).id
+    }
+
+    /// How many forks lie on the path from the root of the tree to this fork, inclusive.
+    pub fn depth(&self, t: &TCellOwner<T>) -> usize {
+        let mut depth = 1;
+        let mut fork = self.ro(t).parent.clone();
+
+        while let Some(parent) = fork {
+            depth += 1;
+            fork = parent.ro(t).parent.clone();
+        }
+
+        depth
+    }
+
     /// Locates the largest window in the fork, walking all of its branches.
     pub fn largest_window(&self, t: &TCellOwner<T>) -> Option<WindowPtr<T>> {
         let mut largest_area = 0;
@@ -70,10 +136,7 @@ impl<T: 'static> ForkPtr<T> {
     pub fn reset_orientation(&self, tiler: &mut Tiler<T>, t: &mut TCellOwner<T>) {
         let this = self.rw(t);
 
-        this.split_handle = match this.orientation {
-            Orientation::Horizontal => this.area.width / 2,
-            Orientation::Vertical => this.area.height / 2,
-        };
+        this.split_handle = this.orientation.half(this.area);
 
         let preferred = preferred_orientation(this.area);
 
@@ -88,8 +151,8 @@ impl<T: 'static> ForkPtr<T> {
 
         let area = this.area;
         this.split_handle = match this.orientation {
-            Orientation::Horizontal => split.min(area.width),
-            Orientation::Vertical => split.min(area.height),
+            Orientation::Horizontal => clamp_split(split, area.width, tiler.min_split_pixels),
+            Orientation::Vertical => clamp_split(split, area.height, tiler.min_split_pixels),
         };
 
         self.work_area_refresh(tiler, t);
@@ -102,19 +165,17 @@ impl<T: 'static> ForkPtr<T> {
         this.split_handle = match this.orientation {
             Orientation::Horizontal => {
                 this.orientation = Orientation::Vertical;
-                let ratio = (this.split_handle * 100) / this.area.width;
-                (this.area.height * ratio) / 100
+                rescale_split(this.split_handle, this.area.width, this.area.height)
             }
 
             Orientation::Vertical => {
                 this.orientation = Orientation::Horizontal;
-                let ratio = (this.split_handle * 100) / this.area.height;
-                (this.area.width * ratio) / 100
+                rescale_split(this.split_handle, this.area.height, this.area.width)
             }
         };
 
         // Swap branches if a fork has had its orientation toggled twice.
-        if this.orientation_toggled {
+        if this.orientation_toggled && tiler.swap_on_double_toggle {
             if let Some(right) = this.right.as_mut() {
                 std::mem::swap(&mut this.left, right);
             }
@@ -125,118 +186,115 @@ impl<T: 'static> ForkPtr<T> {
         self.work_area_refresh(tiler, t);
     }
 
-    /// Swaps a window owned by this fork with a different window.
-    pub fn swap(&self, our: &WindowPtr<T>, their: &WindowPtr<T>, t: &mut TCellOwner<T>) {
-        let this = self.rw(t);
-        if let Branch::Window(ref mut window) = this.left {
-            if Rc::ptr_eq(window, our) {
-                this.left = Branch::Window(their.clone());
-                return;
-            }
-        }
-
-        if let Some(Branch::Window(ref mut window)) = this.right {
-            if Rc::ptr_eq(window, our) {
-                this.right = Some(Branch::Window(their.clone()));
-            }
-        }
-    }
-
-    /// Generator which locates all windows in this fork, but does allocate.
+    /// Generator which locates all windows in this fork in depth-first order, but does allocate.
     pub fn windows<'a>(&self, t: &'a TCellOwner<T>) -> impl Iterator<Item = WindowPtr<T>> + 'a {
-        let mut forks: Vec<ForkPtr<T>> = vec![self.clone()];
-        let mut branches: Vec<Branch<T>> = Vec::new();
+        let mut branches: Vec<Branch<T>> = vec![Branch::Fork(self.clone())];
         let mut windows: Vec<WindowPtr<T>> = Vec::new();
 
-        std::iter::from_fn(move || {
-            loop {
-                if let Some(window) = windows.pop() {
-                    return Some(window);
-                }
-
-                if let Some(branch) = branches.pop() {
-                    match branch {
-                        Branch::Window(window) => {
-                            return Some(window);
-                        }
-
-                        Branch::Fork(fork) => {
-                            forks.push(fork.clone());
-                        }
+        std::iter::from_fn(move || loop {
+            if let Some(window) = windows.pop() {
+                return Some(window);
+            }
 
-                        Branch::Stack(stack) => {
-                            windows.extend_from_slice(&stack.ro(t).windows);
-                            return windows.pop();
-                        }
-                    }
+            match branches.pop()? {
+                Branch::Window(window) => {
+                    return Some(window);
                 }
 
-                if let Some(fork) = forks.pop() {
-                    branches.push(fork.ro(t).left.clone());
+                // Right is pushed first so that the left branch is visited first.
+                Branch::Fork(fork) => {
+                    let fork = fork.ro(t);
 
-                    if let Some(right) = fork.ro(t).right.clone() {
+                    if let Some(right) = fork.right.clone() {
                         branches.push(right);
                     }
 
-                    continue;
+                    branches.push(fork.left.clone());
                 }
 
-                break;
+                Branch::Stack(stack) => {
+                    windows.extend(stack.ro(t).windows.iter().rev().cloned());
+                }
             }
-
-            None
         })
     }
 
+    /// Assigns this fork, and every fork, stack, and window beneath it, to a workspace.
+    pub fn workspace_set(&self, workspace: u32, t: &mut TCellOwner<T>) {
+        let mut forks = vec![self.clone()];
+
+        while let Some(fork) = forks.pop() {
+            fork.rw(t).workspace = workspace;
+
+            let fork_ = fork.ro(t);
+            let branches: Vec<Branch<T>> = std::iter::once(fork_.left.clone())
+                .chain(fork_.right.clone())
+                .collect();
+
+            for branch in branches {
+                match branch {
+                    Branch::Fork(fork) => forks.push(fork),
+                    Branch::Stack(stack) => {
+                        stack.rw(t).workspace = workspace;
+                        for window in stack.ro(t).windows.clone() {
+                            window.rw(t).workspace = workspace;
+                        }
+                    }
+                    Branch::Window(window) => window.rw(t).workspace = workspace,
+                }
+            }
+        }
+    }
+
     /// Recalculate the work areas of the fork's branches.
     pub fn work_area_refresh(&self, tiler: &mut Tiler<T>, t: &mut TCellOwner<T>) {
         self.work_area_update(tiler, self.ro(t).area, t)
     }
 
     /// Update the work area of the fork and its branches.
-    #[allow(clippy::many_single_char_names)]
     pub fn work_area_update(&self, tiler: &mut Tiler<T>, area: Rect, t: &mut TCellOwner<T>) {
+        // Trees changed in bulk are placed once, after every change is made.
+        if tiler.refresh_deferred {
+            return;
+        }
+
         tracing::debug!("assigning fork to {:?}", area);
+
         let mut left_rect = area;
         let left_branch: Branch<T>;
         let mut right_branch: Option<(Branch<T>, Rect)> = None;
 
+        let gaps = tiler
+            .workspaces
+            .get(&self.ro(t).workspace)
+            .map_or_else(GapConfig::default, |workspace| workspace.ro(t).gaps);
+
         {
             let this = self.rw(t);
 
             // Update the location of the split in the fork
             this.split_handle = match this.orientation {
-                Orientation::Horizontal => {
-                    let ratio = this.split_handle * 100 / this.area.width;
-                    area.width * ratio / 100
-                }
-
-                Orientation::Vertical => {
-                    let ratio = this.split_handle * 100 / this.area.height;
-                    area.height * ratio / 100
-                }
+                Orientation::Horizontal => clamp_split(
+                    rescale_split(this.split_handle, this.area.width, area.width),
+                    area.width,
+                    tiler.min_split_pixels,
+                ),
+
+                Orientation::Vertical => clamp_split(
+                    rescale_split(this.split_handle, this.area.height, area.height),
+                    area.height,
+                    tiler.min_split_pixels,
+                ),
             };
 
             left_branch = this.left.clone();
 
             if let Some(right) = this.right.clone() {
-                let x = area.x;
-                let y = area.y;
-                let w = area.width;
-                let h = area.height;
-                let r = this.split_handle;
-
-                match this.orientation {
-                    Orientation::Vertical => {
-                        left_rect = Rect::new(x, y, w, r);
-                        right_branch = Some((right, Rect::new(x, y + r, w, h - r)));
-                    }
+                let gap = gaps.between(&this.left, &right);
 
-                    Orientation::Horizontal => {
-                        left_rect = Rect::new(x, y, r, h);
-                        right_branch = Some((right, Rect::new(x + r, y, w - r, h)));
-                    }
-                }
+                let (left, right_rect) = split_rect(area, this.orientation, this.split_handle, gap);
+                left_rect = left;
+                right_branch = Some((right, right_rect));
             }
 
             this.area = area;
@@ -263,6 +321,9 @@ impl<T: 'static> ForkPtr<T> {
 /// A branch may contain a window, a stack, or another fork. The dimensions of a fork are
 /// split between the two branches vertically or horizontally.
 pub(crate) struct Fork<T: 'static> {
+    /// The ID of this fork, assigned when it is registered with the tiler.
+    pub id: usize,
+
     /// The position and dimensions of this fork and its children.
     pub area: Rect,
 
@@ -292,12 +353,10 @@ impl<T: 'static> Fork<T> {
     pub fn new(area: Rect, left: Branch<T>, workspace: u32) -> Self {
         let orientation = preferred_orientation(area);
 
-        let split_handle = match orientation {
-            Orientation::Horizontal => area.x_center() - 1,
-            Orientation::Vertical => area.y_center() - 1,
-        };
+        let split_handle = orientation.half(area);
 
         Self {
+            id: 0,
             area,
             left,
             right: None,
@@ -341,7 +400,51 @@ impl<T: 'static> Drop for Fork<T> {
     }
 }
 
-fn preferred_orientation(rect: Rect) -> Orientation {
+/// Keeps both sides of a split at least `min` pixels long, splitting evenly if they can't be.
+fn clamp_split(split: u32, length: u32, min: u32) -> u32 {
+    if min.saturating_mul(2) > length {
+        length / 2
+    } else {
+        split.max(min).min(length - min)
+    }
+}
+
+/// Moves a split to the same proportion of a new length.
+fn rescale_split(split: u32, from: u32, to: u32) -> u32 {
+    (u64::from(split) * u64::from(to) / u64::from(from.max(1))) as u32
+}
+
+/// The areas of the left and right branches of a fork with this area and split, with the
+/// gap divided between the two sides of the split.
+#[allow(clippy::many_single_char_names)]
+pub(crate) fn split_rect(
+    area: Rect,
+    orientation: Orientation,
+    split: u32,
+    gap: u32,
+) -> (Rect, Rect) {
+    let x = area.x;
+    let y = area.y;
+    let w = area.width;
+    let h = area.height;
+    let r = split;
+
+    let (a, b) = (gap / 2, gap - gap / 2);
+
+    match orientation {
+        Orientation::Vertical => (
+            Rect::new(x, y, w, r.saturating_sub(a)),
+            Rect::new(x, y + r + b, w, (h - r).saturating_sub(b)),
+        ),
+
+        Orientation::Horizontal => (
+            Rect::new(x, y, r.saturating_sub(a), h),
+            Rect::new(x + r + b, y, (w - r).saturating_sub(b), h),
+        ),
+    }
+}
+
+pub(crate) fn preferred_orientation(rect: Rect) -> Orientation {
     if rect.height > rect.width {
         Orientation::Vertical
     } else {
@@ -349,11 +452,26 @@ fn preferred_orientation(rect: Rect) -> Orientation {
     }
 }
 
+/// The orientation and split that [`ForkPtr::reset_orientation`] gives a fork with this
+/// area and orientation.
+pub(crate) fn reset_split(orientation: Orientation, area: Rect) -> (Orientation, u32) {
+    match (orientation, preferred_orientation(area)) {
+        (Orientation::Horizontal, Orientation::Vertical) => (
+            Orientation::Vertical,
+            rescale_split(orientation.half(area), area.width, area.height),
+        ),
+        (Orientation::Vertical, Orientation::Horizontal) => (
+            Orientation::Horizontal,
+            rescale_split(orientation.half(area), area.height, area.width),
+        ),
+        _ => (orientation, orientation.half(area)),
+    }
+}
+
 mod debug {
     use super::{Branch, ForkPtr};
     use qcell::TCellOwner;
     use std::fmt::{self, Debug};
-    use std::rc::Rc;
 
     pub(crate) struct ForkDebug<'a, T: 'static> {
         pub fork: &'a ForkPtr<T>,
@@ -378,13 +496,13 @@ mod debug {
 
             let fork = self.fork.ro(self.t);
 
-            let left = self.fork.ro(self.t).parent.as_ref().map(|p| Rc::as_ptr(&p));
+            let parent = fork.parent.as_ref().map(|p| p.id(self.t));
 
             let right = fork.right.as_ref().map(|branch| as_debug(branch, self.t));
 
             fmt.debug_struct("Fork")
-                .field("ptr", &Rc::as_ptr(self.fork))
-                .field("parent", &left)
+                .field("id", &fork.id)
+                .field("parent", &parent)
                 .field("orientation", &fork.orientation)
                 .field("left", &as_debug(&fork.left, self.t))
                 .field("right", &right)
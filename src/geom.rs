@@ -4,13 +4,19 @@
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 pub struct Point {
-    x: u32,
-    y: u32,
+    pub x: u32,
+    pub y: u32,
 }
 
 impl Point {
+    pub fn new(x: u32, y: u32) -> Self {
+        Self { x, y }
+    }
+
     pub fn distance(self, other: Point) -> f64 {
-        (((other.x - self.x).pow(2) + (other.y - self.y).pow(2)) as f64).sqrt()
+        let x = f64::from(other.x) - f64::from(self.x);
+        let y = f64::from(other.y) - f64::from(self.y);
+        (x * x + y * y).sqrt()
     }
 
     pub fn distance_from_rect(&self, rect: &Rect) -> f64 {
@@ -21,7 +27,70 @@ impl Point {
     }
 }
 
+/// Distances to move each edge of a rect inward.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Insets {
+    pub top: u32,
+    pub bottom: u32,
+    pub left: u32,
+    pub right: u32,
+}
+
+/// A choice of each edge of a rect.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct EdgeSet {
+    pub top: bool,
+    pub bottom: bool,
+    pub left: bool,
+    pub right: bool,
+}
+
+impl EdgeSet {
+    /// Every edge.
+    pub const ALL: EdgeSet = EdgeSet {
+        top: true,
+        bottom: true,
+        left: true,
+        right: true,
+    };
+}
+
+/// How placements are aligned to the physical pixels of a scaled display.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RoundingMode {
+    /// Move each edge to the physical pixel before it.
+    Floor,
+    /// Move each edge to the nearest physical pixel.
+    Round,
+    /// Move each edge inward to the nearest physical pixel.
+    SnapToGrid,
+}
+
+/// The smallest logical distance which is a whole number of physical pixels at this scale.
+pub(crate) fn pixel_grid(scale: f64) -> u32 {
+    (1..=120)
+        .find(|&grid| {
+            let physical = f64::from(grid) * scale;
+            (physical - physical.round()).abs() < 1e-6
+        })
+        .unwrap_or(1)
+}
+
 /// The positioning and dimensions of a rectangular object.
+/// A translation was given which would move a rect to a negative coordinate.
+#[derive(Copy, Clone, Debug, Display, PartialEq, Eq)]
+#[display(
+    fmt = "translating by ({}, {}) would leave a negative coordinate",
+    _0,
+    _1
+)]
+pub struct NegativeCoordinate(pub i32, pub i32);
+
+impl std::error::Error for NegativeCoordinate {}
+
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 pub struct Rect {
@@ -45,12 +114,90 @@ impl Rect {
         self.width * self.height
     }
 
+    /// Moves the rect by an offset, if it keeps both of its coordinates positive.
+    pub fn translate(self, dx: i32, dy: i32) -> Result<Rect, NegativeCoordinate> {
+        let x = u32::try_from(i64::from(self.x) + i64::from(dx));
+        let y = u32::try_from(i64::from(self.y) + i64::from(dy));
+
+        match (x, y) {
+            (Ok(x), Ok(y)) => Ok(Rect::new(x, y, self.width, self.height)),
+            _ => Err(NegativeCoordinate(dx, dy)),
+        }
+    }
+
+    /// Moves each edge of the rect inward, without letting it go past the opposite edge.
+    pub fn inset(self, insets: Insets) -> Rect {
+        let left = insets.left.min(self.width);
+        let top = insets.top.min(self.height);
+
+        Rect::new(
+            self.x + left,
+            self.y + top,
+            (self.width - left).saturating_sub(insets.right),
+            (self.height - top).saturating_sub(insets.bottom),
+        )
+    }
+
+    /// Whether the point lies within this rect.
+    pub fn contains(&self, point: Point) -> bool {
+        point.x >= self.x && point.x < self.x_end() && point.y >= self.y && point.y < self.y_end()
+    }
+
+    /// Moves the edges of the rect onto multiples of the grid.
+    pub(crate) fn align_to_grid(self, grid: u32, mode: RoundingMode) -> Rect {
+        let floor = |value: u32| value - value % grid;
+        let ceil = |value: u32| floor(value + grid - 1);
+        let round = |value: u32| floor(value + grid / 2);
+
+        let (x, y, x_end, y_end) = match mode {
+            RoundingMode::Floor => (
+                floor(self.x),
+                floor(self.y),
+                floor(self.x_end()),
+                floor(self.y_end()),
+            ),
+            RoundingMode::Round => (
+                round(self.x),
+                round(self.y),
+                round(self.x_end()),
+                round(self.y_end()),
+            ),
+            RoundingMode::SnapToGrid => (
+                ceil(self.x),
+                ceil(self.y),
+                floor(self.x_end()),
+                floor(self.y_end()),
+            ),
+        };
+
+        Rect::new(x, y, x_end.saturating_sub(x), y_end.saturating_sub(y))
+    }
+
+    /// Moves the rect so that at least `min` pixels of its width and height lie within
+    /// the bounds, or all of it if it is smaller than that.
+    pub(crate) fn keep_visible(self, bounds: Rect, min: u32) -> Rect {
+        let width = min.min(self.width).min(bounds.width);
+        let height = min.min(self.height).min(bounds.height);
+
+        let x = self
+            .x
+            .max((bounds.x + width).saturating_sub(self.width))
+            .min(bounds.x_end() - width);
+
+        let y = self
+            .y
+            .max((bounds.y + height).saturating_sub(self.height))
+            .min(bounds.y_end() - height);
+
+        Rect::new(x, y, self.width, self.height)
+    }
+
     pub fn distance_downward(&self, other: &Rect) -> f64 {
         self.south().distance(other.north())
     }
 
     pub fn distance_eastward(&self, other: &Rect) -> f64 {
-        self.west().distance(other.east())
+        self.east().distance(other.west())
     }
 
     pub fn distance_upward(&self, other: &Rect) -> f64 {
@@ -58,7 +205,7 @@ impl Rect {
     }
 
     pub fn distance_westward(&self, other: &Rect) -> f64 {
-        self.east().distance(other.west())
+        self.west().distance(other.east())
     }
 
     pub fn is_below(&self, other: &Rect) -> bool {
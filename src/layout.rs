@@ -0,0 +1,303 @@
+// Copyright 2021 System76 <info@system76.com>
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::branch::Branch;
+use crate::fork::{Fork, ForkPtr, Orientation};
+use crate::stack::StackPtr;
+use crate::window::{WindowID, WindowPtr};
+use crate::{Rect, Tiler};
+use qcell::TCellOwner;
+
+/// The tree of a workspace and the windows in it, which may be restored later.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WorkspaceSnapshot {
+    pub root: Layout,
+}
+
+/// The split structure of a tree, and the windows that were in it.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Layout {
+    Window(WindowID),
+    Stack {
+        /// The windows of the stack, in tab order.
+        windows: Vec<WindowID>,
+        /// The window whose tab was raised.
+        active: WindowID,
+    },
+    Fork(Box<ForkLayout>),
+}
+
+/// The orientation and split of a fork in a layout.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ForkLayout {
+    /// The region the fork occupied, which its split is rescaled from.
+    pub area: Rect,
+    pub orientation: Orientation,
+    pub split_handle: u32,
+    pub left: Layout,
+    pub right: Option<Layout>,
+}
+
+impl Layout {
+    /// Records the structure of the tree beneath this fork.
+    pub(crate) fn capture<T>(fork: &ForkPtr<T>, t: &TCellOwner<T>) -> Self {
+        let fork = fork.ro(t);
+
+        Layout::Fork(Box::new(ForkLayout {
+            area: fork.area,
+            orientation: fork.orientation,
+            split_handle: fork.split_handle,
+            left: Self::from_branch(&fork.left, t),
+            right: fork.right.as_ref().map(|right| Self::from_branch(right, t)),
+        }))
+    }
+
+    fn from_branch<T>(branch: &Branch<T>, t: &TCellOwner<T>) -> Self {
+        match branch {
+            Branch::Window(window) => Layout::Window(window.id(t)),
+            Branch::Stack(stack) => {
+                let stack = stack.ro(t);
+                Layout::Stack {
+                    windows: stack.windows.iter().map(|window| window.id(t)).collect(),
+                    active: stack.active.id(t),
+                }
+            }
+            Branch::Fork(fork) => Self::capture(fork, t),
+        }
+    }
+
+    /// How many windows are needed to fill every slot of this layout, as a template.
+    pub(crate) fn slots(&self) -> usize {
+        match self {
+            Layout::Window(_) | Layout::Stack { .. } => 1,
+            Layout::Fork(fork) => fork.left.slots() + fork.right.as_ref().map_or(0, Layout::slots),
+        }
+    }
+
+    /// This layout as a template filled by these windows, one to a slot in depth-first
+    /// order. Forks are left out until a window can fill their right branch.
+    fn fill(&self, windows: &mut Vec<WindowID>) -> Option<Self> {
+        match self {
+            Layout::Window(_) => Some(Layout::Window(windows.pop()?)),
+
+            Layout::Stack { .. } => {
+                let window = windows.pop()?;
+                Some(Layout::Stack {
+                    windows: vec![window],
+                    active: window,
+                })
+            }
+
+            Layout::Fork(fork) => {
+                if windows.len() <= fork.left.slots() {
+                    return fork.left.fill(windows);
+                }
+
+                let left = fork.left.fill(windows)?;
+                let right = fork.right.as_ref().and_then(|right| right.fill(windows));
+
+                Some(Layout::Fork(Box::new(ForkLayout {
+                    area: fork.area,
+                    orientation: fork.orientation,
+                    split_handle: fork.split_handle,
+                    left,
+                    right,
+                })))
+            }
+        }
+    }
+
+    /// Removes the windows that fail this test, and the branches left empty without them.
+    /// Forks left with a single branch are replaced by that branch.
+    pub(crate) fn retain(self, keep: &mut impl FnMut(WindowID) -> bool) -> Option<Self> {
+        match self {
+            Layout::Window(id) => Some(Layout::Window(id)).filter(|_| keep(id)),
+
+            Layout::Stack {
+                mut windows,
+                active,
+            } => {
+                windows.retain(|&id| keep(id));
+                let active = if windows.contains(&active) {
+                    active
+                } else {
+                    *windows.first()?
+                };
+
+                Some(Layout::Stack { windows, active })
+            }
+
+            Layout::Fork(fork) => {
+                let ForkLayout {
+                    area,
+                    orientation,
+                    split_handle,
+                    left,
+                    right,
+                } = *fork;
+
+                let left = left.retain(keep);
+                let right = right.and_then(|right| right.retain(keep));
+
+                match (left, right) {
+                    (Some(left), Some(right)) => Some(Layout::Fork(Box::new(ForkLayout {
+                        area,
+                        orientation,
+                        split_handle,
+                        left,
+                        right: Some(right),
+                    }))),
+                    (Some(branch), None) | (None, Some(branch)) => Some(branch),
+                    (None, None) => None,
+                }
+            }
+        }
+    }
+
+    /// The windows of this layout, in depth-first order.
+    pub(crate) fn window_ids(&self) -> Vec<WindowID> {
+        match self {
+            Layout::Window(id) => vec![*id],
+            Layout::Stack { windows, .. } => windows.clone(),
+            Layout::Fork(fork) => {
+                let mut ids = fork.left.window_ids();
+                if let Some(right) = fork.right.as_ref() {
+                    ids.extend(right.window_ids());
+                }
+                ids
+            }
+        }
+    }
+
+    /// Arranges windows into a new tree in the shape of this layout as a template, filling
+    /// slots in depth-first order. Forks are left out until a window can fill their right
+    /// branch.
+    pub(crate) fn build_template<T>(
+        &self,
+        tiler: &mut Tiler<T>,
+        area: Rect,
+        workspace: u32,
+        windows: Vec<WindowPtr<T>>,
+        t: &mut TCellOwner<T>,
+    ) -> Option<ForkPtr<T>> {
+        let mut ids: Vec<WindowID> = windows.iter().rev().map(|window| window.id(t)).collect();
+        self.fill(&mut ids)?.build(tiler, area, workspace, t)
+    }
+
+    /// Arranges the windows of the tiler into a new tree in the shape of this layout.
+    /// Every window in the layout must be managed by the tiler, and detached from its tree.
+    pub(crate) fn build<T>(
+        &self,
+        tiler: &mut Tiler<T>,
+        area: Rect,
+        workspace: u32,
+        t: &mut TCellOwner<T>,
+    ) -> Option<ForkPtr<T>> {
+        match self {
+            Layout::Fork(fork) => fork.build(tiler, None, workspace, t),
+
+            // A single window or stack still needs a fork to hold it.
+            branch => {
+                let first = *branch.window_ids().first()?;
+                let first = tiler.windows.get(&first)?.clone();
+                let root = ForkPtr::new(Fork::new(area, Branch::Window(first), workspace));
+                tiler.fork_register(root.clone(), t);
+
+                let left = branch.build_branch(tiler, &root, workspace, t)?;
+                root.rw(t).left = left;
+
+                Some(root)
+            }
+        }
+    }
+
+    fn build_branch<T>(
+        &self,
+        tiler: &mut Tiler<T>,
+        parent: &ForkPtr<T>,
+        workspace: u32,
+        t: &mut TCellOwner<T>,
+    ) -> Option<Branch<T>> {
+        match self {
+            Layout::Window(id) => {
+                let window = tiler.windows.get(id)?.clone();
+                window.fork_set(parent.clone(), t);
+                Some(Branch::Window(window))
+            }
+
+            Layout::Stack { windows, active } => {
+                let windows: Vec<WindowPtr<T>> = windows
+                    .iter()
+                    .filter_map(|id| tiler.windows.get(id).cloned())
+                    .collect();
+
+                let (first, rest) = windows.split_first()?;
+                first.fork_set(parent.clone(), t);
+
+                let stack = StackPtr::new(first, parent.clone(), t);
+                tiler.stack_register(stack.clone(), t);
+                tiler.event_queue.stack_assign(&stack, first, t);
+
+                for window in rest {
+                    window.fork_set(parent.clone(), t);
+                    stack.attach(window, t);
+                    tiler.event_queue.stack_assign(&stack, window, t);
+                }
+
+                if let Some(active) = tiler.windows.get(active) {
+                    stack.rw(t).active = active.clone();
+                }
+
+                Some(Branch::Stack(stack))
+            }
+
+            Layout::Fork(fork) => {
+                let fork = fork.build(tiler, Some(parent.clone()), workspace, t)?;
+                Some(Branch::Fork(fork))
+            }
+        }
+    }
+}
+
+impl ForkLayout {
+    fn build<T>(
+        &self,
+        tiler: &mut Tiler<T>,
+        parent: Option<ForkPtr<T>>,
+        workspace: u32,
+        t: &mut TCellOwner<T>,
+    ) -> Option<ForkPtr<T>> {
+        // The first window holds the left branch until the branch is built.
+        let first = *self.left.window_ids().first()?;
+        let first = tiler.windows.get(&first)?.clone();
+
+        let fork = ForkPtr::new(Fork {
+            id: 0,
+            area: self.area,
+            parent,
+            left: Branch::Window(first),
+            right: None,
+            workspace,
+            orientation: self.orientation,
+            split_handle: self.split_handle,
+            orientation_toggled: false,
+        });
+
+        tiler.fork_register(fork.clone(), t);
+
+        let left = self.left.build_branch(tiler, &fork, workspace, t)?;
+        let right = match self.right.as_ref() {
+            Some(right) => right.build_branch(tiler, &fork, workspace, t),
+            None => None,
+        };
+
+        let fork_ = fork.rw(t);
+        fork_.left = left;
+        fork_.right = right;
+
+        Some(fork)
+    }
+}
@@ -9,20 +9,30 @@ extern crate derive_more;
 extern crate serde;
 
 mod branch;
+mod command;
 mod display;
 mod events;
 mod fork;
 mod geom;
+mod layout;
+mod owned;
 mod stack;
 mod tiler;
 mod window;
 mod workspace;
 
-pub use self::events::{Event, ForkUpdate, Placement};
-pub use self::fork::Orientation;
-pub use self::geom::{Point, Rect};
-pub use self::stack::StackMovement;
-pub use self::tiler::Tiler;
-pub use self::window::{WindowID, WindowPtr};
+pub use self::command::Command;
+pub use self::display::Transform;
+pub use self::events::{Event, EventCompaction, ForkUpdate, Placement};
+pub use self::fork::{ForkChild, Orientation, PlacementError, Side};
+pub use self::geom::{EdgeSet, Insets, NegativeCoordinate, Point, Rect, RoundingMode};
+pub use self::layout::{ForkLayout, Layout, WorkspaceSnapshot};
+pub use self::owned::OwnedTiler;
+pub use self::stack::{NewStackFocus, StackMovement, StackRefocus, StackTabOrder, TabBarPosition};
+pub use self::tiler::{
+    AttachDecision, DetachRefocus, Direction, GapConfig, ResizeMode, SwapFocusPolicy, Tiler,
+    TreeInconsistency, WorkspaceFocusPolicy,
+};
+pub use self::window::{Decoration, Gravity, IdInUse, WindowID, WindowNotFound, WindowPtr};
 
 pub use qcell::TCellOwner;
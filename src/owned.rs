@@ -0,0 +1,135 @@
+// Copyright 2021 System76 <info@system76.com>
+// SPDX-License-Identifier: MPL-2.0
+
+use crate::{Direction, Event, Rect, Tiler, WindowID, WindowNotFound};
+use qcell::TCellOwner;
+
+/// A tiler which owns the token of its cells, for embedders with a single tiler.
+///
+/// Only the operations which address windows by their IDs are provided here: attaching,
+/// detaching, focusing, moving, and switching workspaces, as a simple embedder drives a
+/// tiler. The rest of the API takes window pointers or cell references which cannot
+/// outlive a borrow of the token, and is reached through [`OwnedTiler::parts`] instead.
+pub struct OwnedTiler<T: 'static> {
+    tiler: Tiler<T>,
+    t: TCellOwner<T>,
+}
+
+impl<T: 'static> Default for OwnedTiler<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: 'static> OwnedTiler<T> {
+    /// Creates a tiler with the token of the marker type `T`.
+    ///
+    /// # Panics
+    ///
+    /// If another token of the marker type `T` exists. Use [`OwnedTiler::try_new`] where
+    /// that may be the case.
+    pub fn new() -> Self {
+        Self {
+            tiler: Tiler::default(),
+            t: TCellOwner::new(),
+        }
+    }
+
+    /// Creates a tiler with the token of the marker type `T`, unless another token of that
+    /// marker type exists.
+    pub fn try_new() -> Option<Self> {
+        Some(Self {
+            tiler: Tiler::default(),
+            t: TCellOwner::try_new()?,
+        })
+    }
+
+    /// The tiler and its token, for methods that are not provided here.
+    pub fn parts(&mut self) -> (&mut Tiler<T>, &mut TCellOwner<T>) {
+        (&mut self.tiler, &mut self.t)
+    }
+
+    /// The tiler, for queries which do not need the token.
+    pub fn tiler(&self) -> &Tiler<T> {
+        &self.tiler
+    }
+
+    /// The ID of the focused window.
+    pub fn active_window(&self) -> Option<WindowID> {
+        self.tiler.active_window().map(|window| window.id(&self.t))
+    }
+
+    /// Attach the window with this ID, creating the window if it is not yet known.
+    pub fn attach(&mut self, id: WindowID) {
+        self.tiler.attach_by_id(id, &mut self.t);
+    }
+
+    /// Detach the window with this ID from its tree.
+    pub fn detach(&mut self, id: WindowID) -> Result<(), WindowNotFound> {
+        self.tiler.detach_by_id(id, &mut self.t)
+    }
+
+    /// Creates or updates a display associated with the tree.
+    pub fn display_update(&mut self, display: u32, area: Rect) {
+        self.tiler.display_update(display, area, &mut self.t);
+    }
+
+    /// Retrieves the latest set of instructions for the window manager to carry out.
+    pub fn events(&mut self) -> impl Iterator<Item = Event> + '_ {
+        self.tiler.events(&mut self.t)
+    }
+
+    /// Focus the window with this ID in the tree.
+    pub fn focus(&mut self, id: WindowID) -> Result<(), WindowNotFound> {
+        self.tiler.focus_by_id(id, &mut self.t)
+    }
+
+    /// Move focus to the window in this direction from the active one.
+    pub fn focus_direction(&mut self, direction: Direction) {
+        let t = &mut self.t;
+
+        match direction {
+            Direction::Above => self.tiler.focus_above(t),
+            Direction::Below => self.tiler.focus_below(t),
+            Direction::Left => self.tiler.focus_left(t),
+            Direction::Right => self.tiler.focus_right(t),
+        }
+    }
+
+    /// Move the active window in this direction.
+    pub fn move_direction(&mut self, direction: Direction) {
+        let t = &mut self.t;
+
+        match direction {
+            Direction::Above => self.tiler.move_above(t),
+            Direction::Below => self.tiler.move_below(t),
+            Direction::Left => self.tiler.move_left(t),
+            Direction::Right => self.tiler.move_right(t),
+        }
+    }
+
+    /// If the active window is stacked, unstack it. If it is not stacked, stack it.
+    pub fn stack_toggle(&mut self) {
+        self.tiler.stack_toggle(&mut self.t);
+    }
+
+    /// Swaps the tree locations of the windows with these IDs.
+    pub fn swap(&mut self, from: WindowID, with: WindowID) -> Result<(), WindowNotFound> {
+        self.tiler.swap_by_id(from, with, &mut self.t)
+    }
+
+    /// Toggle the orientation of the active window.
+    pub fn toggle_orientation(&mut self) {
+        self.tiler.toggle_orientation(&mut self.t);
+    }
+
+    /// Switch to this workspace.
+    pub fn workspace_switch(&mut self, workspace: u32) {
+        self.tiler.workspace_switch(workspace, &mut self.t);
+    }
+
+    /// Associate a workspace with a display, and creates the workspace if it didn't exist.
+    pub fn workspace_update(&mut self, workspace: u32, display: u32) {
+        self.tiler.workspace_update(workspace, display, &mut self.t);
+    }
+}
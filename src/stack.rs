@@ -15,6 +15,72 @@ use std::rc::Rc;
 pub enum StackMovement {
     Left(WindowID),
     Right(WindowID),
+    /// Move this window to the given tab position.
+    To(WindowID, usize),
+}
+
+/// Which edge of a stack its tab bar is drawn on.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TabBarPosition {
+    Top,
+    Bottom,
+}
+
+impl Default for TabBarPosition {
+    fn default() -> Self {
+        TabBarPosition::Top
+    }
+}
+
+/// How the tabs of a stack are ordered as windows are attached to it.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StackTabOrder {
+    /// Windows are placed in the order that they were attached.
+    Insertion,
+    /// Windows are placed in ascending order of their IDs.
+    WindowId,
+    /// The most recently focused windows are placed first.
+    FocusRecency,
+}
+
+impl Default for StackTabOrder {
+    fn default() -> Self {
+        StackTabOrder::Insertion
+    }
+}
+
+/// Which window of a stack is raised when its active window is detached.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StackRefocus {
+    /// The tab beside the detached window.
+    Positional,
+    /// The most recently focused of the remaining tabs.
+    MostRecent,
+}
+
+impl Default for StackRefocus {
+    fn default() -> Self {
+        StackRefocus::Positional
+    }
+}
+
+/// Which tab of a stack is raised when a window is attached to the stack.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NewStackFocus {
+    /// The tab that was raised stays raised.
+    Existing,
+    /// The tab of the attached window is raised and focused.
+    New,
+}
+
+impl Default for NewStackFocus {
+    fn default() -> Self {
+        NewStackFocus::New
+    }
 }
 
 #[derive(Deref, DerefMut)]
@@ -29,6 +95,7 @@ impl<T: 'static> StackPtr<T> {
     pub fn new(window: &WindowPtr<T>, parent: ForkPtr<T>, t: &mut TCellOwner<T>) -> Self {
         let workspace = window.ro(t).workspace;
         let ptr = StackPtr(Rc::new(TCell::new(Stack {
+            id: 0,
             area: window.ro(t).rect,
             active: window.clone(),
             parent,
@@ -41,15 +108,57 @@ impl<T: 'static> StackPtr<T> {
         ptr
     }
 
+    /// The ID that the tiler assigned to this stack.
+    pub fn id(&self, t: &TCellOwner<T>) -> usize {
+        self.ro(t).id
+    }
+
     pub fn attach(&self, window: &WindowPtr<T>, t: &mut TCellOwner<T>) {
         window.rw(t).stack = Some(self.clone());
         self.rw(t).windows.push(window.clone());
     }
 
+    /// Attach a window directly before or after a window in this stack.
+    pub fn attach_beside(
+        &self,
+        window: &WindowPtr<T>,
+        reference: &WindowPtr<T>,
+        after: bool,
+        t: &mut TCellOwner<T>,
+    ) {
+        window.rw(t).stack = Some(self.clone());
+
+        let this = self.rw(t);
+        let position = match this.windows.iter().position(|w| Rc::ptr_eq(w, reference)) {
+            Some(position) if after => position + 1,
+            Some(position) => position,
+            None => this.windows.len(),
+        };
+
+        this.windows.insert(position, window.clone());
+    }
+
     pub fn detach(&self, tiler: &mut Tiler<T>, window: &WindowPtr<T>, t: &mut TCellOwner<T>) {
         window.rw(t).stack = None;
         tiler.event_queue.stack_detach(self, window, t);
 
+        // The most recently focused of the remaining windows, if refocusing by recency.
+        let recent = match tiler.stack_refocus {
+            StackRefocus::Positional => None,
+            StackRefocus::MostRecent => self
+                .ro(t)
+                .windows
+                .iter()
+                .filter(|w| !Rc::ptr_eq(w, window))
+                .filter_map(|w| {
+                    let id = w.id(t);
+                    let recency = tiler.focus_history.iter().position(|&r| r == id)?;
+                    Some((recency, w.clone()))
+                })
+                .min_by_key(|(recency, _)| *recency)
+                .map(|(_, w)| w),
+        };
+
         let this = self.rw(t);
 
         if let Some(pos) = this.windows.iter().position(|w| Rc::ptr_eq(w, window)) {
@@ -57,11 +166,13 @@ impl<T: 'static> StackPtr<T> {
 
             // Set the focus window if the detached window was the active window.
             if Rc::ptr_eq(window, &this.active) {
-                if let Some(to_focus) = this
-                    .windows
-                    .get(pos)
-                    .or_else(|| this.windows.get(pos - 1))
-                    .cloned()
+                if let Some(to_focus) =
+                    recent
+                        .or_else(|| this.windows.get(pos).cloned())
+                        .or_else(|| {
+                            pos.checked_sub(1)
+                                .and_then(|pos| this.windows.get(pos).cloned())
+                        })
                 {
                     this.active = to_focus.clone();
                     tiler.event_queue.stack_raise_window(self, &to_focus, t);
@@ -73,11 +184,48 @@ impl<T: 'static> StackPtr<T> {
             if this.windows.is_empty() {
                 let parent = self.ro(t).parent.clone();
                 tiler.detach_branch(parent, BranchRef::Stack(self), t);
-                tiler.event_queue.stack_destroy(self);
+                tiler.stack_destroy(self, t);
             }
         }
     }
 
+    /// Reorders the windows in the stack, returning the movements if the order changed.
+    ///
+    /// `recent` lists window IDs from the most to the least recently focused.
+    pub fn sort(
+        &self,
+        order: StackTabOrder,
+        recent: &[WindowID],
+        t: &mut TCellOwner<T>,
+    ) -> Vec<StackMovement> {
+        let previous: Vec<WindowID> = self.ro(t).windows.iter().map(|w| w.id(t)).collect();
+
+        let mut windows = self.ro(t).windows.clone();
+
+        match order {
+            StackTabOrder::Insertion => return Vec::new(),
+            StackTabOrder::WindowId => windows.sort_by_key(|w| w.id(t)),
+            StackTabOrder::FocusRecency => windows.sort_by_key(|w| {
+                let id = w.id(t);
+                recent.iter().position(|&r| r == id).unwrap_or(usize::MAX)
+            }),
+        }
+
+        let sorted: Vec<WindowID> = windows.iter().map(|w| w.id(t)).collect();
+
+        if sorted == previous {
+            return Vec::new();
+        }
+
+        self.rw(t).windows = windows;
+
+        sorted
+            .into_iter()
+            .enumerate()
+            .map(|(position, id)| StackMovement::To(id, position))
+            .collect()
+    }
+
     pub fn move_left(&self, t: &mut TCellOwner<T>) -> Option<StackMovement> {
         let this = self.rw(t);
 
@@ -134,29 +282,32 @@ impl<T: 'static> StackPtr<T> {
         None
     }
 
-    pub fn swap(&self, our: &WindowPtr<T>, their: &WindowPtr<T>, t: &mut TCellOwner<T>) {
-        for window in self.rw(t).windows.iter_mut() {
-            if Rc::ptr_eq(window, our) {
-                std::mem::swap(window, &mut their.clone());
-            }
-        }
-    }
-
     pub fn work_area_refresh(&self, tiler: &mut Tiler<T>, t: &mut TCellOwner<T>) {
         self.work_area_update(tiler, self.ro(t).area, t);
     }
 
     pub fn work_area_update(&self, tiler: &mut Tiler<T>, area: Rect, t: &mut TCellOwner<T>) {
-        self.rw(t).area = area;
+        let previous = std::mem::replace(&mut self.rw(t).area, area);
+
+        let window_area = tiler.stack_window_area(area);
+
         for window in self.ro(t).windows.clone() {
-            window.work_area_update(tiler, area, t);
+            window.work_area_update(tiler, window_area, t);
         }
 
-        tiler.event_queue.stack_update(self, t);
+        tiler.event_queue.stack_update(
+            self,
+            tiler.tab_bar_position,
+            previous,
+            tiler.gap_transition,
+            t,
+        );
     }
 }
 
 pub(crate) struct Stack<T: 'static> {
+    /// The ID of this stack, assigned when it is registered with the tiler.
+    pub id: usize,
     pub area: Rect,
     pub active: WindowPtr<T>,
     pub parent: ForkPtr<T>,
@@ -203,6 +354,7 @@ impl<'a, T> Debug for StackDebug<'a, T> {
             .collect();
 
         fmt.debug_struct("Stack")
+            .field("id", &self.stack.id)
             .field("windows", &windows)
             .finish()
     }
@@ -2,22 +2,34 @@
 // SPDX-License-Identifier: MPL-2.0
 
 use crate::branch::{Branch, BranchRef};
-use crate::display::DisplayPtr;
-use crate::events::EventQueue;
-use crate::fork::{Fork, ForkPtr, Orientation};
-use crate::stack::{StackMovement, StackPtr};
-use crate::window::{Window, WindowID, WindowPtr};
+use crate::command::Command;
+use crate::display::{DisplayPtr, Transform};
+use crate::events::{EventCompaction, EventQueue, ForkUpdate, Placement};
+use crate::fork::{
+    reset_split, split_rect, Fork, ForkChild, ForkPtr, Orientation, PlacementError, Side,
+};
+use crate::geom::pixel_grid;
+use crate::layout::{Layout, WorkspaceSnapshot};
+use crate::stack::{
+    NewStackFocus, StackMovement, StackPtr, StackRefocus, StackTabOrder, TabBarPosition,
+};
+use crate::window::{Decoration, Gravity, IdInUse, Window, WindowID, WindowNotFound, WindowPtr};
 use crate::workspace::WorkspacePtr;
-use crate::{Event, Rect};
+use crate::{EdgeSet, Event, Insets, NegativeCoordinate, Point, Rect, RoundingMode};
 use either::Either;
 use qcell::{TCell, TCellOwner};
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fmt::{self, Debug};
 use std::rc::Rc;
 
 type DistanceFn = fn(&Rect, &Rect) -> f64;
 type DirectionalConditionFn = fn(&Rect, &Rect) -> bool;
 
+/// The distance between the origins of cascading floating windows.
+const CASCADE_STEP: u32 = 32;
+
+/// A direction relative to the active window or display.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Direction {
     Above,
     Below,
@@ -25,6 +37,218 @@ pub enum Direction {
     Right,
 }
 
+/// Which window is focused after the active window is swapped with another.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SwapFocusPolicy {
+    /// Focus stays on the active window as it moves.
+    FollowWindow,
+    /// Focus moves to the window that takes the place of the active window.
+    StayInPlace,
+}
+
+impl Default for SwapFocusPolicy {
+    fn default() -> Self {
+        SwapFocusPolicy::FollowWindow
+    }
+}
+
+/// Which window is focused after the active window is detached.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DetachRefocus {
+    /// The window on the same workspace that was focused most recently.
+    PreviousFocus,
+    /// The nearest window in this direction, or the previously focused window if there is
+    /// none.
+    Direction(Direction),
+}
+
+impl Default for DetachRefocus {
+    fn default() -> Self {
+        DetachRefocus::PreviousFocus
+    }
+}
+
+/// How moving the split of a fork affects the other splits along the same axis.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ResizeMode {
+    /// The splits within the branches of the fork keep their proportions.
+    Proportional,
+    /// Only the branches on either side of the split change size, and every other split
+    /// along the axis stays where it is.
+    Isolated,
+    /// The branches after the split keep their size and are pushed along, such that only
+    /// the branches before the split and the last branch along the axis change size.
+    Chained,
+}
+
+impl Default for ResizeMode {
+    fn default() -> Self {
+        ResizeMode::Proportional
+    }
+}
+
+/// Where a window rule places a window as it is attached.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AttachDecision {
+    /// The window is attached as it would be without a rule.
+    Tile,
+    /// The window floats over the tree of the active workspace, at this rect relative to
+    /// the origin of its display.
+    Float(Rect),
+    /// The window is attached to this workspace instead, which is not switched to.
+    ToWorkspace(u32),
+    /// The window is attached to the active workspace of this display instead.
+    ToDisplay(u32),
+}
+
+impl Default for AttachDecision {
+    fn default() -> Self {
+        AttachDecision::Tile
+    }
+}
+
+/// Which window is focused when switching to a workspace or display.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WorkspaceFocusPolicy {
+    /// The window that was last focused on the workspace.
+    Stored,
+    /// The window under the cursor, or the window that was last focused if there is none.
+    AtPoint,
+}
+
+impl Default for WorkspaceFocusPolicy {
+    fn default() -> Self {
+        WorkspaceFocusPolicy::Stored
+    }
+}
+
+/// A problem found in the trees of the tiler.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TreeInconsistency {
+    /// A fork holds a single branch, where it should have been replaced by that branch.
+    SingleBranchFork(usize),
+    /// The parent association of a branch is not the fork or stack which holds it.
+    StaleParent(ForkChild),
+    /// A window is associated with a fork or stack that the tiler no longer has.
+    DanglingWindow(WindowID),
+}
+
+/// The gaps around and between the tiles of a workspace.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct GapConfig {
+    /// Between the tree and the edges of its display.
+    pub outer: u32,
+    /// At a split where a fork is beside a fork or window.
+    pub inner_fork: u32,
+    /// At a split between two windows.
+    pub inner_window: u32,
+    /// At a split beside a stack.
+    pub stack: u32,
+}
+
+impl GapConfig {
+    /// The same gap at every split, and none at the edges of the display.
+    pub fn uniform(gap: u32) -> Self {
+        Self {
+            outer: 0,
+            inner_fork: gap,
+            inner_window: gap,
+            stack: gap,
+        }
+    }
+
+    /// The gap at a split between these branches, which depends on what lies on either side.
+    pub(crate) fn between<T: 'static>(&self, left: &Branch<T>, right: &Branch<T>) -> u32 {
+        match (left, right) {
+            (Branch::Stack(_), _) | (_, Branch::Stack(_)) => self.stack,
+            (Branch::Fork(_), _) | (_, Branch::Fork(_)) => self.inner_fork,
+            _ => self.inner_window,
+        }
+    }
+}
+
+/// Gaps between tiles which shrink as more windows are tiled on a workspace.
+#[derive(Copy, Clone)]
+struct AdaptiveGaps {
+    base: u32,
+    per_window_reduction: u32,
+    min: u32,
+}
+
+impl AdaptiveGaps {
+    /// The gap between tiles on a workspace with this many tiled windows.
+    fn gap(self, windows: usize) -> u32 {
+        let extra = u32::try_from(windows.saturating_sub(1)).unwrap_or(u32::MAX);
+        let reduction = self.per_window_reduction.saturating_mul(extra);
+        self.base.saturating_sub(reduction).max(self.min)
+    }
+}
+
+/// Where a window is placed as it is attached.
+enum AttachPlan<T: 'static> {
+    /// Floated over this workspace by the attach rule, at this rect relative to its origin.
+    Float(u32, Rect),
+    /// Attached by the attach rule to this workspace, which is not switched to.
+    OtherWorkspace(WorkspacePtr<T>),
+    /// Attached to the overflow of this workspace, which is at its capacity.
+    Overflow(u32),
+    /// Floated on this workspace, which has tiling disabled.
+    Floating(u32),
+    /// Fills the next slot of the layout template of this workspace.
+    Template(u32),
+    /// Attached beside this window: the attach anchor, or the focus window.
+    Beside(WindowPtr<T>),
+    /// Attached to this workspace, which has no window to attach beside.
+    Workspace(WorkspacePtr<T>),
+}
+
+/// How a window attached beside a window of a tree joins the tree.
+enum TileAttach<T: 'static> {
+    /// Joins the stack of the window.
+    Join(StackPtr<T>),
+    /// Stacks the window, and then joins its stack.
+    StackOnto,
+    /// Takes the empty right branch of this fork.
+    FillRight(ForkPtr<T>),
+    /// Replaces this branch of this fork with a new fork of the branch and the window.
+    Split(ForkPtr<T>, Branch<T>),
+}
+
+/// The copies of the stacks and windows of mirrored displays, by ID and the display
+/// mirroring them, scaled to the area of that display.
+#[derive(Default)]
+struct MirrorCopies {
+    stacks: BTreeMap<(usize, u32), Rect>,
+    windows: BTreeMap<(WindowID, u32), Rect>,
+}
+
+impl MirrorCopies {
+    /// Events which show these copies, leaving out those which are in `last` as they are.
+    fn events(&self, last: &MirrorCopies) -> Vec<Event> {
+        let stacks = self
+            .stacks
+            .iter()
+            .filter(|(key, rect)| last.stacks.get(key) != Some(rect))
+            .map(|(&(id, display), &rect)| Event::StackMirror(id, display, rect));
+
+        let windows = self
+            .windows
+            .iter()
+            .filter(|(key, rect)| last.windows.get(key) != Some(rect))
+            .map(|(&(id, display), &rect)| Event::WindowMirror(id, display, rect));
+
+        stacks.chain(windows).collect()
+    }
+}
+
+/// A workspace with tiling disabled, and the tree that its windows were tiled in.
+struct FloatingWorkspace<T: 'static> {
+    layout: Option<Layout>,
+    windows: Vec<WindowPtr<T>>,
+}
+
 /// A tiling window manager
 pub struct Tiler<T: 'static> {
     pub(crate) event_queue: EventQueue<T>,
@@ -32,11 +256,83 @@ pub struct Tiler<T: 'static> {
     active: Option<WindowPtr<T>>,
     active_workspace: u32,
     active_workspace_changed: bool,
+    adaptive_gaps: Option<AdaptiveGaps>,
+    attach_anchor: Option<WindowPtr<T>>,
+    attach_rule: Option<Box<dyn Fn(WindowID) -> AttachDecision>>,
+    auto_hide_single_border: bool,
+    auto_stack_same_client: bool,
+    auto_stack_threshold: Option<u32>,
+    cursor: Option<Point>,
+    default_float_rect: Option<Rect>,
+    detach_refocus: DetachRefocus,
+    display_focus_wrap: bool,
+    display_isolation: bool,
+    /// The display that each mirroring display shows the active workspace of.
+    display_mirrors: BTreeMap<u32, u32>,
+    emit_previous_rect: bool,
+    empty_workspace_orientation: Option<Orientation>,
+    event_sink: Option<Box<dyn FnMut(Event)>>,
+    /// Windows floated over the trees of their workspaces by the attach rule.
+    floated: Vec<WindowPtr<T>>,
+    focus_echo: Option<u64>,
+    focus_follows_workspace: bool,
+    pub(crate) focus_history: Vec<WindowID>,
+    focus_history_limit: usize,
+    focus_serial: u64,
+    focus_serials: bool,
+    focus_steals_on_urgent: bool,
+    focus_wrap: bool,
+    fork_id_next: usize,
+    fullscreen_hides_siblings: bool,
+    gap_animation: bool,
+    gap_config: Option<GapConfig>,
+    /// Whether the placements being made are of a change of gaps.
+    pub(crate) gap_transition: bool,
+    /// The window under the cursor which is to be focused once the hover is committed.
+    hover: Option<WindowID>,
+    layout_templates: BTreeMap<u32, Layout>,
+    max_tree_depth: Option<usize>,
+    pub(crate) min_split_pixels: u32,
+    minimum_visible_area: u32,
+    /// The copies shown on mirroring displays, as they were last reported.
+    mirrored: MirrorCopies,
+    new_stack_focus: NewStackFocus,
+    /// Where windows go which do not fit on a workspace that is at its capacity.
+    overflow_workspaces: BTreeMap<u32, u32>,
+    /// The splits requested of forks which have yet to move that far.
+    pending_resizes: BTreeMap<usize, u32>,
+    primary_display: Option<u32>,
+    ratio_snap: u32,
+    /// Whether trees are left unplaced as they change, to be placed once when complete.
+    pub(crate) refresh_deferred: bool,
+    resize_mode: ResizeMode,
+    resize_throttle: u32,
+    rotate_focus_follows_slot: bool,
+    smart_borders: bool,
+    stack_cycle_wrap: bool,
+    stack_id_next: usize,
+    pub(crate) stack_refocus: StackRefocus,
+    stack_tab_order: StackTabOrder,
+    swap_focus_policy: SwapFocusPolicy,
+    pub(crate) swap_on_double_toggle: bool,
+    tab_activation_raises_focus: bool,
+    pub(crate) tab_bar_height: u32,
+    pub(crate) tab_bar_position: TabBarPosition,
+    tabbed: BTreeMap<u32, Layout>,
+    tiling_disabled: BTreeMap<u32, FloatingWorkspace<T>>,
+    workspace_affinity: BTreeMap<u32, u32>,
+    /// The most windows that each workspace with a capacity may hold.
+    workspace_capacity: BTreeMap<u32, usize>,
+    workspace_focus_policy: WorkspaceFocusPolicy,
+    /// Whether a workspace was switched to, before which the active workspace is only
+    /// the default of the tiler.
+    workspace_switched: bool,
 
     pub windows: BTreeMap<WindowID, WindowPtr<T>>,
     forks: BTreeMap<usize, ForkPtr<T>>,
+    stacks: BTreeMap<usize, StackPtr<T>>,
     displays: BTreeMap<u32, DisplayPtr<T>>,
-    workspaces: BTreeMap<u32, WorkspacePtr<T>>,
+    pub(crate) workspaces: BTreeMap<u32, WorkspacePtr<T>>,
 }
 
 impl<T: 'static> Default for Tiler<T> {
@@ -47,7 +343,68 @@ impl<T: 'static> Default for Tiler<T> {
             active: None,
             active_workspace: 0,
             active_workspace_changed: false,
+            adaptive_gaps: None,
+            attach_anchor: None,
+            attach_rule: None,
+            auto_hide_single_border: false,
+            auto_stack_same_client: false,
+            auto_stack_threshold: None,
+            cursor: None,
+            default_float_rect: None,
+            detach_refocus: DetachRefocus::default(),
+            display_focus_wrap: false,
+            display_isolation: false,
+            display_mirrors: BTreeMap::new(),
+            emit_previous_rect: false,
+            empty_workspace_orientation: None,
+            event_sink: None,
+            floated: Vec::new(),
+            focus_echo: None,
+            focus_follows_workspace: true,
+            focus_history: Vec::new(),
+            focus_history_limit: 32,
+            focus_serial: 0,
+            focus_serials: false,
+            focus_steals_on_urgent: false,
+            focus_wrap: false,
+            fork_id_next: 0,
+            fullscreen_hides_siblings: true,
+            gap_animation: false,
+            gap_config: None,
+            gap_transition: false,
+            hover: None,
+            layout_templates: BTreeMap::new(),
+            max_tree_depth: None,
+            min_split_pixels: 0,
+            minimum_visible_area: 0,
+            mirrored: MirrorCopies::default(),
+            new_stack_focus: NewStackFocus::default(),
+            overflow_workspaces: BTreeMap::new(),
+            pending_resizes: BTreeMap::new(),
+            primary_display: None,
+            ratio_snap: 5,
+            refresh_deferred: false,
+            resize_mode: ResizeMode::default(),
+            resize_throttle: 0,
+            rotate_focus_follows_slot: false,
+            smart_borders: false,
+            stack_cycle_wrap: false,
+            stack_id_next: 0,
+            stack_refocus: StackRefocus::default(),
+            stack_tab_order: StackTabOrder::default(),
+            swap_focus_policy: SwapFocusPolicy::default(),
+            swap_on_double_toggle: true,
+            tab_activation_raises_focus: true,
+            tab_bar_height: 0,
+            tab_bar_position: TabBarPosition::default(),
+            tabbed: BTreeMap::new(),
+            tiling_disabled: BTreeMap::new(),
+            workspace_affinity: BTreeMap::new(),
+            workspace_capacity: BTreeMap::new(),
+            workspace_focus_policy: WorkspaceFocusPolicy::default(),
+            workspace_switched: false,
             forks: BTreeMap::new(),
+            stacks: BTreeMap::new(),
             windows: BTreeMap::new(),
             displays: BTreeMap::new(),
             workspaces: BTreeMap::new(),
@@ -60,244 +417,1382 @@ impl<T: 'static> Tiler<T> {
         self.active.as_ref()
     }
 
-    /// Attach a window to the focused window in the tiler, and associate it with the tiler.
-    pub fn attach(&mut self, window: &WindowPtr<T>, t: &mut TCellOwner<T>) {
-        // Attach the window to the tiler in case it was not.
-        self.windows.insert(window.id(t), window.clone());
+    /// The IDs of the forks from the root of the tree to the active window, root first.
+    pub fn active_window_path(&self, t: &TCellOwner<T>) -> Vec<usize> {
+        let mut path = Vec::new();
+        let mut fork = self.active.as_ref().and_then(|active| active.fork(t));
 
-        if let Some(focus) = self.active_window().cloned() {
-            tracing::debug!("attaching to focus window");
-            self.attach_to_window(window, &focus, t);
-            return;
+        while let Some(parent) = fork {
+            path.push(parent.id(t));
+            fork = parent.ro(t).parent.clone();
         }
 
-        tracing::debug!("no active window: attaching to display instead");
-
-        self.set_active_window(window, t);
+        path.reverse();
+        path
+    }
 
-        let workspace = self
-            .workspaces
-            .get(&self.active_workspace)
-            .expect("no workspace found to attach to")
-            .clone();
+    /// The windows managed by the tiler which have been focused, from the most to the
+    /// least recently focused.
+    pub fn focus_history(&self) -> &[WindowID] {
+        &self.focus_history
+    }
 
-        self.attach_to_workspace(window, &workspace, t);
+    /// Whether the active window is in a stack.
+    pub fn active_window_is_stacked(&self, t: &TCellOwner<T>) -> bool {
+        self.active_window()
+            .map_or(false, |active| active.stack(t).is_some())
     }
 
-    /// Attach a window to an existing window
-    fn attach_to_window(
-        &mut self,
-        new_window: &WindowPtr<T>,
-        attach_to: &WindowPtr<T>,
-        t: &mut TCellOwner<T>,
-    ) {
-        // If window is attached to stack, then attach new window to the same stack
-        if let Some((stack, fork)) = attach_to.stack(t).zip(attach_to.fork(t)) {
-            new_window.fork_set(fork.clone(), t);
-            stack.attach(new_window, t);
-            stack.work_area_update(self, stack.ro(t).area, t);
-            return;
-        }
+    /// Whether the active window is floating, apart from the tree of its workspace.
+    pub fn active_window_is_floating(&self, t: &TCellOwner<T>) -> bool {
+        self.active_window().map_or(false, |active| {
+            let floated = match self.tiling_disabled.get(&active.ro(t).workspace) {
+                Some(floating) => &floating.windows,
+                None => &self.floated,
+            };
 
-        // If window is attached to a fork, attach to the same fork
-        if let Some(fork) = attach_to.fork(t) {
-            self.attach_to_window_in_fork(new_window, attach_to, &fork, t);
-            return;
-        }
+            floated.iter().any(|window| Rc::ptr_eq(window, active))
+        })
+    }
 
-        tracing::error!("attempted attach to window that's not attached to anything");
+    /// Whether the active window is fullscreen.
+    pub fn active_window_is_fullscreen(&self, t: &TCellOwner<T>) -> bool {
+        self.active_window()
+            .map_or(false, |active| active.ro(t).fullscreen)
     }
 
-    /// Attach the `window` to the `attaching` window in the `fork`.
-    fn attach_to_window_in_fork(
-        &mut self,
-        window: &WindowPtr<T>,
-        attaching: &WindowPtr<T>,
-        fork: &ForkPtr<T>,
-        t: &mut TCellOwner<T>,
-    ) {
-        let workspace: u32;
+    /// Rearranges the tree of the active workspace into columns of equal width.
+    ///
+    /// Windows and stacks become columns in the order that they appeared in the tree.
+    pub fn arrange_equal_columns(&mut self, t: &mut TCellOwner<T>) {
+        let workspace = self.active_workspace;
 
-        // If the right branch is empty, assign our new window to it.
-        {
-            let fork_ = fork.rw(t);
+        let workspace_ = ward::ward!(self.workspaces.get(&workspace).cloned(), else { return });
+        let root = ward::ward!(workspace_.fork(t), else { return });
+        let area = workspace_.area(t);
 
-            workspace = fork_.workspace;
+        // Collect the windows and stacks of the tree, discarding its forks.
+        let mut columns = Vec::new();
+        let mut branches = vec![Branch::Fork(root)];
 
-            if fork_.right.is_none() {
-                fork_.right = Some(Branch::Window(window.clone()));
-                window.fork_set(fork.clone(), t);
-                return;
-            }
-        };
+        while let Some(branch) = branches.pop() {
+            match branch {
+                Branch::Fork(fork) => {
+                    self.fork_destroy(&fork, t);
 
-        // Create a new fork branch and assign both windows to it.
-        let new_fork = ForkPtr::new({
-            let area = Rect::new(1, 1, 1, 1);
-            let branch = Branch::Window(attaching.clone());
-            let mut fork = Fork::new(area, branch, workspace);
-            fork.right = Some(Branch::Window(window.clone()));
-            fork
-        });
+                    let fork_ = fork.ro(t);
+                    if let Some(right) = fork_.right.clone() {
+                        branches.push(right);
+                    }
 
-        self.fork_register(new_fork.clone(), t);
+                    branches.push(fork_.left.clone());
+                }
 
-        attaching.fork_set(new_fork.clone(), t);
-        window.fork_set(new_fork.clone(), t);
+                column => columns.push(column),
+            }
+        }
 
-        let new_branch = Branch::Fork(new_fork.clone());
+        // Each fork holds one column on its left, and the remaining columns on its right.
+        let count = columns.len() as u64;
+        let edge =
+            |column: usize| area.x + (u64::from(area.width) * column as u64 / count.max(1)) as u32;
+
+        let mut branch = ward::ward!(columns.pop(), else { return });
+
+        for (column, left) in columns.into_iter().enumerate().rev() {
+            let x = edge(column);
+
+            let fork = ForkPtr::new(Fork {
+                id: 0,
+                area: Rect::new(x, area.y, area.x + area.width - x, area.height),
+                parent: None,
+                left: left.clone(),
+                right: Some(branch.clone()),
+                workspace,
+                orientation: Orientation::Horizontal,
+                split_handle: edge(column + 1) - x,
+                orientation_toggled: false,
+            });
 
-        // Then assign the new branch to the fork where the window was.
-        {
-            let fork_ = fork.rw(t);
-            match fork_.branch(BranchRef::Window(attaching)) {
-                Some(Either::Left(branch)) | Some(Either::Right(branch)) => *branch = new_branch,
-                None => tracing::error!("invalid parent fork association in window"),
-            }
+            self.fork_register(fork.clone(), t);
+
+            left.parent_set(&fork, t);
+            branch.parent_set(&fork, t);
+            branch = Branch::Fork(fork);
         }
 
-        // Refresh work areas
-        fork.work_area_refresh(self, t);
+        let root = match branch {
+            Branch::Fork(fork) => fork,
+            column => {
+                let fork = ForkPtr::new(Fork::new(area, column.clone(), workspace));
+                self.fork_register(fork.clone(), t);
+                column.parent_set(&fork, t);
+                fork
+            }
+        };
 
-        // Reassign fork orientation and refresh again. TODO: Avoid redoing refresh
-        new_fork.reset_orientation(self, t);
-        new_fork.work_area_refresh(self, t);
+        workspace_.rw(t).fork = Some(root.clone());
+        root.work_area_update(self, area, t);
+
+        self.dispatch_events(t);
     }
 
-    /// Attach a window a tree on a display.
-    fn attach_to_workspace(
-        &mut self,
-        window: &WindowPtr<T>,
-        workspace: &WorkspacePtr<T>,
-        t: &mut TCellOwner<T>,
-    ) {
-        let area = workspace.area(t);
+    /// Attach a window to the focused window in the tiler, and associate it with the tiler.
+    ///
+    /// If an attach anchor was set, the window is attached to the anchor instead. Windows
+    /// attached to a workspace with a layout template fill the next slot of the template.
+    /// An attach rule, if set, is consulted first.
+    pub fn attach(&mut self, window: &WindowPtr<T>, t: &mut TCellOwner<T>) {
+        self.attach_window(window, t);
+        self.dispatch_events(t);
+    }
 
-        // Assign window to an existing fork on the workspace.
-        if let Some(fork) = workspace.fork(t) {
-            if let Some(attach_to) = fork.largest_window(t) {
-                self.attach_to_window_in_fork(window, &attach_to, &fork, t);
-                fork.work_area_refresh(self, t);
+    fn attach_window(&mut self, window: &WindowPtr<T>, t: &mut TCellOwner<T>) {
+        // Attach the window to the tiler in case it was not.
+        self.windows.insert(window.id(t), window.clone());
+
+        // Without a valid active workspace, the primary display's workspace is used.
+        if self.attach_anchor.is_none() && self.switched_workspace().is_none() {
+            let primary = ward::ward!(self.attach_workspace(t), else {
+                tracing::error!("no workspace found to attach to");
                 return;
-            }
+            });
+
+            self.active_workspace = primary;
+            self.active_workspace_changed = true;
+            self.workspace_switched = true;
         }
 
-        // Create a new fork and assign that, otherwise.
-        let branch = Branch::Window(window.clone());
-        let fork = ForkPtr::new(Fork::new(area, branch, workspace.id(t)));
-        self.fork_register(fork.clone(), t);
+        let plan = ward::ward!(self.attach_plan(window.id(t), t), else {
+            tracing::error!("no workspace found to attach to");
+            return;
+        });
 
-        window.fork_set(fork.clone(), t);
+        self.attach_planned(window, plan, t);
+    }
 
-        let workspace = workspace.rw(t);
-        workspace.focus = Some(window.clone());
-        workspace.fork = Some(fork.clone());
+    /// Where a window with this ID is placed as it is attached.
+    fn attach_plan(&self, id: WindowID, t: &TCellOwner<T>) -> Option<AttachPlan<T>> {
+        if let Some(plan) = self.attach_plan_by_rule(id, t) {
+            return Some(plan);
+        }
 
-        fork.work_area_refresh(self, t);
+        let workspace = self.attach_workspace(t)?;
+
+        if self.workspace_full(workspace, id, t) {
+            return Some(AttachPlan::Overflow(workspace));
+        }
+
+        self.attach_plan_on(workspace, t)
     }
 
-    /// Detach a window from its tree, and removes its association with this tiler.
-    pub fn detach(&mut self, window: &WindowPtr<T>, t: &mut TCellOwner<T>) {
-        // Remove the window from management of the tiler.
-        self.windows.remove(&window.id(t));
+    /// Where the attach rule places a window with this ID. `None` if the window is to be
+    /// tiled as usual.
+    fn attach_plan_by_rule(&self, id: WindowID, t: &TCellOwner<T>) -> Option<AttachPlan<T>> {
+        let workspace = match (self.attach_rule.as_ref()?)(id) {
+            AttachDecision::Tile => return None,
 
-        if let Some(stack) = window.stack(t) {
-            window.fork_take(t);
-            stack.detach(self, window, t);
-            return;
-        }
+            AttachDecision::Float(rect) => {
+                let workspace = self.active_workspace;
 
-        if let Some(fork) = window.fork_take(t) {
-            self.detach_branch(fork, BranchRef::Window(window), t);
+                if !self.workspaces.contains_key(&workspace) {
+                    tracing::error!("no workspace found to float window on");
+                    return None;
+                }
+
+                return Some(AttachPlan::Float(workspace, rect));
+            }
+
+            AttachDecision::ToWorkspace(workspace) => workspace,
+
+            AttachDecision::ToDisplay(display_id) => {
+                let active = self.displays.get(&display_id).and_then(|d| d.ro(t).active);
+                ward::ward!(active, else {
+                    tracing::error!("no workspace on display {} to attach to", display_id);
+                    return None;
+                })
+            }
+        };
+
+        if workspace == self.active_workspace {
+            return None;
         }
 
-        // If window being detached is the active window, remove focus
-        if let Some(active) = self.active.as_ref() {
-            if Rc::ptr_eq(window, active) {
-                self.active = None;
-                self.active_changed = false;
+        match self.workspaces.get(&workspace) {
+            Some(workspace) => Some(AttachPlan::OtherWorkspace(workspace.clone())),
+            None => {
+                tracing::error!("cannot attach to non-existent workspace {}", workspace);
+                None
             }
         }
     }
 
-    /// Detach a window from a fork.
-    fn detach_fork(&mut self, fork: ForkPtr<T>, t: &mut TCellOwner<T>) {
-        eprintln!("requested to detach fork");
-        let mut detaching = Some(fork);
+    /// Where a window is placed as it is attached to this workspace, which has room for it.
+    fn attach_plan_on(&self, workspace: u32, t: &TCellOwner<T>) -> Option<AttachPlan<T>> {
+        if self.tiling_disabled.contains_key(&workspace) {
+            return Some(AttachPlan::Floating(workspace));
+        }
 
-        while let Some(fork) = detaching.take() {
-            self.event_queue.fork_destroy(&fork);
-            self.forks.remove(&(Rc::as_ptr(&fork) as usize));
+        if self.layout_templates.contains_key(&workspace) {
+            return Some(AttachPlan::Template(workspace));
+        }
 
-            if let Some(parent) = fork.rw(t).parent.take() {
-                let parent_ = parent.rw(t);
-                if parent_.left_is(BranchRef::Fork(&fork)) {
-                    if let Some(right) = parent_.right.take() {
-                        parent_.left = right;
-                    }
-                } else if parent_.right_is(BranchRef::Fork(&fork)) {
-                    parent_.right = None;
-                }
-            } else {
-                for workspace in self.workspaces.values() {
-                    if let Some(ref root) = workspace.fork(t) {
-                        if Rc::ptr_eq(root, &fork) {
-                            workspace.rw(t).fork = None;
-                            return;
-                        }
-                    }
-                }
-            }
+        let beside = self.attach_anchor.clone().or_else(|| {
+            self.active_window()
+                .filter(|focus| focus.ro(t).workspace == workspace && focus.fork(t).is_some())
+                .cloned()
+        });
+
+        match beside {
+            Some(beside) => Some(AttachPlan::Beside(beside)),
+            None => self
+                .workspaces
+                .get(&workspace)
+                .cloned()
+                .map(AttachPlan::Workspace),
         }
     }
 
-    /// Detach a window or stack from a fork
-    pub(crate) fn detach_branch(
+    /// The workspace that windows attach to: that of the attach anchor, or the active
+    /// workspace, or without a valid active workspace, that of the primary display.
+    ///
+    /// Before any workspace was switched to, the default active workspace is used only
+    /// without a primary display.
+    fn attach_workspace(&self, t: &TCellOwner<T>) -> Option<u32> {
+        if let Some(anchor) = self.attach_anchor.as_ref() {
+            return Some(anchor.ro(t).workspace);
+        }
+
+        if let Some(active) = self.switched_workspace() {
+            return Some(active);
+        }
+
+        self.primary_display
+            .and_then(|display| self.displays.get(&display))
+            .and_then(|display| display.ro(t).active)
+            .or_else(|| Some(self.active_workspace).filter(|w| self.workspaces.contains_key(w)))
+    }
+
+    /// The active workspace, if one was switched to and it still exists.
+    fn switched_workspace(&self) -> Option<u32> {
+        Some(self.active_workspace)
+            .filter(|workspace| self.workspace_switched && self.workspaces.contains_key(workspace))
+    }
+
+    /// Places a window as it was planned to be attached.
+    fn attach_planned(
         &mut self,
-        fork: ForkPtr<T>,
-        reference: BranchRef<'_, T>,
+        window: &WindowPtr<T>,
+        plan: AttachPlan<T>,
         t: &mut TCellOwner<T>,
     ) {
-        tracing::debug!("detaching branch from Fork({})", Rc::as_ptr(&fork) as usize);
+        match plan {
+            AttachPlan::Float(workspace, rect) => {
+                tracing::debug!("floating window over workspace {} by rule", workspace);
+                window.rw(t).workspace = workspace;
+                self.set_active_window(window, t);
+                self.attach_floating(window, workspace, Some(rect), t);
+            }
 
-        // After removing a window, it's possible to have a fork in a fork with an empty
-        // branch on one side. When this happens, we will discard the parent fork and
-        // place the grandchild in its place. Then the child association of the
-        // grandparent fork is updated to point to the grandchild who is now a direct
-        // child.
-        fn reparent<T>(
-            tiler: &mut Tiler<T>,
-            grandparent: Option<ForkPtr<T>>,
-            parent: ForkPtr<T>,
-            grandchild: ForkPtr<T>,
-            t: &mut TCellOwner<T>,
-        ) {
-            match grandparent {
-                Some(grandparent) => {
-                    // Update child association of the grandparent fork
-                    let grandparent_ = grandparent.rw(t);
-                    match grandparent_.branch(BranchRef::Fork(&parent)) {
-                        Some(Either::Left(branch)) | Some(Either::Right(branch)) => {
-                            *branch = Branch::Fork(grandchild.clone())
-                        }
-                        None => tracing::error!("fork contained parent that doesn't own it"),
+            AttachPlan::OtherWorkspace(workspace) => {
+                tracing::debug!("attaching to workspace {} by rule", workspace.id(t));
+                self.attach_to_other_workspace(window, &workspace, t);
+                self.visibility_sync(window, t);
+            }
+
+            AttachPlan::Overflow(workspace) => match self.overflow_target(workspace, window, t) {
+                Some(overflow) => {
+                    tracing::debug!("workspace {} is full: attaching to its overflow", workspace);
+                    self.attach_to_other_workspace(window, &overflow, t);
+                    self.visibility_sync(window, t);
+                }
+
+                // Without an overflow with room, the window is attached as if there were room.
+                None => {
+                    if let Some(plan) = self.attach_plan_on(workspace, t) {
+                        self.attach_planned(window, plan, t);
                     }
+                }
+            },
 
-                    grandparent.work_area_refresh(tiler, t);
+            AttachPlan::Floating(workspace) => {
+                tracing::debug!("attaching as floating to workspace {}", workspace);
 
-                    // Update the parent association of the grandchild now a child
-                    grandchild.rw(t).parent = Some(grandparent);
+                if self.active.is_none() {
+                    self.set_active_window(window, t);
                 }
-                None => {
-                    let mut display = None;
 
-                    for info in tiler.workspaces.values() {
-                        if let Some(ref workspace_fork) = info.ro(t).fork {
-                            if !Rc::ptr_eq(workspace_fork, &parent) {
-                                continue;
-                            }
+                self.attach_floating(window, workspace, None, t);
+            }
 
-                            display = Some(info.ro(t).parent.ro(t).area);
+            AttachPlan::Template(workspace) => {
+                tracing::debug!("attaching to layout template of workspace {}", workspace);
+                self.attach_to_template(window, workspace, t);
+            }
+
+            AttachPlan::Beside(beside) => {
+                tracing::debug!("attaching beside window {:?}", beside.id(t));
+                self.attach_to_window(window, &beside, t);
+                self.attach_raise(window, t);
+            }
+
+            AttachPlan::Workspace(workspace) => {
+                tracing::debug!(
+                    "no active window in the tree of the workspace: attaching to display instead"
+                );
+
+                window.rw(t).workspace = workspace.id(t);
+                self.set_active_window(window, t);
+
+                self.attach_to_workspace(window, &workspace, t);
+            }
+        }
+    }
+
+    /// Where a window with this ID would be placed if it were attached next, without
+    /// attaching it. `None` if it would be floated, fill a slot of a layout template, or be
+    /// attached to a workspace other than the one attached to.
+    ///
+    /// Adaptive gaps, which shrink as the attach adds a window to the workspace, are not
+    /// accounted for.
+    pub fn attach_preview(&self, id: WindowID, t: &TCellOwner<T>) -> Option<Rect> {
+        match self.attach_plan(id, t)? {
+            AttachPlan::Beside(beside) => self.tile_attach_preview(id, &beside, t),
+
+            // Without a window to attach beside, the largest window of the tree is used, and
+            // a workspace without a tree is filled.
+            AttachPlan::Workspace(workspace) => match workspace.fork(t) {
+                Some(root) => self.tile_attach_preview(id, &root.largest_window(t)?, t),
+                None => Some(workspace.area(t)),
+            },
+
+            _ => None,
+        }
+    }
+
+    /// Where a window with this ID would be placed if it were attached beside this window.
+    fn tile_attach_preview(
+        &self,
+        id: WindowID,
+        attach_to: &WindowPtr<T>,
+        t: &TCellOwner<T>,
+    ) -> Option<Rect> {
+        let gaps = self.workspaces.get(&attach_to.ro(t).workspace)?.ro(t).gaps;
+        let gravity = self
+            .windows
+            .get(&id)
+            .map_or(Gravity::Center, |window| window.ro(t).gravity);
+
+        // The attached window is a window, as is the window it is attached beside.
+        let attached = Branch::Window(attach_to.clone());
+
+        let (area, orientation, split, gap) = match self.tile_attach(id, attach_to, t)? {
+            TileAttach::Join(stack) => return Some(self.stack_window_area(stack.ro(t).area)),
+
+            // The window is replaced by a stack, and a stack has its own gap.
+            TileAttach::StackOnto => {
+                let fork = attach_to.fork(t)?;
+                let area = branch_area(&fork, &attached, |_| gaps.stack, t);
+                return Some(self.stack_window_area(area));
+            }
+
+            TileAttach::FillRight(fork) => {
+                let fork_ = fork.ro(t);
+                let gap = gaps.between(&fork_.left, &attached);
+                (fork_.area, fork_.orientation, fork_.split_handle, gap)
+            }
+
+            // The branch is replaced by a fork, which is created horizontal and then reset to
+            // the area it takes.
+            TileAttach::Split(fork, branch) => {
+                let area = branch_area(
+                    &fork,
+                    &branch,
+                    |other| gaps.between(other, &Branch::Fork(fork.clone())),
+                    t,
+                );
+
+                let (orientation, split) = reset_split(Orientation::Horizontal, area);
+                (area, orientation, split, gaps.between(&branch, &attached))
+            }
+        };
+
+        // As `gravity_apply` arranges the fork which the window is attached to.
+        let (orientation, split, left) = match gravity.split_side() {
+            None => (orientation, split, false),
+            Some((gravitated, left)) if gravitated == orientation => (orientation, split, left),
+            Some((gravitated, left)) => {
+                let split = gravitated.half(area);
+
+                (gravitated, split, left)
+            }
+        };
+
+        let (left_rect, right_rect) = split_rect(area, orientation, split, gap);
+        Some(if left { left_rect } else { right_rect })
+    }
+
+    /// An event which shows where a window with this ID would be placed if it were attached
+    /// next, for the window manager to indicate while the window maps. The tree is left as
+    /// it is.
+    ///
+    /// The event is returned rather than queued, as it describes no change to the tree.
+    pub fn begin_attach_feedback(&self, id: WindowID, t: &TCellOwner<T>) -> Option<Event> {
+        self.attach_preview(id, t).map(Event::InsertPreview)
+    }
+
+    /// Whether this workspace is at its capacity, without counting the window with this ID.
+    fn workspace_full(&self, workspace: u32, id: WindowID, t: &TCellOwner<T>) -> bool {
+        let capacity = ward::ward!(self.workspace_capacity.get(&workspace), else {
+            return false;
+        });
+
+        let floating = self.tiling_disabled.contains_key(&workspace);
+        let held = self
+            .windows
+            .iter()
+            .filter(|(&other, _)| other != id)
+            .map(|(_, other)| other.ro(t))
+            .filter(|other| other.workspace == workspace)
+            .filter(|other| floating || other.fork.is_some())
+            .count();
+
+        held >= *capacity
+    }
+
+    /// The workspace that a window attached to this workspace overflows to, if this
+    /// workspace is at its capacity. Overflow workspaces which are full overflow in turn,
+    /// and are created on the display of the workspace where none was designated.
+    fn overflow_target(
+        &mut self,
+        workspace: u32,
+        window: &WindowPtr<T>,
+        t: &mut TCellOwner<T>,
+    ) -> Option<WorkspacePtr<T>> {
+        let id = window.id(t);
+        let mut current = workspace;
+        let mut visited = BTreeSet::new();
+
+        loop {
+            if !self.workspace_full(current, id, t) {
+                return Some(current)
+                    .filter(|&current| current != workspace)
+                    .and_then(|current| self.workspaces.get(&current).cloned());
+            }
+
+            if !visited.insert(current) {
+                tracing::error!(
+                    "every overflow workspace of workspace {} is full",
+                    workspace
+                );
+                return None;
+            }
+
+            current = match self.overflow_workspaces.get(&current) {
+                Some(&next) if self.workspaces.contains_key(&next) => next,
+                _ => {
+                    let next = self.workspace_create_beside(current, t)?.id(t);
+                    self.overflow_workspaces.insert(current, next);
+                    next
+                }
+            };
+        }
+    }
+
+    /// Raises and focuses a window which was attached to a stack if new tabs are raised,
+    /// or hides it behind the raised tab otherwise.
+    fn attach_raise(&mut self, window: &WindowPtr<T>, t: &mut TCellOwner<T>) {
+        let stack = ward::ward!(window.stack(t), else { return });
+
+        match self.new_stack_focus {
+            NewStackFocus::New => {
+                stack.rw(t).active = window.clone();
+                self.event_queue.stack_raise_window(&stack, window, t);
+                window.focus(self, t);
+            }
+
+            NewStackFocus::Existing => {
+                if !Rc::ptr_eq(&stack.ro(t).active, window) && window.ro(t).visible {
+                    window.rw(t).visible = false;
+                    let id = window.id(t);
+                    self.event_queue.windows.entry(id).or_default().visibility = Some(false);
+                }
+            }
+        }
+    }
+
+    /// Attach the window with this ID, creating the window if it is not yet known.
+    pub fn attach_by_id(&mut self, id: WindowID, t: &mut TCellOwner<T>) {
+        let window = self.window(id);
+        self.attach(&window, t);
+    }
+
+    /// Float a window on a workspace, at this rect relative to the origin of the workspace,
+    /// or else cascading from the windows floated before it.
+    fn attach_floating(
+        &mut self,
+        window: &WindowPtr<T>,
+        workspace: u32,
+        rect: Option<Rect>,
+        t: &mut TCellOwner<T>,
+    ) {
+        let area = ward::ward!(self.workspaces.get(&workspace), else { return }).area(t);
+
+        // Windows floated over a tiled workspace are kept apart from its tree.
+        let floated = match self.tiling_disabled.get_mut(&workspace) {
+            Some(floating) => &mut floating.windows,
+            None => &mut self.floated,
+        };
+
+        let cascade = (area.width.min(area.height) / 2).max(1);
+        let offset = (CASCADE_STEP * floated.len() as u32) % cascade;
+        floated.push(window.clone());
+
+        window.rw(t).workspace = workspace;
+
+        let rect = match rect {
+            Some(rect) => Rect::new(area.x + rect.x, area.y + rect.y, rect.width, rect.height),
+            None => match self.default_float_rect {
+                Some(rect) => Rect::new(
+                    area.x + rect.x + offset,
+                    area.y + rect.y + offset,
+                    rect.width,
+                    rect.height,
+                ),
+                None => Rect::new(
+                    area.x + offset,
+                    area.y + offset,
+                    area.width / 2,
+                    area.height / 2,
+                ),
+            },
+        };
+
+        window.work_area_update(self, rect, t);
+    }
+
+    /// Rebuild the tree of a workspace from its layout template, with this window added.
+    fn attach_to_template(&mut self, window: &WindowPtr<T>, workspace: u32, t: &mut TCellOwner<T>) {
+        let workspace_ = ward::ward!(self.workspaces.get(&workspace).cloned(), else {
+            tracing::error!("cannot attach to template of non-existent workspace");
+            return;
+        });
+
+        let layout = ward::ward!(self.layout_templates.remove(&workspace), else {
+            return;
+        });
+
+        if self.active.is_none() {
+            self.set_active_window(window, t);
+        }
+
+        let mut windows: Vec<WindowPtr<T>> = match workspace_.fork(t) {
+            Some(fork) => fork.windows(t).collect(),
+            None => Vec::new(),
+        };
+
+        for attached in &windows {
+            self.detach_from_tree(attached, t);
+        }
+
+        windows.push(window.clone());
+
+        let area = workspace_.area(t);
+        let filled = windows.len() >= layout.slots();
+        let root = layout.build_template(self, area, workspace, windows, t);
+
+        {
+            let workspace_ = workspace_.rw(t);
+            workspace_.fork = root.clone();
+
+            if workspace_.focus.is_none() {
+                workspace_.focus = Some(window.clone());
+            }
+        }
+
+        if let Some(root) = root {
+            root.work_area_update(self, area, t);
+        }
+
+        if !filled {
+            self.layout_templates.insert(workspace, layout);
+        }
+    }
+
+    /// Attach a window to an existing window
+    fn attach_to_window(
+        &mut self,
+        new_window: &WindowPtr<T>,
+        attach_to: &WindowPtr<T>,
+        t: &mut TCellOwner<T>,
+    ) {
+        let id = new_window.id(t);
+
+        let plan = ward::ward!(self.tile_attach(id, attach_to, t), else {
+            tracing::error!("attempted attach to window that's not attached to anything");
+            return;
+        });
+
+        match plan {
+            TileAttach::Join(stack) => self.attach_to_stack(new_window, &stack, t),
+
+            TileAttach::StackOnto => {
+                attach_to.stack_toggle(self, t);
+
+                if let Some(stack) = attach_to.stack(t) {
+                    self.attach_to_stack(new_window, &stack, t);
+                }
+            }
+
+            TileAttach::FillRight(fork) => {
+                fork.rw(t).right = Some(Branch::Window(new_window.clone()));
+                new_window.fork_set(fork.clone(), t);
+                fork.work_area_refresh(self, t);
+                self.gravity_apply(&fork, new_window, t);
+            }
+
+            TileAttach::Split(fork, branch) => self.split_branch(branch, new_window, &fork, t),
+        }
+    }
+
+    /// How a window with this ID joins the tree when attached beside this window. `None`
+    /// if the window is not in a tree.
+    fn tile_attach(
+        &self,
+        id: WindowID,
+        attach_to: &WindowPtr<T>,
+        t: &TCellOwner<T>,
+    ) -> Option<TileAttach<T>> {
+        let fork = attach_to.fork(t)?;
+
+        // If window is attached to stack, then attach new window to the same stack
+        if let Some(stack) = attach_to.stack(t) {
+            // Unless stacks group the windows of a client, and the window is of another.
+            if !self.auto_stack_same_client || id.0 == attach_to.id(t).0 {
+                return Some(TileAttach::Join(stack));
+            }
+
+            if fork.ro(t).right.is_none() {
+                return Some(TileAttach::FillRight(fork));
+            }
+
+            return Some(TileAttach::Split(fork, Branch::Stack(stack)));
+        }
+
+        // Windows of the same client are grouped into a stack.
+        if self.auto_stack_same_client && id.0 == attach_to.id(t).0 {
+            return Some(TileAttach::StackOnto);
+        }
+
+        // If the right branch is empty, assign our new window to it.
+        if fork.ro(t).right.is_none() {
+            return Some(TileAttach::FillRight(fork));
+        }
+
+        // Stack onto the window instead if another fork would make the tree too deep.
+        if self
+            .max_tree_depth
+            .map_or(false, |max| fork.depth(t) >= max)
+        {
+            return Some(TileAttach::StackOnto);
+        }
+
+        // Or if another tile would make the tiles of the workspace too small on average.
+        if let Some(threshold) = self.auto_stack_threshold {
+            let workspace = fork.ro(t).workspace;
+            if self.average_tile_area(workspace, 1, t) < u64::from(threshold) {
+                return Some(TileAttach::StackOnto);
+            }
+        }
+
+        Some(TileAttach::Split(fork, Branch::Window(attach_to.clone())))
+    }
+
+    /// Attach a window to a stack, as a tab of it.
+    fn attach_to_stack(
+        &mut self,
+        window: &WindowPtr<T>,
+        stack: &StackPtr<T>,
+        t: &mut TCellOwner<T>,
+    ) {
+        window.fork_set(stack.ro(t).parent.clone(), t);
+        stack.attach(window, t);
+        self.event_queue.stack_assign(stack, window, t);
+        self.stack_sort(stack, t);
+        stack.work_area_update(self, stack.ro(t).area, t);
+    }
+
+    /// Replaces a branch of the `fork` with a new fork of that branch and the `window`.
+    fn split_branch(
+        &mut self,
+        branch: Branch<T>,
+        window: &WindowPtr<T>,
+        fork: &ForkPtr<T>,
+        t: &mut TCellOwner<T>,
+    ) {
+        let workspace = fork.ro(t).workspace;
+
+        // Create a new fork branch and assign the branch and the window to it.
+        let new_fork = ForkPtr::new({
+            let area = Rect::new(1, 1, 1, 1);
+            let mut fork = Fork::new(area, branch.clone(), workspace);
+            fork.right = Some(Branch::Window(window.clone()));
+            fork
+        });
+
+        self.fork_register(new_fork.clone(), t);
+
+        new_fork.rw(t).parent = Some(fork.clone());
+        branch.parent_set(&new_fork, t);
+        window.fork_set(new_fork.clone(), t);
+
+        // Then assign the new branch to the fork where the branch was.
+        {
+            let fork_ = fork.rw(t);
+
+            let slot = if fork_.left == branch {
+                Some(&mut fork_.left)
+            } else {
+                fork_.right.as_mut().filter(|right| **right == branch)
+            };
+
+            match slot {
+                Some(slot) => *slot = Branch::Fork(new_fork.clone()),
+                None => tracing::error!("invalid parent fork association in branch"),
+            }
+        }
+
+        // Refresh work areas
+        fork.work_area_refresh(self, t);
+
+        // Reassign fork orientation and refresh again. TODO: Avoid redoing refresh
+        new_fork.reset_orientation(self, t);
+        new_fork.work_area_refresh(self, t);
+        self.gravity_apply(&new_fork, window, t);
+    }
+
+    /// Arranges a fork such that a window attached to it lies on the side of its gravity.
+    fn gravity_apply(&mut self, fork: &ForkPtr<T>, window: &WindowPtr<T>, t: &mut TCellOwner<T>) {
+        let (orientation, left) = ward::ward!(window.ro(t).gravity.split_side(), else { return });
+
+        let fork_ = fork.rw(t);
+
+        if fork_.orientation != orientation {
+            fork_.orientation = orientation;
+            fork_.split_handle = orientation.half(fork_.area);
+        }
+
+        if fork_.left_is(BranchRef::Window(window)) != left {
+            if let Some(right) = fork_.right.as_mut() {
+                std::mem::swap(right, &mut fork_.left);
+            }
+        }
+
+        fork.work_area_refresh(self, t);
+    }
+
+    /// The average area of the tiles on a workspace, counting each stack as one tile, if
+    /// there were this many more tiles.
+    fn average_tile_area(&self, workspace: u32, extra: u64, t: &TCellOwner<T>) -> u64 {
+        let area = ward::ward!(self.workspaces.get(&workspace), else { return 0 }).area(t);
+
+        let windows = self
+            .windows
+            .values()
+            .map(|window| window.ro(t))
+            .filter(|window| window.workspace == workspace)
+            .filter(|window| window.fork.is_some() && window.stack.is_none())
+            .count();
+
+        let stacks = self
+            .stacks
+            .values()
+            .filter(|stack| stack.ro(t).workspace == workspace)
+            .count();
+
+        let tiles = windows as u64 + stacks as u64 + extra;
+
+        u64::from(area.width) * u64::from(area.height) / tiles.max(1)
+    }
+
+    /// Attach a window beside a target window, split where a cursor dropped it.
+    ///
+    /// The target is split at the cursor, along the edge that the cursor is nearest, and
+    /// the window takes the side of the split that the cursor is on. A window dropped on a
+    /// stacked window joins its stack.
+    pub fn attach_at_point(
+        &mut self,
+        window: &WindowPtr<T>,
+        target: &WindowPtr<T>,
+        cursor: Point,
+        t: &mut TCellOwner<T>,
+    ) {
+        if Rc::ptr_eq(window, target) || target.fork(t).is_none() {
+            tracing::error!("cannot attach beside a window that is not tiled");
+            return;
+        }
+
+        // The window may be dragged from elsewhere in the tree.
+        self.detach_from_tree(window, t);
+        self.windows.insert(window.id(t), window.clone());
+
+        let fork = ward::ward!(target.fork(t), else { return });
+
+        if target.stack(t).is_some() {
+            self.attach_to_window(window, target, t);
+            self.dispatch_events(t);
+            return;
+        }
+
+        let rect = target.ro(t).rect;
+        let x = cursor.x.saturating_sub(rect.x).min(rect.width);
+        let y = cursor.y.saturating_sub(rect.y).min(rect.height);
+
+        // Split along the edge that the cursor is nearest, relative to the size of the window.
+        let horizontal = u64::from(x.min(rect.width - x)) * u64::from(rect.height)
+            <= u64::from(y.min(rect.height - y)) * u64::from(rect.width);
+
+        let (orientation, split, length) = if horizontal {
+            (Orientation::Horizontal, x, rect.width)
+        } else {
+            (Orientation::Vertical, y, rect.height)
+        };
+
+        let dropped = Branch::Window(window.clone());
+        let occupant = Branch::Window(target.clone());
+
+        let (left, right) = if split * 2 < length {
+            (dropped, occupant)
+        } else {
+            (occupant, dropped)
+        };
+
+        let fork_ = fork.rw(t);
+
+        if fork_.right.is_none() {
+            // The target is alone in its fork, which is split instead.
+            fork_.split_handle = match orientation {
+                Orientation::Horizontal => rect.x + split - fork_.area.x,
+                Orientation::Vertical => rect.y + split - fork_.area.y,
+            };
+
+            fork_.orientation = orientation;
+            fork_.left = left;
+            fork_.right = Some(right);
+
+            window.fork_set(fork.clone(), t);
+            fork.work_area_refresh(self, t);
+            self.dispatch_events(t);
+            return;
+        }
+
+        let workspace = fork_.workspace;
+
+        let new_fork = ForkPtr::new({
+            let mut new_fork = Fork::new(rect, left.clone(), workspace);
+            new_fork.right = Some(right.clone());
+            new_fork.orientation = orientation;
+            new_fork.split_handle = split;
+            new_fork.parent = Some(fork.clone());
+            new_fork
+        });
+
+        match fork_.branch(BranchRef::Window(target)) {
+            Some(Either::Left(branch)) | Some(Either::Right(branch)) => {
+                *branch = Branch::Fork(new_fork.clone())
+            }
+            None => tracing::error!("invalid parent fork association in window"),
+        }
+
+        self.fork_register(new_fork.clone(), t);
+        left.parent_set(&new_fork, t);
+        right.parent_set(&new_fork, t);
+
+        fork.work_area_refresh(self, t);
+        self.dispatch_events(t);
+    }
+
+    /// Attach the window with this ID to a specific branch of a fork.
+    ///
+    /// An empty branch is assigned the window directly. An occupied branch is split into a
+    /// new fork, with the window on its right. The window is only created if it is attached.
+    pub fn attach_to_fork_branch(
+        &mut self,
+        window: WindowID,
+        fork: usize,
+        side: Side,
+        t: &mut TCellOwner<T>,
+    ) -> Result<(), PlacementError> {
+        let fork = self
+            .forks
+            .get(&fork)
+            .cloned()
+            .ok_or(PlacementError::ForkNotFound(fork))?;
+
+        if let Some(window) = self.windows.get(&window) {
+            if window.fork(t).is_some() {
+                return Err(PlacementError::WindowInTree(window.id(t)));
+            }
+        }
+
+        let window = self.window(window);
+        let fork_ = fork.rw(t);
+
+        let occupant = match side {
+            Side::Left => fork_.left.clone(),
+            Side::Right => match fork_.right.clone() {
+                Some(branch) => branch,
+                None => {
+                    fork_.right = Some(Branch::Window(window.clone()));
+                    window.fork_set(fork.clone(), t);
+                    fork.work_area_refresh(self, t);
+                    self.dispatch_events(t);
+                    return Ok(());
+                }
+            },
+        };
+
+        // The occupying branch is split, with this window on the right.
+        self.split_branch(occupant, &window, &fork, t);
+
+        self.dispatch_events(t);
+        Ok(())
+    }
+
+    /// Attach a window a tree on a display.
+    fn attach_to_workspace(
+        &mut self,
+        window: &WindowPtr<T>,
+        workspace: &WorkspacePtr<T>,
+        t: &mut TCellOwner<T>,
+    ) {
+        let area = workspace.area(t);
+
+        // Assign window to an existing fork on the workspace.
+        if let Some(fork) = workspace.fork(t) {
+            if let Some(attach_to) = fork.largest_window(t) {
+                self.attach_to_window(window, &attach_to, t);
+                fork.work_area_refresh(self, t);
+                return;
+            }
+        }
+
+        // Create a new fork and assign that, otherwise.
+        let branch = Branch::Window(window.clone());
+        let mut fork = Fork::new(area, branch, workspace.id(t));
+
+        if let Some(orientation) = self.empty_workspace_orientation {
+            fork.orientation = orientation;
+            fork.split_handle = orientation.half(area);
+        }
+
+        let fork = ForkPtr::new(fork);
+        self.fork_register(fork.clone(), t);
+
+        window.fork_set(fork.clone(), t);
+
+        let workspace = workspace.rw(t);
+        workspace.focus = Some(window.clone());
+        workspace.fork = Some(fork.clone());
+
+        fork.work_area_refresh(self, t);
+    }
+
+    /// Reports the decoration of each window whose decoration changed.
+    fn decorations_refresh(&mut self, t: &mut TCellOwner<T>) {
+        let mut tiled = BTreeMap::<u32, usize>::new();
+
+        if self.auto_hide_single_border {
+            for window in self.windows.values() {
+                let window = window.ro(t);
+                if window.fork.is_some() {
+                    *tiled.entry(window.workspace).or_default() += 1;
+                }
+            }
+        }
+
+        for (id, window) in self.windows.iter() {
+            let this = window.ro(t);
+
+            let alone = this.fork.is_some() && tiled.get(&this.workspace) == Some(&1);
+
+            let decoration = this.decoration_override.unwrap_or(if alone {
+                Decoration::NoBorder
+            } else {
+                Decoration::Border
+            });
+
+            if this.decoration != decoration {
+                window.rw(t).decoration = decoration;
+                self.event_queue.windows.entry(*id).or_default().decoration = Some(decoration);
+            }
+        }
+
+        self.borders_refresh(t);
+    }
+
+    /// Reports the edges of the border of each window whose edges changed. With smart
+    /// borders, edges which lie on the boundary of the tiled region of the workspace have
+    /// no border; otherwise every edge has one.
+    fn borders_refresh(&mut self, t: &mut TCellOwner<T>) {
+        for (id, window) in self.windows.iter() {
+            let this = window.ro(t);
+
+            if !self.smart_borders && this.borders.is_none() {
+                continue;
+            }
+
+            let borders = if !self.smart_borders || this.fork.is_none() {
+                EdgeSet::ALL
+            } else if this.fullscreen {
+                EdgeSet::default()
+            } else {
+                let region = self
+                    .workspaces
+                    .get(&this.workspace)
+                    .and_then(|workspace| workspace.fork(t))
+                    .map(|fork| fork.ro(t).area);
+
+                let region = ward::ward!(region, else { continue });
+
+                // Stacked windows are measured by their stack, which includes its tab bar.
+                let area = match this.stack.as_ref() {
+                    Some(stack) => stack.ro(t).area,
+                    None => this.outer_rect(),
+                };
+
+                EdgeSet {
+                    top: area.y > region.y,
+                    bottom: area.y_end() < region.y_end(),
+                    left: area.x > region.x,
+                    right: area.x_end() < region.x_end(),
+                }
+            };
+
+            if this.borders != Some(borders) {
+                window.rw(t).borders = Some(borders);
+                self.event_queue.windows.entry(*id).or_default().borders = Some(borders);
+            }
+        }
+    }
+
+    /// Annotates an event with what its IDs currently refer to in the tree.
+    pub fn describe_event(&self, event: &Event, t: &TCellOwner<T>) -> String {
+        let context = match event {
+            Event::Fork(id, _) | Event::ForkDestroy(id) => match self.forks.get(id) {
+                Some(fork) => {
+                    let windows: Vec<WindowID> = fork.windows(t).map(|w| w.id(t)).collect();
+                    format!("fork {} contains {:?}", id, windows)
+                }
+                None => format!("fork {} does not exist", id),
+            },
+
+            Event::StackAssign(id, _)
+            | Event::StackDetach(id, _)
+            | Event::StackDestroy(id)
+            | Event::StackMirror(id, ..)
+            | Event::StackPlace(id, ..)
+            | Event::StackRaise(id, _)
+            | Event::StackMovement(id, _)
+            | Event::StackVisibility(id, _) => match self.stacks.get(id) {
+                Some(stack) => {
+                    let windows: Vec<WindowID> =
+                        stack.ro(t).windows.iter().map(|w| w.id(t)).collect();
+                    format!("stack {} contains {:?}", id, windows)
+                }
+                None => format!("stack {} does not exist", id),
+            },
+
+            Event::Focus(id, _)
+            | Event::WindowBorders(id, _)
+            | Event::WindowDecoration(id, _)
+            | Event::WindowMirror(id, ..)
+            | Event::WindowPlace(id, _)
+            | Event::WindowUrgency(id, _)
+            | Event::WindowVisibility(id, _) => match self.windows.get(id) {
+                Some(window) => {
+                    let window = window.ro(t);
+                    format!(
+                        "{:?} is on workspace {} in fork {:?}",
+                        id,
+                        window.workspace,
+                        window.fork.as_ref().map(|fork| fork.id(t))
+                    )
+                }
+                None => format!("{:?} is not managed by the tiler", id),
+            },
+
+            Event::InsertPreview(_) => format!(
+                "next window attaches to workspace {}",
+                self.attach_anchor
+                    .as_ref()
+                    .map_or(self.active_workspace, |anchor| anchor.ro(t).workspace)
+            ),
+
+            Event::FocusRect(_) => format!(
+                "active window is {:?}",
+                self.active_window().map(|window| window.id(t))
+            ),
+
+            Event::FocusWorkspace(workspace) | Event::WorkspaceAssign { workspace, .. } => {
+                let windows: Vec<WindowID> = self
+                    .windows
+                    .values()
+                    .filter(|window| window.ro(t).workspace == *workspace)
+                    .map(|window| window.id(t))
+                    .collect();
+
+                format!("workspace {} has {:?}", workspace, windows)
+            }
+        };
+
+        format!("{:?}: {}", event, context)
+    }
+
+    /// Detach a window from its tree, and removes its association with this tiler.
+    pub fn detach(&mut self, window: &WindowPtr<T>, t: &mut TCellOwner<T>) {
+        let refocus = self.detach_refocus_target(window, t);
+        self.detach_window(window, t);
+        self.detach_refocus(refocus, window.ro(t).workspace, t);
+        self.dispatch_events(t);
+    }
+
+    /// If this window is the active window, the window to focus once it is detached. The
+    /// inner `None` defers the choice to the previously focused window.
+    fn detach_refocus_target(
+        &self,
+        window: &WindowPtr<T>,
+        t: &TCellOwner<T>,
+    ) -> Option<Option<WindowPtr<T>>> {
+        let active = self.active.as_ref()?;
+
+        if !Rc::ptr_eq(active, window) {
+            return None;
+        }
+
+        Some(match self.detach_refocus {
+            DetachRefocus::PreviousFocus => None,
+            DetachRefocus::Direction(direction) => {
+                let (distance, filter) = direction_fns(direction);
+
+                self.window_in_direction(distance, filter, t)
+            }
+        })
+    }
+
+    /// Focuses the window chosen before the active window was detached, or the previously
+    /// focused window on its workspace if none was chosen or it was detached as well.
+    fn detach_refocus(
+        &mut self,
+        refocus: Option<Option<WindowPtr<T>>>,
+        workspace: u32,
+        t: &mut TCellOwner<T>,
+    ) {
+        let target = ward::ward!(refocus, else { return });
+
+        let target = target
+            .filter(|target| self.windows.contains_key(&target.id(t)))
+            .or_else(|| {
+                self.focus_history
+                    .iter()
+                    .filter_map(|id| self.windows.get(id))
+                    .find(|window| window.ro(t).workspace == workspace)
+                    .cloned()
+            });
+
+        if let Some(target) = target {
+            target.focus(self, t);
+        }
+    }
+
+    fn detach_window(&mut self, window: &WindowPtr<T>, t: &mut TCellOwner<T>) {
+        // Remove the window from management of the tiler.
+        let id = window.id(t);
+        self.windows.remove(&id);
+        self.focus_history.retain(|&recent| recent != id);
+
+        for floating in self.tiling_disabled.values_mut() {
+            floating
+                .windows
+                .retain(|floating| !Rc::ptr_eq(floating, window));
+        }
+
+        self.floated.retain(|floated| !Rc::ptr_eq(floated, window));
+
+        // If window being detached is the attach anchor, restore focus-based attaching
+        if let Some(anchor) = self.attach_anchor.as_ref() {
+            if Rc::ptr_eq(window, anchor) {
+                self.attach_anchor = None;
+            }
+        }
+
+        // The windows hidden beneath a fullscreen window are shown again without it.
+        if window.ro(t).fullscreen {
+            window.rw(t).fullscreen = false;
+            let workspace = window.ro(t).workspace;
+            self.fullscreen_siblings_show(workspace, t);
+        }
+
+        self.detach_from_tree(window, t);
+
+        // If window being detached is the active window, remove focus
+        if let Some(active) = self.active.as_ref() {
+            if Rc::ptr_eq(window, active) {
+                self.active = None;
+                self.active_changed = false;
+            }
+        }
+
+        // Windows are no longer placed once they are detached.
+        self.event_queue.window_forget(id);
+    }
+
+    /// Detach every window with these IDs, such as windows destroyed together by their
+    /// clients, and refresh each affected workspace once all of them are detached.
+    pub fn reap(&mut self, ids: &[WindowID], t: &mut TCellOwner<T>) {
+        let mut workspaces = Vec::new();
+        let mut refocus = None;
+
+        self.refresh_deferred = true;
+
+        for id in ids {
+            if let Some(window) = self.windows.get(id).cloned() {
+                let workspace = window.ro(t).workspace;
+                if !workspaces.contains(&workspace) {
+                    workspaces.push(workspace);
+                }
+
+                if let Some(target) = self.detach_refocus_target(&window, t) {
+                    refocus = Some((target, workspace));
+                }
+
+                self.detach_window(&window, t);
+            }
+        }
+
+        self.refresh_deferred = false;
+
+        for workspace in workspaces {
+            if let Some(workspace) = self.workspaces.get(&workspace).cloned() {
+                if let Some(fork) = workspace.fork(t) {
+                    fork.work_area_update(self, workspace.area(t), t);
+                }
+            }
+        }
+
+        if let Some((target, workspace)) = refocus {
+            self.detach_refocus(Some(target), workspace, t);
+        }
+
+        self.dispatch_events(t);
+    }
+
+    /// Detach a window from the stack or fork that it is attached to.
+    fn detach_from_tree(&mut self, window: &WindowPtr<T>, t: &mut TCellOwner<T>) {
+        if let Some(stack) = window.stack(t) {
+            window.fork_take(t);
+            stack.detach(self, window, t);
+            return;
+        }
+
+        if let Some(fork) = window.fork_take(t) {
+            self.detach_branch(fork, BranchRef::Window(window), t);
+        }
+    }
+
+    /// Detach the window with this ID from its tree.
+    pub fn detach_by_id(
+        &mut self,
+        id: WindowID,
+        t: &mut TCellOwner<T>,
+    ) -> Result<(), WindowNotFound> {
+        let window = self.window_by_id(id)?;
+        self.detach(&window, t);
+        Ok(())
+    }
+
+    /// Detach a window from a fork.
+    fn detach_fork(&mut self, fork: ForkPtr<T>, t: &mut TCellOwner<T>) {
+        eprintln!("requested to detach fork");
+        let mut detaching = Some(fork);
+
+        while let Some(fork) = detaching.take() {
+            self.fork_destroy(&fork, t);
+
+            if let Some(parent) = fork.rw(t).parent.take() {
+                let parent_ = parent.rw(t);
+                if parent_.left_is(BranchRef::Fork(&fork)) {
+                    if let Some(right) = parent_.right.take() {
+                        parent_.left = right;
+                    }
+                } else if parent_.right_is(BranchRef::Fork(&fork)) {
+                    parent_.right = None;
+                }
+            } else {
+                for workspace in self.workspaces.values() {
+                    if let Some(ref root) = workspace.fork(t) {
+                        if Rc::ptr_eq(root, &fork) {
+                            workspace.rw(t).fork = None;
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Detach a window or stack from a fork
+    pub(crate) fn detach_branch(
+        &mut self,
+        fork: ForkPtr<T>,
+        reference: BranchRef<'_, T>,
+        t: &mut TCellOwner<T>,
+    ) {
+        tracing::debug!("detaching branch from Fork({})", fork.id(t));
+
+        // After removing a window, the fork is left with a single branch. When this
+        // happens, we will discard the fork and place the remaining grandchild in its
+        // place. Then the child association of the grandparent fork is updated to point
+        // to the grandchild who is now a direct child.
+        fn reparent<T>(
+            tiler: &mut Tiler<T>,
+            grandparent: Option<ForkPtr<T>>,
+            parent: ForkPtr<T>,
+            grandchild: Branch<T>,
+            t: &mut TCellOwner<T>,
+        ) {
+            match grandparent {
+                Some(grandparent) => {
+                    // Update child association of the grandparent fork
+                    let grandparent_ = grandparent.rw(t);
+                    match grandparent_.branch(BranchRef::Fork(&parent)) {
+                        Some(Either::Left(branch)) | Some(Either::Right(branch)) => {
+                            *branch = grandchild.clone()
+                        }
+                        None => tracing::error!("fork contained parent that doesn't own it"),
+                    }
+
+                    // Update the parent association of the grandchild now a child
+                    grandchild.parent_set(&grandparent, t);
+
+                    grandparent.work_area_refresh(tiler, t);
+                }
+                None => {
+                    // Only a fork may replace the root fork of a workspace.
+                    let grandchild = match grandchild {
+                        Branch::Fork(grandchild) => grandchild,
+                        _ => return,
+                    };
+
+                    let mut display = None;
+
+                    for info in tiler.workspaces.values() {
+                        if let Some(ref workspace_fork) = info.ro(t).fork {
+                            if !Rc::ptr_eq(workspace_fork, &parent) {
+                                continue;
+                            }
+
+                            display = Some(info.area(t));
 
                             grandchild.rw(t).parent = None;
                             info.rw(t).fork = Some(grandchild.clone());
@@ -305,438 +1800,3374 @@ impl<T: 'static> Tiler<T> {
                         }
                     }
 
-                    if let Some(display) = display {
-                        grandchild.work_area_update(tiler, display, t);
-                    }
-                }
-            }
+                    if let Some(display) = display {
+                        grandchild.work_area_update(tiler, display, t);
+                    }
+                }
+            }
+
+            // Remove the orphaned fork
+            parent.rw(t).parent.take();
+            tiler.detach_fork(parent, t);
+        }
+
+        let fork_ = fork.rw(t);
+
+        if fork_.left_is(reference) {
+            tracing::debug!("detaching left branch of fork");
+            match fork_.right.take() {
+                Some(right) => {
+                    tracing::debug!("right branch of fork was assigned to left branch");
+                    fork_.left = right;
+                }
+                None => {
+                    tracing::debug!("fork is now childless");
+                    self.detach_fork(fork, t);
+                    return;
+                }
+            }
+        } else if fork_.right_is(reference) {
+            tracing::debug!("detaching right branch of fork");
+            fork_.right = None;
+        } else {
+            return;
+        }
+
+        // A window or stack may remain alone at the root of a workspace. Otherwise, the
+        // remaining branch takes the place of its fork.
+        let fork_ = fork.ro(t);
+        match (fork_.parent.clone(), fork_.left.clone()) {
+            (None, Branch::Window(_)) | (None, Branch::Stack(_)) => {
+                fork.work_area_refresh(self, t);
+            }
+            (grandparent, grandchild) => {
+                tracing::debug!("reparenting remaining branch of fork into parent");
+                reparent(self, grandparent, fork, grandchild, t);
+            }
+        }
+    }
+
+    /// Detaches every window on a workspace, and returns their IDs. The workspace remains,
+    /// without a tree.
+    pub fn clear_workspace(&mut self, workspace: u32, t: &mut TCellOwner<T>) -> Vec<WindowID> {
+        let workspace_ = ward::ward!(self.workspaces.get(&workspace).cloned(), else {
+            tracing::error!("cannot clear non-existent workspace {}", workspace);
+            return Vec::new();
+        });
+
+        let mut windows: Vec<WindowPtr<T>> = match workspace_.fork(t) {
+            Some(root) => root.windows(t).collect(),
+            None => Vec::new(),
+        };
+
+        if let Some(floating) = self.tiling_disabled.get(&workspace) {
+            windows.extend(floating.windows.iter().cloned());
+        }
+
+        windows.extend(
+            self.floated
+                .iter()
+                .filter(|floated| floated.ro(t).workspace == workspace)
+                .cloned(),
+        );
+
+        let ids: Vec<WindowID> = windows.iter().map(|window| window.id(t)).collect();
+
+        for window in &windows {
+            self.detach_window(window, t);
+        }
+
+        self.tabbed.remove(&workspace);
+
+        let workspace_ = workspace_.rw(t);
+        workspace_.fork = None;
+        workspace_.focus = None;
+
+        self.dispatch_events(t);
+
+        ids
+    }
+
+    /// Copies the split structure of the source workspace to an empty destination workspace.
+    ///
+    /// Windows attached to the destination fill the slots of the copied structure, in
+    /// depth-first order, until every slot is filled.
+    pub fn clone_layout_to_workspace(&mut self, src: u32, dst: u32, t: &mut TCellOwner<T>) {
+        let fork = ward::ward!(self.workspaces.get(&src).and_then(|w| w.fork(t)), else {
+            tracing::error!("cannot clone layout of workspace {} without a tree", src);
+            return;
+        });
+
+        let destination = ward::ward!(self.workspaces.get(&dst), else {
+            tracing::error!("cannot clone layout to non-existent workspace {}", dst);
+            return;
+        });
+
+        if destination.fork(t).is_some() {
+            tracing::error!("cannot clone layout to workspace {} that has windows", dst);
+            return;
+        }
+
+        self.layout_templates.insert(dst, Layout::capture(&fork, t));
+    }
+
+    /// The area of a display.
+    pub fn display_area(&self, display: u32, t: &TCellOwner<T>) -> Option<Rect> {
+        self.displays.get(&display).map(|display| display.area(t))
+    }
+
+    /// Removes a display from the tree, and migrates its workspaces to another display
+    pub fn display_detach(&mut self, display_id: u32, t: &mut TCellOwner<T>) {
+        // Get the active display to assign to.
+        let active = self
+            .workspaces
+            .get(&self.active_workspace)
+            .expect("active workspace doesn't exist")
+            .ro(t)
+            .parent
+            .clone();
+
+        // Remove the display from the tiler.
+        let display_ptr = ward::ward!(self.displays.remove(&display_id), else {
+            tracing::error!("detach of non-existent display: {}", display_id);
+            return;
+        });
+
+        // Take ownership of its workspaces.
+        let mut workspaces = HashMap::new();
+        std::mem::swap(&mut workspaces, &mut display_ptr.rw(t).workspaces);
+
+        // Migrate workspaces.
+        for workspace in workspaces.into_values() {
+            active.assign_workspace(workspace, t);
+        }
+        self.dispatch_events(t);
+    }
+
+    /// Creates or updates a display associated with the tree.
+    ///
+    /// When a display is added, the workspaces which prefer it are assigned back to it.
+    pub fn display_update(&mut self, display: u32, area: Rect, t: &mut TCellOwner<T>) {
+        let added = !self.displays.contains_key(&display);
+
+        let display_ = self
+            .displays
+            .entry(display)
+            .or_insert_with(|| DisplayPtr::new(area))
+            .clone();
+
+        if added {
+            let affine: Vec<u32> = self
+                .workspace_affinity
+                .iter()
+                .filter(|(_, &preferred)| preferred == display)
+                .map(|(&workspace, _)| workspace)
+                .collect();
+
+            for workspace in affine {
+                let workspace_ = ward::ward!(self.workspaces.get(&workspace).cloned(), else {
+                    continue;
+                });
+
+                let previous = workspace_.ro(t).parent.clone();
+                display_.assign_workspace(workspace_, t);
+
+                // The display it leaves shows another of its workspaces instead.
+                if previous.ro(t).active == Some(workspace) {
+                    let next = previous.ro(t).workspaces.keys().min().copied();
+                    previous.rw(t).active = next;
+                }
+
+                if display_.ro(t).active.is_none() {
+                    display_.rw(t).active = Some(workspace);
+                }
+
+                self.event_queue
+                    .events
+                    .push(Event::WorkspaceAssign { workspace, display });
+            }
+        }
+
+        display_.work_area_update(self, area, t);
+        self.dispatch_events(t);
+    }
+
+    /// Offsets every display, and the windows on them, by the same distance, as when the
+    /// origin of the coordinates of the compositor moves.
+    ///
+    /// If a display or floating window would be moved to a negative coordinate, nothing is
+    /// moved and an error is returned.
+    pub fn translate_all_displays(
+        &mut self,
+        dx: i32,
+        dy: i32,
+        t: &mut TCellOwner<T>,
+    ) -> Result<(), NegativeCoordinate> {
+        let displays = self
+            .displays
+            .values()
+            .map(|display| Ok((display.clone(), display.ro(t).area.translate(dx, dy)?)))
+            .collect::<Result<Vec<(DisplayPtr<T>, Rect)>, _>>()?;
+
+        // Floating windows are not placed by the trees of their workspaces.
+        let floating = self
+            .tiling_disabled
+            .values()
+            .flat_map(|floating| floating.windows.iter())
+            .chain(self.floated.iter())
+            .map(|window| Ok((window.clone(), window.ro(t).outer_rect().translate(dx, dy)?)))
+            .collect::<Result<Vec<(WindowPtr<T>, Rect)>, _>>()?;
+
+        for (display, area) in displays {
+            display.work_area_update(self, area, t);
+        }
+
+        for (window, rect) in floating {
+            window.work_area_update(self, rect, t);
+        }
+
+        self.dispatch_events(t);
+        Ok(())
+    }
+
+    /// Recomputes the placement of every fork, stack, and window from the trees as they
+    /// are, and reports each placement and the visibility of every window again, including
+    /// those which were reported already.
+    pub fn reflow(&mut self, t: &mut TCellOwner<T>) {
+        self.event_queue.reported_reset();
+        self.mirrored = MirrorCopies::default();
+
+        for display in self.displays.values().cloned().collect::<Vec<_>>() {
+            display.work_area_refresh(self, t);
+        }
+
+        // Floating windows are not placed by the trees of their workspaces.
+        let floating: Vec<WindowPtr<T>> = self
+            .tiling_disabled
+            .values()
+            .flat_map(|floating| floating.windows.iter().cloned())
+            .chain(self.floated.iter().cloned())
+            .collect();
+
+        for window in floating {
+            let rect = window.ro(t).outer_rect();
+            window.work_area_update(self, rect, t);
+        }
+
+        for (&id, window) in self.windows.iter() {
+            let visible = window.ro(t).visible;
+            self.event_queue.windows.entry(id).or_default().visibility = Some(visible);
+        }
+
+        self.dispatch_events(t);
+    }
+
+    /// Recomputes the gaps of each workspace, and rearranges the trees whose gaps changed.
+    fn gaps_refresh(&mut self, t: &mut TCellOwner<T>) {
+        for workspace in self.workspaces.values().cloned().collect::<Vec<_>>() {
+            let id = workspace.id(t);
+
+            let gaps = match (self.gap_config, self.adaptive_gaps) {
+                (Some(config), _) => config,
+
+                (None, Some(gaps)) => {
+                    let tiled = self
+                        .windows
+                        .values()
+                        .filter(|w| w.ro(t).workspace == id && w.ro(t).fork.is_some())
+                        .count();
+
+                    GapConfig::uniform(gaps.gap(tiled))
+                }
+
+                (None, None) => GapConfig::default(),
+            };
+
+            if workspace.ro(t).gaps == gaps {
+                continue;
+            }
+
+            workspace.rw(t).gaps = gaps;
+
+            if let Some(fork) = workspace.fork(t) {
+                self.gap_transition = self.gap_animation;
+                fork.work_area_update(self, workspace.area(t), t);
+                self.gap_transition = false;
+            }
+        }
+    }
+
+    /// Completes an operation, rearranging the trees whose gaps it changed and reporting
+    /// the decorations it changed, and passes pending events to the event sink, if one
+    /// was set.
+    fn dispatch_events(&mut self, t: &mut TCellOwner<T>) {
+        self.gaps_refresh(t);
+        self.decorations_refresh(t);
+
+        if let Some(mut sink) = self.event_sink.take() {
+            for event in self.events(t) {
+                sink(event);
+            }
+
+            self.event_sink = Some(sink);
+        }
+    }
+
+    /// The events which recreate the current state of the tiler for a window manager
+    /// without any, such as one that connected after the tiler was started.
+    ///
+    /// Events which are pending retrieval are left pending.
+    pub fn emit_initial_state(&self, t: &TCellOwner<T>) -> Vec<Event> {
+        let mut events = Vec::new();
+
+        for (&display, display_) in self.displays.iter() {
+            let mut workspaces: Vec<u32> = display_.ro(t).workspaces.keys().copied().collect();
+            workspaces.sort_unstable();
+
+            for workspace in workspaces {
+                events.push(Event::WorkspaceAssign { workspace, display });
+            }
+        }
+
+        for (&id, fork) in self.forks.iter() {
+            let fork = fork.ro(t);
+            let update = ForkUpdate {
+                workspace: fork.workspace,
+                orientation: fork.orientation,
+                rect: fork.area,
+                handle: fork.split_handle,
+            };
+
+            events.push(Event::Fork(id, update));
+        }
+
+        for (&id, stack) in self.stacks.iter() {
+            let stack = stack.ro(t);
+            let placement = Placement {
+                area: stack.area,
+                workspace: stack.workspace,
+                from: None,
+                gap_transition: false,
+            };
+
+            events.push(Event::StackPlace(id, placement, self.tab_bar_position));
+
+            for window in stack.windows.iter() {
+                events.push(Event::StackAssign(id, window.id(t)));
+            }
+
+            events.push(Event::StackRaise(id, stack.active.id(t)));
+        }
+
+        for (&id, window) in self.windows.iter() {
+            let window = window.ro(t);
+            let placement = Placement {
+                area: window.rect,
+                workspace: window.workspace,
+                from: None,
+                gap_transition: false,
+            };
+
+            events.push(Event::WindowPlace(id, placement));
+            events.push(Event::WindowVisibility(id, window.visible));
+            events.push(Event::WindowDecoration(id, window.decoration));
+
+            if let Some(borders) = window.borders {
+                events.push(Event::WindowBorders(id, borders));
+            }
+
+            if window.urgent {
+                events.push(Event::WindowUrgency(id, true));
+            }
+        }
+
+        events.extend(self.mirror_copies(t).events(&MirrorCopies::default()));
+
+        if self.workspaces.contains_key(&self.active_workspace) {
+            events.push(Event::FocusWorkspace(self.active_workspace));
+        }
+
+        if let Some(active) = self.active_window() {
+            events.push(Event::Focus(active.id(t), None));
+            events.push(Event::FocusRect(active.ro(t).rect));
+        }
+
+        let minimum_visible_area = self.minimum_visible_area;
+        let visible = self.visible_areas(t);
+        let rounding = self.placement_rounding(t);
+
+        events
+            .into_iter()
+            .map(|event| keep_visible(event, &visible, minimum_visible_area))
+            .map(|event| align_placement(event, &rounding))
+            .collect()
+    }
+
+    /// The area of the display of each workspace, if placements are kept visible within it.
+    fn visible_areas(&self, t: &TCellOwner<T>) -> BTreeMap<u32, Rect> {
+        if self.minimum_visible_area == 0 {
+            return BTreeMap::new();
+        }
+
+        self.workspaces
+            .iter()
+            .map(|(id, workspace)| (*id, workspace.ro(t).parent.area(t)))
+            .collect()
+    }
+
+    /// The pixel grid of the display of each workspace, if its placements are aligned to one.
+    fn placement_rounding(&self, t: &TCellOwner<T>) -> BTreeMap<u32, (u32, RoundingMode)> {
+        self.workspaces
+            .iter()
+            .filter_map(|(id, workspace)| {
+                let rounding = workspace.ro(t).parent.ro(t).rounding;
+                rounding.map(|rounding| (*id, rounding))
+            })
+            .collect()
+    }
+
+    /// The copies of the stacks and windows on the active workspace of each mirrored
+    /// display, for each display mirroring it.
+    fn mirror_copies(&self, t: &TCellOwner<T>) -> MirrorCopies {
+        let mut copies = MirrorCopies::default();
+
+        for (&dst, &src) in self.display_mirrors.iter() {
+            let src = ward::ward!(self.displays.get(&src), else { continue });
+            let dst_area = ward::ward!(self.displays.get(&dst), else { continue }).area(t);
+            let workspace = ward::ward!(src.ro(t).active, else { continue });
+            let src_area = src.area(t);
+
+            for (&id, stack) in self.stacks.iter() {
+                let stack = stack.ro(t);
+                if stack.workspace == workspace {
+                    let rect = mirror_rect(stack.area, src_area, dst_area);
+                    copies.stacks.insert((id, dst), rect);
+                }
+            }
+
+            for (&id, window) in self.windows.iter() {
+                let window = window.ro(t);
+                if window.workspace == workspace {
+                    let rect = mirror_rect(window.rect, src_area, dst_area);
+                    copies.windows.insert((id, dst), rect);
+                }
+            }
+        }
+
+        copies
+    }
+
+    /// Retrieves the latest set of instructions for the window manager to carry out.
+    pub fn events<'a>(&'a mut self, t: &'a mut TCellOwner<T>) -> impl Iterator<Item = Event> + 'a {
+        // Placements are kept partly visible on the display of their workspace.
+        let minimum_visible_area = self.minimum_visible_area;
+        let visible = self.visible_areas(t);
+
+        let focus: Option<[Event; 2]> = if self.active_changed {
+            let serial = self.focus_serial_next();
+            self.active_window().map(|a| {
+                let rect = a.ro(t).rect;
+                let rect = visible.get(&a.ro(t).workspace).map_or(rect, |&display| {
+                    rect.keep_visible(display, minimum_visible_area)
+                });
+
+                [Event::Focus(a.id(t), serial), Event::FocusRect(rect)]
+            })
+        } else {
+            None
+        };
+
+        self.active_changed = false;
+        self.focus_echo = None;
+
+        let workspace_switch = if self.active_workspace_changed {
+            Some(Event::FocusWorkspace(self.active_workspace))
+        } else {
+            None
+        };
+
+        self.active_workspace_changed = false;
+
+        // Placements are aligned to the pixel grid of the display of their workspace.
+        let rounding = self.placement_rounding(t);
+
+        // Mirrored displays are copied to the displays mirroring them, where they changed.
+        let mirrored = self.mirror_copies(t);
+        let mirror = mirrored.events(&self.mirrored);
+        self.mirrored = mirrored;
+
+        let emit_previous_rect = self.emit_previous_rect;
+
+        self.event_queue
+            .consume_events()
+            .map(move |event| previous_rect(event, emit_previous_rect))
+            .map(move |event| keep_visible(event, &visible, minimum_visible_area))
+            .map(move |event| align_placement(event, &rounding))
+            .chain(mirror)
+            .chain(workspace_switch.into_iter())
+            .chain(focus.into_iter().flatten())
+    }
+
+    /// Applies a command to the tiler.
+    pub fn apply(&mut self, command: &Command, t: &mut TCellOwner<T>) {
+        match *command {
+            Command::Attach(window) => self.attach_by_id(window, t),
+
+            Command::AttachToFork { window, fork, side } => {
+                let _ = self.attach_to_fork_branch(window, fork, side, t);
+            }
+
+            Command::Detach(window) => {
+                let _ = self.detach_by_id(window, t);
+            }
+
+            Command::DisplayUpdate {
+                display,
+                dimensions,
+            } => self.display_update(display, dimensions, t),
+
+            Command::DisplayDetach(display) => self.display_detach(display, t),
+
+            Command::Focus(window) => {
+                let _ = self.focus_by_id(window, t);
+            }
+
+            Command::FocusAbove => self.focus_above(t),
+            Command::FocusBelow => self.focus_below(t),
+            Command::FocusLeft => self.focus_left(t),
+            Command::FocusRight => self.focus_right(t),
+            Command::FocusDisplayAbove => self.focus_display_above(t),
+            Command::FocusDisplayBelow => self.focus_display_below(t),
+            Command::FocusDisplayLeft => self.focus_display_left(t),
+            Command::FocusDisplayRight => self.focus_display_right(t),
+            Command::MoveAbove => self.move_above(t),
+            Command::MoveBelow => self.move_below(t),
+            Command::MoveLeft => self.move_left(t),
+            Command::MoveRight => self.move_right(t),
+
+            Command::Resize(fork, handle) => self.fork_resize(fork, handle, t),
+
+            Command::Swap(a, b) => {
+                let _ = self.swap_by_id(a, b, t);
+            }
+
+            Command::SwapActive(window) => {
+                self.swap_active_with_id(window, t);
+            }
+
+            Command::ToggleOrientation => self.toggle_orientation(t),
+            Command::ToggleStack => self.stack_toggle(t),
+
+            Command::WorkspaceSwitch(workspace) => self.workspace_switch(workspace, t),
+
+            Command::WorkspaceUpdate { workspace, display } => {
+                self.workspace_update(workspace, display, t)
+            }
+        }
+    }
+
+    /// Applies each command in order, then retrieves the events of them all at once.
+    ///
+    /// Placements are merged across the commands, so each fork, stack, and window is placed
+    /// only where the last command left it. The events are returned rather than passed to
+    /// the event sink, if one was set.
+    pub fn apply_batch(&mut self, commands: &[Command], t: &mut TCellOwner<T>) -> Vec<Event> {
+        let compaction = self.event_queue.compaction;
+        let sink = self.event_sink.take();
+
+        // Placements superseded within the batch are never reported.
+        if compaction == EventCompaction::None {
+            self.event_queue.compaction = EventCompaction::PerFrame;
+        }
+
+        for command in commands {
+            self.apply(command, t);
+        }
+
+        self.event_queue.compaction = compaction;
+        self.event_sink = sink;
+
+        self.events(t).collect()
+    }
+
+    /// Focus this window in the tree.
+    pub fn focus(&mut self, window: &WindowPtr<T>, t: &mut TCellOwner<T>) {
+        if !self.windows.contains_key(&window.id(t)) {
+            tracing::error!("cannot focus a window that is not managed by the tiler");
+            return;
+        }
+
+        window.focus(self, t);
+        self.dispatch_events(t);
+    }
+
+    /// Focus the window with this ID in the tree.
+    pub fn focus_by_id(
+        &mut self,
+        id: WindowID,
+        t: &mut TCellOwner<T>,
+    ) -> Result<(), WindowNotFound> {
+        let window = self.window_by_id(id)?;
+        self.focus(&window, t);
+        Ok(())
+    }
+
+    /// The serial for the next focus event, which echoes the serial supplied by the window
+    /// manager if it initiated the focus change. Serials never decrease, so a stale serial
+    /// is raised to the latest one reported.
+    fn focus_serial_next(&mut self) -> Option<u64> {
+        if let Some(serial) = self.focus_echo.take() {
+            self.focus_serial = self.focus_serial.max(serial);
+            return Some(self.focus_serial);
+        }
+
+        if !self.focus_serials {
+            return None;
+        }
+
+        self.focus_serial += 1;
+        Some(self.focus_serial)
+    }
+
+    /// Move focus to the window above the active one.
+    pub fn focus_above(&mut self, t: &mut TCellOwner<T>) {
+        self.focus_toward(Direction::Above, t);
+        self.dispatch_events(t);
+    }
+
+    /// Move focus to the window below the active one.
+    pub fn focus_below(&mut self, t: &mut TCellOwner<T>) {
+        self.focus_toward(Direction::Below, t);
+        self.dispatch_events(t);
+    }
+
+    /// Move focus to the largest tiled window on the active workspace, other than the active
+    /// window. Of the windows in a stack, only the active one is considered.
+    pub fn focus_biggest_unfocused(&mut self, t: &mut TCellOwner<T>) {
+        let biggest = self
+            .windows
+            .values()
+            .filter(|window| {
+                let this = window.ro(t);
+
+                if this.workspace != self.active_workspace || this.fork.is_none() {
+                    return false;
+                }
+
+                if let Some(active) = self.active.as_ref() {
+                    if Rc::ptr_eq(active, window) {
+                        return false;
+                    }
+                }
+
+                this.stack
+                    .as_ref()
+                    .map_or(true, |stack| Rc::ptr_eq(&stack.ro(t).active, window))
+            })
+            .max_by_key(|window| window.ro(t).rect.area())
+            .cloned();
+
+        if let Some(biggest) = biggest {
+            self.set_active_window(&biggest, t);
+        }
+
+        self.dispatch_events(t);
+    }
+
+    /// Move focus to the nearest window in a direction from the active window. When there
+    /// is none, the search is retried from the area of each enclosing fork in turn.
+    pub fn focus_direction_or_parent(&mut self, direction: Direction, t: &mut TCellOwner<T>) {
+        let active = ward::ward!(self.active_window().cloned(), else { return });
+
+        let mut region = active.ro(t).rect;
+        let mut fork = active.fork(t);
+
+        loop {
+            if let Some(window) = self.window_beyond(&active, region, direction, t) {
+                self.set_active_window(&window, t);
+                break;
+            }
+
+            let parent = ward::ward!(fork, else { break });
+            region = parent.ro(t).area;
+            fork = parent.ro(t).parent.clone();
+        }
+
+        self.dispatch_events(t);
+    }
+
+    /// Move focus to the window left of the active one.
+    pub fn focus_left(&mut self, t: &mut TCellOwner<T>) {
+        self.focus_with_stack(StackPtr::select_left, Direction::Left, t);
+        self.dispatch_events(t);
+    }
+
+    /// Move focus to the left window, even if in a stack.
+    pub fn focus_left_absolute(&mut self, t: &mut TCellOwner<T>) {
+        self.focus_toward(Direction::Left, t);
+        self.dispatch_events(t);
+    }
+
+    /// Move focus to the window right of the active one.
+    pub fn focus_right(&mut self, t: &mut TCellOwner<T>) {
+        self.focus_with_stack(StackPtr::select_right, Direction::Right, t);
+        self.dispatch_events(t);
+    }
+
+    /// Move focus to the right window, even if in a stack.
+    pub fn focus_right_absolute(&mut self, t: &mut TCellOwner<T>) {
+        self.focus_toward(Direction::Right, t);
+        self.dispatch_events(t);
+    }
+
+    /// Move focus to the nearest window in a direction from the active window, or to the
+    /// display in that direction if there is none and displays are not isolated.
+    fn focus_toward(&mut self, direction: Direction, t: &mut TCellOwner<T>) {
+        let (distance, filter) = direction_fns(direction);
+
+        if let Some(active) = self.window_in_direction(distance, filter, t) {
+            self.set_active_window(&active, t);
+            return;
+        }
+
+        if !self.display_isolation {
+            if let Some(display) = self.display_in_direction(distance, filter, t) {
+                self.focus_display(display, t);
+                return;
+            }
+        }
+
+        // Past the last window and display, focus wraps to the window at the other edge
+        // of the workspace, or else to the display at the other end.
+        if let Some(active) = self.window_wrapped(direction, t) {
+            self.set_active_window(&active, t);
+        } else if !self.display_isolation {
+            if let Some(display) = self.display_wrapped(direction, t) {
+                self.focus_display(display, t);
+            }
+        }
+    }
+
+    /// Move focus to the workspace on the display in a direction from the active one,
+    /// wrapping around to the display furthest in the opposite direction.
+    fn focus_display_toward(&mut self, direction: Direction, t: &mut TCellOwner<T>) {
+        let (distance, filter) = direction_fns(direction);
+
+        let display = self
+            .display_in_direction(distance, filter, t)
+            .or_else(|| self.display_wrapped(direction, t));
+
+        if let Some(display) = display {
+            self.focus_display(display, t);
+        }
+    }
+
+    /// Focus the active window on this display.
+    fn focus_display(&mut self, display: DisplayPtr<T>, t: &mut TCellOwner<T>) {
+        let workspace = display
+            .ro(t)
+            .active
+            .and_then(|active| display.ro(t).workspaces.get(&active).cloned());
+
+        if let Some(workspace) = workspace {
+            let focus = self
+                .window_under_cursor(workspace.id(t), t)
+                .or_else(|| workspace.ro(t).focus.clone());
+
+            if let Some(active) = focus {
+                if self.focus_follows_workspace {
+                    self.set_active_window(&active, t);
+                } else {
+                    self.set_active_window_without_switch(&active, t);
+                }
+            }
+        }
+    }
+
+    /// Move focus to the workspace on the display to the left of the active one.
+    pub fn focus_display_left(&mut self, t: &mut TCellOwner<T>) {
+        self.focus_display_toward(Direction::Left, t);
+        self.dispatch_events(t);
+    }
+
+    /// Move focus to the workspace on the display to the right of the active one.
+    pub fn focus_display_right(&mut self, t: &mut TCellOwner<T>) {
+        self.focus_display_toward(Direction::Right, t);
+        self.dispatch_events(t);
+    }
+
+    /// Move focus to the workspace on the display above the active one.
+    pub fn focus_display_above(&mut self, t: &mut TCellOwner<T>) {
+        self.focus_display_toward(Direction::Above, t);
+        self.dispatch_events(t);
+    }
+
+    /// Move focus to the workspace on the display below the active one.
+    pub fn focus_display_below(&mut self, t: &mut TCellOwner<T>) {
+        self.focus_display_toward(Direction::Below, t);
+        self.dispatch_events(t);
+    }
+
+    /// Manage focus movements with consideration for in-stack movements.
+    fn focus_with_stack(
+        &mut self,
+        stack_func: fn(&StackPtr<T>, &mut TCellOwner<T>) -> Option<WindowPtr<T>>,
+        direction: Direction,
+        t: &mut TCellOwner<T>,
+    ) {
+        let active = ward::ward!(self.active_window(), else { return });
+
+        // If window is in a stack, select the window to the left of the stack.
+        if let Some(stack) = active.stack(t) {
+            let active = active.clone();
+            let mut select = stack_func(&stack, t);
+
+            // From the tab at either end, wrapping selects the tab at the other end.
+            if select.is_none() && self.stack_cycle_wrap {
+                let windows = &stack.ro(t).windows;
+                if windows.len() > 1 {
+                    select = if Rc::ptr_eq(&windows[0], &active) {
+                        windows.last().cloned()
+                    } else {
+                        windows.first().cloned()
+                    };
+                }
+            }
+
+            if let Some(left) = select {
+                stack.rw(t).active = left.clone();
+                left.focus(self, t);
+                return;
+            }
+        }
+
+        self.focus_toward(direction, t);
+    }
+
+    /// The area that a fork and its branches occupy, if the fork exists.
+    pub fn fork_area(&self, fork: usize, t: &TCellOwner<T>) -> Option<Rect> {
+        self.forks.get(&fork).map(|fork| fork.ro(t).area)
+    }
+
+    /// The region around the split of a fork which grabs it for resizing, this many pixels
+    /// thick and centered on the split. `None` if the fork does not exist or is not split.
+    pub fn fork_handle_rect(&self, fork: usize, thickness: u32, t: &TCellOwner<T>) -> Option<Rect> {
+        let fork = self.forks.get(&fork)?.ro(t);
+        fork.right.as_ref()?;
+
+        let area = fork.area;
+        let before = thickness / 2;
+
+        let rect = match fork.orientation {
+            Orientation::Horizontal => {
+                let x = (area.x + fork.split_handle)
+                    .saturating_sub(before)
+                    .max(area.x);
+                Rect::new(x, area.y, thickness.min(area.x_end() - x), area.height)
+            }
+
+            Orientation::Vertical => {
+                let y = (area.y + fork.split_handle)
+                    .saturating_sub(before)
+                    .max(area.y);
+                Rect::new(area.x, y, area.width, thickness.min(area.y_end() - y))
+            }
+        };
+
+        Some(rect)
+    }
+
+    /// The fork on the active workspace whose split handle contains this point. Where the
+    /// handles of nested forks overlap, the deepest fork is chosen.
+    pub fn fork_handle_at(&self, point: Point, thickness: u32, t: &TCellOwner<T>) -> Option<usize> {
+        self.forks
+            .iter()
+            .filter(|(_, fork)| fork.ro(t).workspace == self.active_workspace)
+            .filter(|(&id, _)| {
+                self.fork_handle_rect(id, thickness, t)
+                    .map_or(false, |rect| rect.contains(point))
+            })
+            .max_by_key(|(_, fork)| fork.depth(t))
+            .map(|(&id, _)| id)
+    }
+
+    /// The direct children of a fork, if the fork exists.
+    pub fn fork_children(
+        &self,
+        fork: usize,
+        t: &TCellOwner<T>,
+    ) -> Option<(ForkChild, Option<ForkChild>)> {
+        let fork = self.forks.get(&fork)?.ro(t);
+
+        let left = ForkChild::new(&fork.left, t);
+        let right = fork.right.as_ref().map(|right| ForkChild::new(right, t));
+
+        Some((left, right))
+    }
+
+    /// The other branch of a fork's parent, if the fork has a parent and a sibling.
+    pub fn fork_sibling(&self, fork: usize, t: &TCellOwner<T>) -> Option<ForkChild> {
+        let fork = self.forks.get(&fork)?;
+        let parent = fork.ro(t).parent.as_ref()?.ro(t);
+
+        let sibling = if parent.left_is(BranchRef::Fork(fork)) {
+            parent.right.as_ref()?
+        } else {
+            &parent.left
+        };
+
+        Some(ForkChild::new(sibling, t))
+    }
+
+    /// Moves the split of a fork so that the side asking for the least length gets exactly
+    /// that length, and the other side gets the rest.
+    ///
+    /// Sides without a minimum size are given the rest of the fork.
+    pub fn resize_fork_to_fit(&mut self, fork: usize, t: &mut TCellOwner<T>) {
+        let fork = ward::ward!(self.forks.get(&fork).cloned(), else {
+            tracing::error!("cannot fit non-existent fork {}", fork);
+            return;
+        });
+
+        let fork_ = fork.ro(t);
+        let right = ward::ward!(fork_.right.as_ref(), else { return });
+
+        let orientation = fork_.orientation;
+        let length = match orientation {
+            Orientation::Horizontal => fork_.area.width,
+            Orientation::Vertical => fork_.area.height,
+        };
+
+        let left_min = fork_.left.min_length(orientation, t);
+        let right_min = right.min_length(orientation, t);
+
+        let split = match (left_min, right_min) {
+            (Some(left), Some(right)) if left <= right => left,
+            (Some(left), None) => left,
+            (_, Some(right)) => length.saturating_sub(right),
+            (None, None) => return,
+        };
+
+        fork.resize(self, split, t);
+        self.dispatch_events(t);
+    }
+
+    /// Rounds the split of every fork to the nearest multiple of the ratio snap percentage,
+    /// such that splits which were dragged or rescaled return to clean ratios.
+    pub fn normalize_ratios(&mut self, t: &mut TCellOwner<T>) {
+        let snap = u64::from(self.ratio_snap);
+
+        // Parents are resized before their children, which are resized within them.
+        let mut forks: Vec<ForkPtr<T>> = self.forks.values().cloned().collect();
+        forks.sort_by_cached_key(|fork| fork.depth(t));
+
+        for fork in forks {
+            let fork_ = fork.ro(t);
+
+            if fork_.right.is_none() {
+                continue;
+            }
+
+            let length = u64::from(match fork_.orientation {
+                Orientation::Horizontal => fork_.area.width,
+                Orientation::Vertical => fork_.area.height,
+            });
+
+            if length == 0 {
+                continue;
+            }
+
+            let percent = (u64::from(fork_.split_handle) * 100 + length / 2) / length;
+            let percent = ((percent + snap / 2) / snap * snap)
+                .max(snap)
+                .min(100 - snap);
+
+            fork.resize(self, (length * percent / 100) as u32, t);
+        }
+
+        self.dispatch_events(t);
+    }
+
+    /// Every window beneath a fork, in depth-first order, or none if the fork does not exist.
+    pub fn subtree_window_ids(&self, fork: usize, t: &TCellOwner<T>) -> Vec<WindowID> {
+        match self.forks.get(&fork) {
+            Some(fork) => fork.windows(t).map(|window| window.id(t)).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Destroy this fork, and stop tracking it in the tiler.
+    fn fork_destroy(&mut self, fork: &ForkPtr<T>, t: &TCellOwner<T>) {
+        let id = fork.id(t);
+        self.event_queue.fork_destroy(fork, t);
+        self.forks.remove(&id);
+        self.pending_resizes.remove(&id);
+    }
+
+    /// Keep track of this fork directly in the tiler, and assign it a unique ID.
+    pub(crate) fn fork_register(&mut self, fork: ForkPtr<T>, t: &mut TCellOwner<T>) {
+        let id = self.fork_id_next;
+        self.fork_id_next += 1;
+
+        fork.rw(t).id = id;
+        self.event_queue.fork_update(&fork, t);
+        self.forks.insert(id, fork);
+    }
+
+    /// Resize a fork with a new split
+    ///
+    /// With a resize throttle set, the split is held back until it moved at least that far
+    /// from where the fork was last resized to, or until [`Tiler::fork_resize_commit`].
+    pub fn fork_resize(&mut self, fork: usize, split: u32, t: &mut TCellOwner<T>) {
+        if let Some(fork_) = self.forks.get(&fork).cloned() {
+            let handle = fork_.ro(t).split_handle;
+
+            if self.resize_throttle > 0
+                && (i64::from(split) - i64::from(handle)).abs() < i64::from(self.resize_throttle)
+            {
+                self.pending_resizes.insert(fork, split);
+            } else {
+                self.pending_resizes.remove(&fork);
+                self.fork_resize_by_mode(&fork_, split, t);
+            }
+        }
+        self.dispatch_events(t);
+    }
+
+    /// Resize a fork to the split last requested of it, if the resize throttle held it back.
+    pub fn fork_resize_commit(&mut self, fork: usize, t: &mut TCellOwner<T>) {
+        let split = ward::ward!(self.pending_resizes.remove(&fork), else { return });
+
+        if let Some(fork) = self.forks.get(&fork).cloned() {
+            self.fork_resize_by_mode(&fork, split, t);
+        }
+        self.dispatch_events(t);
+    }
+
+    /// Moves the split of a fork, and the other splits along its axis by the resize mode.
+    fn fork_resize_by_mode(&mut self, fork: &ForkPtr<T>, split: u32, t: &mut TCellOwner<T>) {
+        let mode = self.resize_mode;
+
+        if mode == ResizeMode::Proportional {
+            fork.resize(self, split, t);
+            return;
+        }
+
+        let orientation = fork.ro(t).orientation;
+        let start = |area: Rect| match orientation {
+            Orientation::Horizontal => area.x,
+            Orientation::Vertical => area.y,
+        };
+
+        let end = |area: Rect| match orientation {
+            Orientation::Horizontal => area.x_end(),
+            Orientation::Vertical => area.y_end(),
+        };
+
+        // The splits along the axis are those of the forks of the same orientation
+        // around this one.
+        let mut top = fork.clone();
+        while let Some(parent) = top.ro(t).parent.clone() {
+            if parent.ro(t).orientation != orientation {
+                break;
+            }
+
+            top = parent;
+        }
+
+        let mut row = Vec::new();
+        row_forks(&top, orientation, &mut row, t);
+
+        let position = ward::ward!(row.iter().position(|other| Rc::ptr_eq(other, fork)), else {
+            fork.resize(self, split, t);
+            return;
+        });
+
+        let edges: Vec<u32> = row
+            .iter()
+            .map(|fork| start(fork.ro(t).area) + fork.ro(t).split_handle)
+            .collect();
+
+        let from = i64::from(edges[position]);
+        let to = i64::from(start(fork.ro(t).area) + split);
+
+        let mut delta = to - from;
+
+        // Chained splits are pushed no further than the end of the row, and pulled no
+        // further than the split before them.
+        if mode == ResizeMode::Chained {
+            let top_area = top.ro(t).area;
+            let last = edges.last().copied().map_or(from, i64::from);
+            let floor = match position.checked_sub(1) {
+                Some(before) => i64::from(edges[before]),
+                None => i64::from(start(top_area)),
+            };
+
+            delta = delta.min(i64::from(end(top_area)) - last).max(floor - from);
+        }
+
+        let mut targets: Vec<(usize, ForkPtr<T>, u32)> = row
+            .into_iter()
+            .zip(edges)
+            .enumerate()
+            .map(|(index, (fork, edge))| {
+                let moves = match mode {
+                    ResizeMode::Chained => index >= position,
+                    _ => index == position,
+                };
+
+                let edge = if moves {
+                    (i64::from(edge) + delta).max(0) as u32
+                } else {
+                    edge
+                };
+
+                (fork.depth(t), fork, edge)
+            })
+            .collect();
+
+        // Parents are resized before their children, whose areas they determine.
+        targets.sort_by_key(|(depth, ..)| *depth);
+
+        for (_, fork, edge) in targets {
+            let split = edge.saturating_sub(start(fork.ro(t).area));
+            fork.resize(self, split, t);
+        }
+    }
+
+    /// How many forks lie on the longest path from the root of a workspace's tree.
+    pub fn fork_tree_depth(&self, workspace: u32, t: &TCellOwner<T>) -> usize {
+        self.forks
+            .values()
+            .filter(|fork| fork.ro(t).workspace == workspace)
+            .map(|fork| fork.depth(t))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Moves the active window to a new workspace on the active display, and switches to it.
+    ///
+    /// The new workspace takes the lowest unused workspace ID, which is returned.
+    pub fn move_active_to_new_workspace(&mut self, t: &mut TCellOwner<T>) -> Option<u32> {
+        let active = ward::ward!(self.active.clone(), else {
+            tracing::error!("no active window to move to a new workspace");
+            return None;
+        });
+
+        let source = active.ro(t).workspace;
+
+        let workspace_ = self.workspace_create_beside(source, t)?;
+        let workspace = workspace_.id(t);
+
+        self.detach_from_workspace(&active, t);
+
+        active.rw(t).workspace = workspace;
+        self.attach_to_workspace(&active, &workspace_, t);
+        self.workspace_activate(workspace, t);
+        self.dispatch_events(t);
+
+        Some(workspace)
+    }
+
+    /// Moves a window to another workspace, beside the focus window of that workspace.
+    ///
+    /// When following, the workspace is switched to and the window keeps the focus.
+    /// Otherwise the active workspace stays as it is, and if the window was focused, the
+    /// focus moves to another window on its former workspace as it would on detaching.
+    pub fn move_to_workspace(
+        &mut self,
+        window: &WindowPtr<T>,
+        workspace: u32,
+        follow: bool,
+        t: &mut TCellOwner<T>,
+    ) {
+        let workspace_ = ward::ward!(self.workspaces.get(&workspace).cloned(), else {
+            tracing::error!("cannot move window to non-existent workspace {}", workspace);
+            return;
+        });
+
+        let source = window.ro(t).workspace;
+
+        if source == workspace {
+            return;
+        }
+
+        // The window to focus is chosen before the window leaves its neighbors.
+        let refocus = if follow {
+            None
+        } else {
+            self.detach_refocus_target(window, t)
+        };
+
+        self.detach_from_workspace(window, t);
+        self.attach_to_other_workspace(window, &workspace_, t);
+
+        if follow {
+            self.set_active_window(window, t);
+        } else {
+            self.visibility_sync(window, t);
+
+            // The moved window may not keep the focus, which would switch workspaces.
+            if self
+                .active
+                .as_ref()
+                .map_or(false, |active| Rc::ptr_eq(active, window))
+            {
+                self.active = None;
+                self.active_changed = true;
+            }
+
+            self.detach_refocus(refocus, source, t);
+        }
+
+        self.dispatch_events(t);
+    }
+
+    /// Attach a window to a workspace other than the active one, beside the focus window of
+    /// that workspace, and make it the focus window of the workspace.
+    fn attach_to_other_workspace(
+        &mut self,
+        window: &WindowPtr<T>,
+        workspace: &WorkspacePtr<T>,
+        t: &mut TCellOwner<T>,
+    ) {
+        let id = workspace.id(t);
+
+        let beside = workspace
+            .ro(t)
+            .focus
+            .clone()
+            .filter(|focus| focus.fork(t).is_some() && !Rc::ptr_eq(focus, window));
+
+        window.rw(t).workspace = id;
+
+        if self.tiling_disabled.contains_key(&id) {
+            self.attach_floating(window, id, None, t);
+        } else if let Some(beside) = beside {
+            self.attach_to_window(window, &beside, t);
+        } else {
+            self.attach_to_workspace(window, workspace, t);
+        }
+
+        workspace.rw(t).focus = Some(window.clone());
+    }
+
+    /// Shows or hides a window moved to another workspace, as it would be after switching
+    /// to that workspace: shown if the workspace is, unless a fullscreen window or the
+    /// raised tab of its stack covers it.
+    fn visibility_sync(&mut self, window: &WindowPtr<T>, t: &mut TCellOwner<T>) {
+        let this = window.ro(t);
+
+        let visible = self.workspace_shown(this.workspace, t)
+            && !self.fullscreen_covered(window, t)
+            && this
+                .stack
+                .as_ref()
+                .map_or(true, |stack| Rc::ptr_eq(&stack.ro(t).active, window));
+
+        if this.visible != visible {
+            window.rw(t).visible = visible;
+            let id = window.id(t);
+            self.event_queue.windows.entry(id).or_default().visibility = Some(visible);
+        }
+    }
+
+    /// Moves the active window to the active workspace of the display in this direction,
+    /// beside the window nearest the edge that it enters from, and keeps it focused.
+    pub fn move_active_to_display_direction(
+        &mut self,
+        direction: Direction,
+        t: &mut TCellOwner<T>,
+    ) {
+        let active = ward::ward!(self.active.clone(), else {
+            tracing::error!("no active window to move to another display");
+            return;
+        });
+
+        let (distance, filter) = direction_fns(direction);
+
+        let display = self
+            .display_in_direction(distance, filter, t)
+            .or_else(|| self.display_wrapped(direction, t));
+
+        let display = ward::ward!(display, else { return });
+
+        let workspace = display
+            .ro(t)
+            .active
+            .and_then(|active| self.workspaces.get(&active).cloned());
+
+        let workspace = ward::ward!(workspace, else {
+            tracing::error!("display in direction has no active workspace");
+            return;
+        });
+
+        let id = workspace.id(t);
+
+        if active.ro(t).workspace == id {
+            return;
+        }
+
+        // The window enters the display from the edge opposite to the direction.
+        let area = workspace.area(t);
+        let edge = match direction {
+            Direction::Above => area.south(),
+            Direction::Below => area.north(),
+            Direction::Left => area.east(),
+            Direction::Right => area.west(),
+        };
+
+        let nearest = self
+            .windows
+            .values()
+            .filter(|window| window.ro(t).workspace == id && window.fork(t).is_some())
+            .min_by(|a, b| {
+                let a = edge.distance_from_rect(&a.ro(t).rect);
+                let b = edge.distance_from_rect(&b.ro(t).rect);
+                a.partial_cmp(&b).unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .cloned();
+
+        self.detach_from_workspace(&active, t);
+
+        active.rw(t).workspace = id;
+
+        if self.tiling_disabled.contains_key(&id) {
+            self.attach_floating(&active, id, None, t);
+        } else if let Some(nearest) = nearest {
+            self.attach_to_window(&active, &nearest, t);
+        } else {
+            self.attach_to_workspace(&active, &workspace, t);
+        }
+
+        // Switching to the workspace focuses its focus window.
+        workspace.rw(t).focus = Some(active.clone());
+        self.set_active_window(&active, t);
+        self.dispatch_events(t);
+    }
+
+    /// Creates a workspace on the display of this workspace, with the lowest unused ID.
+    fn workspace_create_beside(
+        &mut self,
+        workspace: u32,
+        t: &mut TCellOwner<T>,
+    ) -> Option<WorkspacePtr<T>> {
+        let display_ = ward::ward!(self.workspaces.get(&workspace).map(|w| w.ro(t).parent.clone()), else {
+            tracing::error!("cannot create a workspace beside non-existent workspace {}", workspace);
+            return None;
+        });
+
+        let display = self
+            .displays
+            .iter()
+            .find(|(_, display)| Rc::ptr_eq(display, &display_))
+            .map(|(id, _)| *id)?;
+
+        let workspace = (0..=u32::MAX).find(|id| !self.workspaces.contains_key(id))?;
+
+        let workspace_ = display_.create_workspace(workspace, t);
+        self.workspaces.insert(workspace, workspace_.clone());
+        self.event_queue
+            .events
+            .push(Event::WorkspaceAssign { workspace, display });
+
+        Some(workspace_)
+    }
+
+    /// Removes a window from the tree of its workspace, and moves the focus of the workspace
+    /// to another of its windows if the window had it.
+    fn detach_from_workspace(&mut self, window: &WindowPtr<T>, t: &mut TCellOwner<T>) {
+        let source = window.ro(t).workspace;
+
+        for floating in self.tiling_disabled.values_mut() {
+            floating
+                .windows
+                .retain(|floating| !Rc::ptr_eq(floating, window));
+        }
+
+        self.floated.retain(|floated| !Rc::ptr_eq(floated, window));
+
+        self.detach_from_tree(window, t);
+
+        if let Some(source) = self.workspaces.get(&source).cloned() {
+            let focus_moved = source
+                .ro(t)
+                .focus
+                .as_ref()
+                .map_or(false, |focus| Rc::ptr_eq(focus, window));
+
+            if focus_moved {
+                let focus = source.fork(t).and_then(|root| root.windows(t).next());
+                source.rw(t).focus = focus;
+            }
+
+            if let Some(root) = source.fork(t) {
+                root.work_area_refresh(self, t);
+            }
+        }
+    }
+
+    /// Moves the tree beneath a fork to another workspace, beside the tree that is there.
+    pub fn move_fork_to_workspace(&mut self, fork: usize, workspace: u32, t: &mut TCellOwner<T>) {
+        let fork = ward::ward!(self.forks.get(&fork).cloned(), else {
+            tracing::error!("cannot move non-existent fork {}", fork);
+            return;
+        });
+
+        let target = ward::ward!(self.workspaces.get(&workspace).cloned(), else {
+            tracing::error!("cannot move fork to non-existent workspace {}", workspace);
+            return;
+        });
+
+        let source = fork.ro(t).workspace;
+
+        if source == workspace {
+            return;
+        }
+
+        let windows: Vec<WindowPtr<T>> = fork.windows(t).collect();
+
+        // The window to focus is chosen before the active window leaves its neighbors, and
+        // is never one that moves with it.
+        let active = self
+            .active
+            .clone()
+            .filter(|active| windows.iter().any(|window| Rc::ptr_eq(window, active)));
+
+        let refocus = active.as_ref().and_then(|active| {
+            self.detach_refocus_target(active, t).map(|target| {
+                target.filter(|target| !windows.iter().any(|window| Rc::ptr_eq(window, target)))
+            })
+        });
+
+        // Detach the tree from the source workspace.
+        match fork.ro(t).parent.clone() {
+            Some(parent) => {
+                self.detach_branch(parent, BranchRef::Fork(&fork), t);
+                fork.rw(t).parent = None;
+            }
+
+            None => {
+                if let Some(source) = self.workspaces.get(&source) {
+                    source.rw(t).fork = None;
+                }
+            }
+        }
+
+        fork.workspace_set(workspace, t);
+
+        // Focus another window on the source workspace if its focus moved away.
+        if let Some(source) = self.workspaces.get(&source).cloned() {
+            let focus_moved = source.ro(t).focus.as_ref().map_or(false, |focus| {
+                windows.iter().any(|window| Rc::ptr_eq(window, focus))
+            });
+
+            if focus_moved {
+                let focus = source.fork(t).and_then(|root| root.windows(t).next());
+                source.rw(t).focus = focus;
+            }
+        }
+
+        if target.ro(t).focus.is_none() {
+            target.rw(t).focus = windows.first().cloned();
+        }
+
+        // Graft the tree into the target workspace.
+        let area = target.area(t);
+
+        match target.fork(t) {
+            Some(root) => {
+                // A fork holding a single branch is replaced by that branch.
+                let branch = if fork.ro(t).right.is_none() {
+                    self.fork_destroy(&fork, t);
+                    fork.ro(t).left.clone()
+                } else {
+                    Branch::Fork(fork)
+                };
+
+                if root.ro(t).right.is_none() {
+                    branch.parent_set(&root, t);
+                    root.rw(t).right = Some(branch);
+                    root.work_area_refresh(self, t);
+                } else {
+                    let new_root = ForkPtr::new({
+                        let mut fork = Fork::new(area, Branch::Fork(root.clone()), workspace);
+                        fork.right = Some(branch.clone());
+                        fork
+                    });
+
+                    self.fork_register(new_root.clone(), t);
+
+                    root.rw(t).parent = Some(new_root.clone());
+                    branch.parent_set(&new_root, t);
+                    target.rw(t).fork = Some(new_root.clone());
+
+                    new_root.work_area_refresh(self, t);
+                }
+            }
+
+            None => {
+                target.rw(t).fork = Some(fork.clone());
+                fork.work_area_update(self, area, t);
+            }
+        }
+
+        for window in &windows {
+            self.visibility_sync(window, t);
+        }
+
+        // The moved windows may not keep the focus, which would switch workspaces.
+        if active.is_some() {
+            self.active = None;
+            self.active_changed = true;
+            self.detach_refocus(refocus, source, t);
+        }
+
+        self.dispatch_events(t);
+    }
+
+    /// When moving vertically or horizontally, move active window out of the stack.
+    fn move_from_stack(
+        &mut self,
+        active: &WindowPtr<T>,
+        fork: &ForkPtr<T>,
+        stack: &StackPtr<T>,
+        direction: Direction,
+        t: &mut TCellOwner<T>,
+    ) {
+        let (orientation, stack_on_left) = match direction {
+            Direction::Above => (Orientation::Vertical, false),
+            Direction::Below => (Orientation::Vertical, true),
+            Direction::Left => (Orientation::Horizontal, false),
+            Direction::Right => (Orientation::Horizontal, true),
+        };
+
+        let area = stack.ro(t).area;
+        let workspace = fork.ro(t).workspace;
+        let windows = stack.ro(t).windows.len();
+
+        let branch = ward::ward!(fork.rw(t).branch(BranchRef::Stack(stack)), else {
+            tracing::error!("invalid parent fork association of stacked window");
+            return;
+        });
+
+        match branch {
+            Either::Left(prev_branch) | Either::Right(prev_branch) => {
+                if windows == 1 {
+                    *prev_branch = Branch::Window(active.clone());
+                    self.stack_destroy(stack, t);
+                } else {
+                    let stack_branch = Branch::Stack(stack.clone());
+                    let window_branch = Branch::Window(active.clone());
+
+                    let (left, right) = if stack_on_left {
+                        (stack_branch, window_branch)
+                    } else {
+                        (window_branch, stack_branch)
+                    };
+
+                    let new_fork = ForkPtr::new({
+                        let mut fork = Fork::new(area, left, workspace);
+                        fork.right = Some(right);
+                        fork
+                    });
+
+                    *prev_branch = Branch::Fork(new_fork.clone());
+
+                    new_fork.rw(t).parent = Some(fork.clone());
+                    self.fork_register(new_fork.clone(), t);
+                    new_fork.orientation_set(self, orientation, t);
+                }
+            }
+        }
+
+        fork.work_area_refresh(self, t);
+    }
+
+    /// When moving horizontally, check if a window is stacked and can be moved within the stack.
+    fn move_horizontally(
+        &mut self,
+        stack_func: fn(&StackPtr<T>, &mut TCellOwner<T>) -> Option<StackMovement>,
+        direction: Direction,
+        t: &mut TCellOwner<T>,
+    ) {
+        let active = ward::ward!(self.active_window(), else { return });
+
+        // If window is in a stack, move the tab positioning in the stack
+        if let Some(stack) = active.stack(t) {
+            if let Some(movement) = stack_func(&stack, t) {
+                self.event_queue.stack_movement(&stack, movement, t);
+                return;
+            }
+        }
+
+        self.move_in_direction(direction, t);
+    }
+
+    /// Move a window to be placed directly after another window.
+    pub fn move_window_after(
+        &mut self,
+        window: &WindowPtr<T>,
+        reference: &WindowPtr<T>,
+        t: &mut TCellOwner<T>,
+    ) {
+        self.attach_beside(window, reference, true, t);
+        self.dispatch_events(t);
+    }
+
+    /// Move a window to be placed directly before another window.
+    pub fn move_window_before(
+        &mut self,
+        window: &WindowPtr<T>,
+        reference: &WindowPtr<T>,
+        t: &mut TCellOwner<T>,
+    ) {
+        self.attach_beside(window, reference, false, t);
+        self.dispatch_events(t);
+    }
+
+    /// Relocate a window to be beside the reference window, in the orientation of the
+    /// reference window's fork. Windows moved beside a stacked window join its stack.
+    fn attach_beside(
+        &mut self,
+        window: &WindowPtr<T>,
+        reference: &WindowPtr<T>,
+        after: bool,
+        t: &mut TCellOwner<T>,
+    ) {
+        if Rc::ptr_eq(window, reference) {
+            return;
+        }
+
+        if !self.windows.contains_key(&window.id(t)) || !self.windows.contains_key(&reference.id(t))
+        {
+            tracing::error!("cannot move windows that are not managed by the tiler");
+            return;
+        }
+
+        if reference.fork(t).is_none() {
+            tracing::error!("cannot move a window beside a window that is not in a tree");
+            return;
+        }
+
+        self.detach_from_tree(window, t);
+
+        let fork = ward::ward!(reference.fork(t), else {
+            tracing::error!("reference window was detached from its tree");
+            return;
+        });
+
+        if let Some(stack) = reference.stack(t) {
+            window.fork_set(fork, t);
+            stack.attach_beside(window, reference, after, t);
+            self.event_queue.stack_assign(&stack, window, t);
+            stack.work_area_refresh(self, t);
+            return;
+        }
+
+        let fork_ = fork.rw(t);
+
+        // The reference window may be the only branch left in its fork.
+        if fork_.right.is_none() {
+            let window_branch = Branch::Window(window.clone());
+
+            fork_.right = Some(if after {
+                window_branch
+            } else {
+                std::mem::replace(&mut fork_.left, window_branch)
+            });
+
+            window.fork_set(fork.clone(), t);
+            fork.work_area_refresh(self, t);
+            return;
+        }
+
+        // Otherwise, the reference window is replaced by a fork containing both windows.
+        let orientation = fork_.orientation;
+        let workspace = fork_.workspace;
+        let area = reference.ro(t).rect;
+
+        let (left, right) = if after {
+            (reference.clone(), window.clone())
+        } else {
+            (window.clone(), reference.clone())
+        };
+
+        let new_fork = ForkPtr::new({
+            let mut new_fork = Fork::new(area, Branch::Window(left), workspace);
+            new_fork.right = Some(Branch::Window(right));
+            new_fork.parent = Some(fork.clone());
+            new_fork.orientation = orientation;
+            new_fork.split_handle = orientation.half(area);
+            new_fork
+        });
+
+        self.fork_register(new_fork.clone(), t);
+
+        match fork.rw(t).branch(BranchRef::Window(reference)) {
+            Some(Either::Left(branch)) | Some(Either::Right(branch)) => {
+                *branch = Branch::Fork(new_fork.clone())
+            }
+            None => tracing::error!("invalid parent fork association in window"),
+        }
+
+        window.fork_set(new_fork.clone(), t);
+        reference.fork_set(new_fork, t);
+
+        fork.work_area_refresh(self, t);
+    }
+
+    /// Records where the cursor is, for choosing which window to focus when switching
+    /// workspaces or displays.
+    pub fn notify_cursor(&mut self, point: Point) {
+        self.cursor = Some(point);
+    }
+
+    /// Records the window that the cursor hovers over, or that it hovers over none, without
+    /// focusing it. The window manager focuses it with [`Tiler::commit_hover`] once the
+    /// cursor has rested on it for long enough.
+    pub fn hover(&mut self, id: Option<WindowID>) {
+        self.hover = id;
+    }
+
+    /// Focuses the window that the cursor last hovered over, if it is still managed and is
+    /// not already focused.
+    pub fn commit_hover(&mut self, t: &mut TCellOwner<T>) {
+        let id = ward::ward!(self.hover.take(), else { return });
+        let window = ward::ward!(self.windows.get(&id).cloned(), else { return });
+
+        if let Some(active) = self.active.as_ref() {
+            if Rc::ptr_eq(active, &window) {
+                return;
+            }
+        }
+
+        self.focus(&window, t);
+    }
+
+    /// Move the active window up in the tree.
+    pub fn move_left(&mut self, t: &mut TCellOwner<T>) {
+        self.move_horizontally(StackPtr::move_left, Direction::Left, t);
+        self.dispatch_events(t);
+    }
+
+    fn move_in_direction(&mut self, direction: Direction, t: &mut TCellOwner<T>) {
+        let active = ward::ward!(self.active_window().cloned(), else { return });
+        let fork = ward::ward!(active.fork(t), else { return });
+
+        // If in a stack, create a fork and make the window adjacent to the stack.
+        if let Some(stack) = active.stack(t) {
+            self.move_from_stack(&active, &fork, &stack, direction, t);
+            return;
+        }
+
+        // Fetch nearest window in direction
+        let (distance, filter) = direction_fns(direction);
+
+        if let Some(window) = self.window_in_direction(distance, filter, t) {
+            let matched_fork = ward::ward!(window.fork(t), else {
+                tracing::error!("cannot move into window that is forkless");
+                return;
+            });
+
+            // If the window being attached is in the same fork, swap positions.
+            if Rc::ptr_eq(&fork, &matched_fork) {
+                let fork_ = fork.rw(t);
+                if let Some(right) = fork_.right.as_mut() {
+                    std::mem::swap(right, &mut fork_.left);
+                    fork.work_area_refresh(self, t);
+                    return;
+                }
+            }
+
+            // Detach and create a fork in new window.
+            self.detach_from_tree(&active, t);
+            self.attach_to_window(&active, &window, t);
+            self.set_active_window(&active, t);
+        }
+
+        // TODO: Move across displays if not found
+    }
+
+    /// Move the active window to the left, even if it is stacked.
+    pub fn move_left_absolute(&mut self, t: &mut TCellOwner<T>) {
+        self.move_in_direction(Direction::Left, t);
+        self.dispatch_events(t);
+    }
+
+    /// Move the active window to the right in the tree.
+    pub fn move_right(&mut self, t: &mut TCellOwner<T>) {
+        self.move_horizontally(StackPtr::move_right, Direction::Right, t);
+        self.dispatch_events(t);
+    }
+
+    /// Move the active window to the right, even if it is stacked.
+    pub fn move_right_absolute(&mut self, t: &mut TCellOwner<T>) {
+        self.move_in_direction(Direction::Right, t);
+        self.dispatch_events(t);
+    }
+
+    /// Move the active window above in the tree.
+    pub fn move_above(&mut self, t: &mut TCellOwner<T>) {
+        self.move_in_direction(Direction::Above, t);
+        self.dispatch_events(t);
+    }
+
+    /// Move the active window below in the tree.
+    pub fn move_below(&mut self, t: &mut TCellOwner<T>) {
+        self.move_in_direction(Direction::Below, t);
+        self.dispatch_events(t);
+    }
+
+    /// Moves the active window, or its stack, one place along the row or column of windows
+    /// that it is arranged in, swapping places with its neighbor in this direction.
+    ///
+    /// A row is formed by nested forks of the same orientation. At the end of a row, the
+    /// window is moved in the direction as with [`Tiler::move_left`] and the like.
+    pub fn move_active_edgewise_within_fork(
+        &mut self,
+        direction: Direction,
+        t: &mut TCellOwner<T>,
+    ) {
+        let active = ward::ward!(self.active_window().cloned(), else { return });
+        let fork = ward::ward!(active.fork(t), else { return });
+
+        let (orientation, forward) = match direction {
+            Direction::Above => (Orientation::Vertical, false),
+            Direction::Below => (Orientation::Vertical, true),
+            Direction::Left => (Orientation::Horizontal, false),
+            Direction::Right => (Orientation::Horizontal, true),
+        };
+
+        let branch = match active.stack(t) {
+            Some(stack) => Branch::Stack(stack),
+            None => Branch::Window(active.clone()),
+        };
+
+        // The row ends at the nearest ancestor whose parent is split the other way.
+        let mut root = fork.clone();
+        if root.ro(t).orientation == orientation {
+            while let Some(parent) = root.ro(t).parent.clone() {
+                if parent.ro(t).orientation != orientation {
+                    break;
+                }
+
+                root = parent;
+            }
+        }
+
+        let slots = if root.ro(t).orientation == orientation {
+            row_slots(&root, orientation, t)
+        } else {
+            Vec::new()
+        };
+
+        let position = slots.iter().position(|(_, slot)| *slot == branch);
+        let neighbor = position.and_then(|position| {
+            if forward {
+                slots.get(position + 1)
+            } else {
+                position
+                    .checked_sub(1)
+                    .and_then(|position| slots.get(position))
+            }
+        });
+
+        let (neighbor_parent, neighbor) = ward::ward!(neighbor.cloned(), else {
+            self.move_in_direction(direction, t);
+            self.dispatch_events(t);
+            return;
+        });
+
+        if Rc::ptr_eq(&fork, &neighbor_parent) {
+            let fork_ = fork.rw(t);
+            if let Some(right) = fork_.right.as_mut() {
+                std::mem::swap(right, &mut fork_.left);
+            }
+        } else {
+            for (parent, from, to) in [
+                (&fork, &branch, &neighbor),
+                (&neighbor_parent, &neighbor, &branch),
+            ] {
+                let parent_ = parent.rw(t);
+                if parent_.left == *from {
+                    parent_.left = to.clone();
+                } else if parent_.right.as_ref() == Some(from) {
+                    parent_.right = Some(to.clone());
+                }
+            }
+
+            branch.parent_set(&neighbor_parent, t);
+            neighbor.parent_set(&fork, t);
+        }
+
+        root.work_area_refresh(self, t);
+
+        // Focus is reported again so that its new position is highlighted.
+        self.active_changed = true;
+        self.dispatch_events(t);
+    }
+
+    /// Makes the active window cover the display of its workspace, or returns it to its
+    /// place in the tree if it already does.
+    ///
+    /// Only windows on the same workspace are hidden, so the windows on other displays
+    /// are left as they are.
+    pub fn toggle_fullscreen(&mut self, t: &mut TCellOwner<T>) {
+        let active = ward::ward!(self.active_window().cloned(), else { return });
+        let fork = ward::ward!(active.fork(t), else { return });
+
+        let fullscreen = !active.ro(t).fullscreen;
+        active.rw(t).fullscreen = fullscreen;
+
+        let workspace = active.ro(t).workspace;
+
+        if !fullscreen {
+            self.fullscreen_siblings_show(workspace, t);
+        } else if self.fullscreen_hides_siblings {
+            for (id, window) in self.windows.iter() {
+                if Rc::ptr_eq(window, &active) || window.ro(t).workspace != workspace {
+                    continue;
+                }
+
+                if window.ro(t).visible {
+                    window.rw(t).visible = false;
+                    self.event_queue.windows.entry(*id).or_default().visibility = Some(false);
+                }
+            }
+        }
+
+        fork.work_area_refresh(self, t);
+        self.dispatch_events(t);
+    }
+
+    /// Shows the windows of a workspace that were hidden beneath a fullscreen window.
+    fn fullscreen_siblings_show(&mut self, workspace: u32, t: &mut TCellOwner<T>) {
+        // Windows of hidden workspaces are shown when their workspace is switched to.
+        if !self.workspace_shown(workspace, t) {
+            return;
+        }
+
+        for (id, window) in self.windows.iter() {
+            if window.ro(t).workspace != workspace || window.ro(t).visible {
+                continue;
+            }
+
+            let visible = window
+                .stack(t)
+                .map_or(true, |stack| Rc::ptr_eq(&stack.ro(t).active, window));
+
+            if visible {
+                window.rw(t).visible = true;
+                self.event_queue.windows.entry(*id).or_default().visibility = Some(true);
+            }
+        }
+    }
+
+    /// Toggle the orientation of the active window.
+    pub fn toggle_orientation(&mut self, t: &mut TCellOwner<T>) {
+        if let Some(active) = self.active_window() {
+            if let Some(fork) = active.fork(t) {
+                fork.toggle_orientation(self, t);
+            }
+        }
+        self.dispatch_events(t);
+    }
+
+    /// Collapses every window on the active workspace into a single stack spanning the
+    /// workspace, or restores the tree that the windows were in before they were collapsed.
+    pub fn toggle_tabbed_workspace(&mut self, t: &mut TCellOwner<T>) {
+        let workspace = self.active_workspace;
+
+        let workspace_ = ward::ward!(self.workspaces.get(&workspace).cloned(), else { return });
+
+        if self.tiling_disabled.contains_key(&workspace) {
+            return;
+        }
+
+        let root = ward::ward!(workspace_.fork(t), else { return });
+        let windows: Vec<WindowPtr<T>> = root.windows(t).collect();
+
+        let layout = match self.tabbed.remove(&workspace) {
+            Some(layout) => Some(layout),
+            None => {
+                self.tabbed.insert(workspace, Layout::capture(&root, t));
+                None
+            }
+        };
+
+        for window in &windows {
+            self.detach_from_tree(window, t);
+        }
+
+        if let Some(layout) = layout {
+            for window in &windows {
+                if !window.ro(t).visible {
+                    window.rw(t).visible = true;
+                    let id = window.id(t);
+                    self.event_queue.windows.entry(id).or_default().visibility = Some(true);
+                }
+            }
+
+            self.tree_restore(&workspace_, Some(layout), windows, t);
+        } else {
+            let focus = self
+                .active_window()
+                .cloned()
+                .filter(|focus| windows.iter().any(|window| Rc::ptr_eq(window, focus)));
+
+            let area = workspace_.area(t);
+
+            let mut windows = windows.into_iter();
+            let first = ward::ward!(windows.next(), else { return });
+
+            let layout = Layout::Stack {
+                windows: vec![first.id(t)],
+                active: first.id(t),
+            };
+
+            let root = ward::ward!(layout.build(self, area, workspace, t), else { return });
+
+            let stack = ward::ward!(first.stack(t), else { return });
+
+            for window in windows {
+                window.fork_set(root.clone(), t);
+                stack.attach(&window, t);
+                self.event_queue.stack_assign(&stack, &window, t);
+            }
+
+            let active = focus.unwrap_or(first);
+            stack.rw(t).active = active.clone();
+
+            // Only the active tab is shown.
+            for window in stack.ro(t).windows.clone() {
+                let visible = Rc::ptr_eq(&window, &active);
+                window.rw(t).visible = visible;
+                let id = window.id(t);
+                self.event_queue.windows.entry(id).or_default().visibility = Some(visible);
+            }
+
+            workspace_.rw(t).fork = Some(root.clone());
+            root.work_area_update(self, area, t);
+
+            self.set_active_window(&active, t);
+        }
+
+        self.dispatch_events(t);
+    }
+
+    /// Pin the window that subsequent attaches will be attached to, instead of the active
+    /// window. Passing `None` restores attaching to the active window.
+    pub fn set_attach_anchor(&mut self, window: Option<&WindowPtr<T>>, t: &TCellOwner<T>) {
+        if let Some(window) = window {
+            if !self.windows.contains_key(&window.id(t)) {
+                tracing::error!("cannot anchor attaches to an unmanaged window");
+                return;
+            }
+        }
+
+        self.attach_anchor = window.cloned();
+    }
+
+    /// Moves the split of the innermost fork bounding the active window on the axis of
+    /// the direction, by a percentage of that fork's dimensions.
+    pub fn resize_active(
+        &mut self,
+        direction: Direction,
+        delta_percent: i32,
+        t: &mut TCellOwner<T>,
+    ) {
+        self.resize_in_direction(direction, delta_percent, false, t);
+        self.dispatch_events(t);
+    }
+
+    /// Moves the split of the outermost fork bounding the active window's subtree on the
+    /// axis of the direction, by a percentage of that fork's dimensions.
+    pub fn resize_container(
+        &mut self,
+        direction: Direction,
+        delta_percent: i32,
+        t: &mut TCellOwner<T>,
+    ) {
+        self.resize_in_direction(direction, delta_percent, true, t);
+        self.dispatch_events(t);
+    }
+
+    /// Moves the split of an ancestor fork of the active window in the given direction.
+    fn resize_in_direction(
+        &mut self,
+        direction: Direction,
+        delta_percent: i32,
+        outermost: bool,
+        t: &mut TCellOwner<T>,
+    ) {
+        let active = ward::ward!(self.active_window().cloned(), else { return });
+
+        let orientation = match direction {
+            Direction::Above | Direction::Below => Orientation::Vertical,
+            Direction::Left | Direction::Right => Orientation::Horizontal,
+        };
+
+        // Walk up the tree to find the fork that is split on the requested axis.
+        let mut candidate = None;
+        let mut next = active.fork(t);
+
+        while let Some(fork) = next {
+            let fork_ = fork.ro(t);
+            let matches = fork_.orientation == orientation && fork_.right.is_some();
+            next = fork_.parent.clone();
+
+            if matches {
+                candidate = Some(fork);
+
+                if !outermost {
+                    break;
+                }
+            }
+        }
+
+        let fork = ward::ward!(candidate, else {
+            tracing::debug!("no fork to resize in direction {:?}", direction);
+            return;
+        });
+
+        let (handle, extent) = {
+            let fork_ = fork.ro(t);
+            let extent = match orientation {
+                Orientation::Horizontal => fork_.area.width,
+                Orientation::Vertical => fork_.area.height,
+            };
+
+            (fork_.split_handle as i64, extent as i64)
+        };
+
+        let mut delta = extent * delta_percent as i64 / 100;
+
+        if let Direction::Above | Direction::Left = direction {
+            delta = -delta;
+        }
+
+        let split = (handle + delta).max(0).min(extent) as u32;
+
+        self.fork_resize_by_mode(&fork, split, t);
+    }
+
+    /// Rotates the windows of the active workspace through the positions of its tree,
+    /// while keeping the structure of the tree intact.
+    ///
+    /// Moving forward, each window moves into the position of the window after it, in
+    /// depth-first order, and the last window moves into the first position.
+    pub fn rotate_windows(&mut self, forward: bool, t: &mut TCellOwner<T>) {
+        let workspace = ward::ward!(self.workspaces.get(&self.active_workspace), else { return });
+        let fork = ward::ward!(workspace.fork(t), else { return });
+
+        let mut windows: Vec<WindowPtr<T>> = fork.windows(t).collect();
+        let count = windows.len();
+
+        if count < 2 {
+            return;
+        }
+
+        // Determine which window will occupy the position of the active window.
+        let active = self.active_window().cloned();
+        let focus = active.as_ref().map(|active| {
+            match windows.iter().position(|w| Rc::ptr_eq(w, active)) {
+                Some(pos) if self.rotate_focus_follows_slot => {
+                    let pos = if forward { pos + count - 1 } else { pos + 1 };
+                    windows[pos % count].clone()
+                }
+                _ => active.clone(),
+            }
+        });
+
+        if !forward {
+            windows.reverse();
+        }
+
+        // Swapping each window with the next moves every window forward one position.
+        for pair in windows.windows(2) {
+            pair[0].swap_position_with(self, &pair[1], t);
+        }
+
+        if let Some(focus) = focus {
+            self.set_active_window(&focus, t);
+        }
+        self.dispatch_events(t);
+    }
+
+    /// The least average area of the tiles of a workspace, below which a window attached
+    /// beside a window is stacked with it rather than splitting it. Each stack counts as
+    /// one tile.
+    ///
+    /// Disabled with `None`, which is the default.
+    pub fn set_auto_stack_threshold(&mut self, area: Option<u32>) {
+        self.auto_stack_threshold = area;
+    }
+
+    /// The most windows that a workspace may hold, or `None` for no limit, which is the
+    /// default. Windows attached to a workspace at its capacity are attached to its overflow
+    /// workspace instead, which is created on the same display if none was designated.
+    pub fn set_workspace_capacity(&mut self, workspace: u32, capacity: Option<usize>) {
+        match capacity {
+            Some(capacity) => self.workspace_capacity.insert(workspace, capacity),
+            None => self.workspace_capacity.remove(&workspace),
+        };
+    }
+
+    /// The workspace that windows go to which do not fit on a workspace at its capacity,
+    /// or `None` to create one when it is needed.
+    pub fn set_overflow_workspace(&mut self, workspace: u32, overflow: Option<u32>) {
+        match overflow {
+            Some(overflow) => self.overflow_workspaces.insert(workspace, overflow),
+            None => self.overflow_workspaces.remove(&workspace),
+        };
+    }
+
+    /// Whether a window attached beside a window of the same client, as identified by the
+    /// first component of their IDs, is stacked with it rather than splitting it. A window
+    /// attached beside the stack of another client splits the stack instead of joining it.
+    pub fn set_auto_stack_same_client(&mut self, stack: bool) {
+        self.auto_stack_same_client = stack;
+    }
+
+    /// The most forks that may lie on a path from the root of a tree.
+    ///
+    /// Windows attached beside a window whose fork is at this depth are stacked with it,
+    /// rather than splitting it into a deeper fork.
+    pub fn set_max_tree_depth(&mut self, depth: Option<usize>) {
+        self.max_tree_depth = depth;
+    }
+
+    /// The fewest pixels that either side of a split may be resized to.
+    ///
+    /// Forks too small to honor this on both sides are split evenly. Takes effect the next
+    /// time a fork is resized or its work area is updated.
+    pub fn set_min_split_pixels(&mut self, pixels: u32) {
+        self.min_split_pixels = pixels;
+    }
+
+    /// The percentage that [`Tiler::normalize_ratios`] rounds the splits of forks to.
+    ///
+    /// Defaults to 5, and is kept between 1 and 50.
+    pub fn set_ratio_snap(&mut self, percent: u32) {
+        self.ratio_snap = percent.clamp(1, 50);
+    }
+
+    /// The orientation of the root fork created when a window is attached to an empty
+    /// workspace, rather than the one preferred by the shape of the workspace.
+    ///
+    /// Takes effect the next time a workspace gains its first window.
+    pub fn set_split_on_empty_workspace_orientation(&mut self, orientation: Option<Orientation>) {
+        self.empty_workspace_orientation = orientation;
+    }
+
+    /// The display whose active workspace windows are attached to when the active
+    /// workspace does not exist.
+    pub fn set_primary_display(&mut self, display: u32) {
+        self.primary_display = Some(display);
+    }
+
+    /// Whether focus moves to the window that rotates into the active window's position,
+    /// rather than staying on the active window as it rotates.
+    pub fn set_rotate_focus_follows_slot(&mut self, follows: bool) {
+        self.rotate_focus_follows_slot = follows;
+    }
+
+    /// Align window and stack placements on this display to its physical pixels, for a
+    /// display with this scale factor. Placements remain in logical coordinates.
+    pub fn set_placement_rounding(
+        &mut self,
+        display: u32,
+        scale: f64,
+        mode: RoundingMode,
+        t: &mut TCellOwner<T>,
+    ) {
+        let display = ward::ward!(self.displays.get(&display).cloned(), else {
+            tracing::error!("cannot set placement rounding of non-existent display");
+            return;
+        });
+
+        display.rw(t).rounding = Some((pixel_grid(scale), mode));
+        display.work_area_refresh(self, t);
+        self.dispatch_events(t);
+    }
+
+    /// Lays out windows on this display in its area with this transform applied, such as
+    /// in portrait for a landscape display that is rotated by 90 degrees.
+    pub fn set_output_transform(
+        &mut self,
+        display: u32,
+        transform: Transform,
+        t: &mut TCellOwner<T>,
+    ) {
+        let display = ward::ward!(self.displays.get(&display).cloned(), else {
+            tracing::error!("cannot set transform of non-existent display");
+            return;
+        });
+
+        if display.ro(t).transform == transform {
+            return;
+        }
+
+        display.rw(t).transform = transform;
+        display.work_area_refresh(self, t);
+        self.dispatch_events(t);
+    }
+
+    /// Whether placements of windows which only change the gaps around them, as when
+    /// adaptive gaps change with the number of tiled windows, are tagged as gap transitions
+    /// and carry the rect that the window had before, so that the compositor may animate
+    /// the gap.
+    pub fn set_default_gap_animation(&mut self, animate: bool) {
+        self.gap_animation = animate;
+    }
+
+    /// Gaps between tiles which shrink as more windows are tiled on a workspace.
+    ///
+    /// The gap of each workspace is `base`, less `per_window_reduction` for every tiled
+    /// window after the first, but never below `min`.
+    pub fn set_adaptive_gaps(
+        &mut self,
+        base: u32,
+        per_window_reduction: u32,
+        min: u32,
+        t: &mut TCellOwner<T>,
+    ) {
+        self.adaptive_gaps = Some(AdaptiveGaps {
+            base,
+            per_window_reduction,
+            min,
+        });
+
+        self.dispatch_events(t);
+    }
+
+    /// The gaps around the tree of each workspace, and at its splits by what is on
+    /// either side of them. Replaces adaptive gaps.
+    pub fn set_gap_config(&mut self, config: GapConfig, t: &mut TCellOwner<T>) {
+        self.gap_config = Some(config);
+        self.dispatch_events(t);
+    }
+
+    /// Keeps at least this many pixels of the width and height of each placement within
+    /// the display of its workspace, moving placements which would lie beyond it. The
+    /// tree is unchanged. Zero disables the clamp.
+    pub fn set_minimum_visible_area(&mut self, pixels: u32) {
+        self.minimum_visible_area = pixels;
+    }
+
+    /// Which window is focused when switching to a workspace or display.
+    pub fn set_workspace_focus_policy(&mut self, policy: WorkspaceFocusPolicy) {
+        self.workspace_focus_policy = policy;
+    }
+
+    /// Whether toggling the orientation of a fork twice swaps its branches, such that
+    /// toggling cycles the window of the left branch through each side of the fork.
+    ///
+    /// When disabled, toggling only changes the orientation. Enabled by default.
+    pub fn set_swap_on_double_toggle(&mut self, swap: bool) {
+        self.swap_on_double_toggle = swap;
+    }
+
+    /// How moving the split of a fork affects the other splits along the same axis, when
+    /// forks are resized with [`Tiler::fork_resize`], [`Tiler::resize_active`], or
+    /// [`Tiler::resize_container`].
+    pub fn set_resize_mode(&mut self, mode: ResizeMode) {
+        self.resize_mode = mode;
+    }
 
-            // Remove the orphaned fork
-            parent.rw(t).parent.take();
-            tiler.detach_fork(parent, t);
+    /// How far the split of a fork has to be dragged before [`Tiler::fork_resize`] moves
+    /// it, such that an interactive resize does not place every window it affects on
+    /// every pixel. Zero, the default, moves the split on every resize.
+    pub fn set_resize_throttle(&mut self, pixels: u32) {
+        self.resize_throttle = pixels;
+    }
+
+    /// Which window is focused after the active window is swapped with another.
+    pub fn set_swap_focus_policy(&mut self, policy: SwapFocusPolicy) {
+        self.swap_focus_policy = policy;
+    }
+
+    /// Whether a window is demanding attention.
+    pub fn set_urgent(&mut self, window: &WindowPtr<T>, urgent: bool, t: &mut TCellOwner<T>) {
+        if window.ro(t).urgent == urgent {
+            return;
         }
 
-        let fork_ = fork.rw(t);
+        window.rw(t).urgent = urgent;
 
-        if fork_.left_is(reference) {
-            tracing::debug!("detaching left branch of fork");
-            if let Some(right) = fork_.right.take() {
-                tracing::debug!("right branch of fork was assigned to left branch");
-                fork_.left = right;
-
-                if let Branch::Fork(ref grandchild) = fork_.left {
-                    tracing::debug!("reparenting left branch of fork into parent");
-                    reparent(
-                        self,
-                        fork_.parent.clone(),
-                        fork.clone(),
-                        grandchild.clone(),
-                        t,
-                    );
-                }
-            } else {
-                tracing::debug!("fork is now childless");
-                self.detach_fork(fork, t);
+        let id = window.id(t);
+        self.event_queue.windows.entry(id).or_default().urgency = Some(urgent);
+
+        if urgent && self.focus_steals_on_urgent {
+            window.focus(self, t);
+        }
+
+        self.dispatch_events(t);
+    }
+
+    /// Sizes a window in multiples of these increments, such as the character cells of a
+    /// terminal. The window is centered in the area left over.
+    pub fn set_resize_increment(
+        &mut self,
+        window: &WindowPtr<T>,
+        width: u32,
+        height: u32,
+        t: &mut TCellOwner<T>,
+    ) {
+        window.rw(t).resize_increment = Some((width, height));
+
+        if let Some(stack) = window.stack(t) {
+            stack.work_area_refresh(self, t);
+        } else if let Some(fork) = window.fork(t) {
+            fork.work_area_refresh(self, t);
+        }
+
+        self.dispatch_events(t);
+    }
+
+    /// The least width and height that a window asks for, which splits are fitted to by
+    /// [`Tiler::resize_fork_to_fit`].
+    pub fn set_min_size(
+        &mut self,
+        window: &WindowPtr<T>,
+        width: u32,
+        height: u32,
+        t: &mut TCellOwner<T>,
+    ) {
+        window.rw(t).min_size = Some((width, height));
+    }
+
+    /// Which side of the window it is attached beside that a window is attached on.
+    ///
+    /// Takes effect the next time the window is attached.
+    pub fn set_gravity(&mut self, window: &WindowPtr<T>, gravity: Gravity, t: &mut TCellOwner<T>) {
+        window.rw(t).gravity = gravity;
+    }
+
+    /// Shrinks the placement of a window by these insets, such as for a client which draws
+    /// its shadows outside of its geometry. Neighboring windows keep their full areas.
+    pub fn set_placement_padding(
+        &mut self,
+        window: &WindowPtr<T>,
+        insets: Insets,
+        t: &mut TCellOwner<T>,
+    ) {
+        window.rw(t).padding = insets;
+
+        if let Some(stack) = window.stack(t) {
+            stack.work_area_refresh(self, t);
+        } else if let Some(fork) = window.fork(t) {
+            fork.work_area_refresh(self, t);
+        }
+
+        self.dispatch_events(t);
+    }
+
+    /// Whether the window that is alone on its workspace is reported to have no border.
+    pub fn set_auto_hide_single_border(&mut self, hide: bool, t: &mut TCellOwner<T>) {
+        self.auto_hide_single_border = hide;
+        self.dispatch_events(t);
+    }
+
+    /// Whether the border of each tiled window is drawn only on its edges which lie between
+    /// it and another tile, and not on those on the boundary of the tiled region of its
+    /// workspace. Reported with [`Event::WindowBorders`].
+    pub fn set_smart_borders(&mut self, smart: bool, t: &mut TCellOwner<T>) {
+        self.smart_borders = smart;
+        self.dispatch_events(t);
+    }
+
+    /// Where windows open on a workspace with tiling disabled, relative to the origin of
+    /// its display. Windows after the first are cascaded from this rect.
+    ///
+    /// By default, windows open at half the size of the display, from its origin.
+    pub fn set_default_float_rect(&mut self, rect: Rect) {
+        self.default_float_rect = Some(rect);
+    }
+
+    /// Whether directional focus stays on the active display, rather than moving to the
+    /// display in that direction when there are no more windows that way.
+    ///
+    /// Focus may still be moved between displays with [`Tiler::focus_display_left`] and
+    /// the like.
+    pub fn set_display_isolation(&mut self, isolation: bool) {
+        self.display_isolation = isolation;
+    }
+
+    /// The display that a workspace returns to whenever the display is added, such as
+    /// after the display was unplugged and its workspaces migrated to another.
+    pub fn set_workspace_affinity(&mut self, workspace: u32, display: u32) {
+        self.workspace_affinity.insert(workspace, display);
+    }
+
+    /// Which window is focused after the active window is detached.
+    pub fn set_detach_refocus(&mut self, refocus: DetachRefocus) {
+        self.detach_refocus = refocus;
+    }
+
+    /// How many windows the focus history holds, after which the least recently focused
+    /// windows are forgotten. Defaults to 32.
+    pub fn set_focus_history_limit(&mut self, limit: usize) {
+        self.focus_history_limit = limit;
+        self.focus_history.truncate(limit);
+    }
+
+    /// Shows the active workspace of the `src` display on the `dst` display as well, with
+    /// each stack and window on it reported with [`Event::StackMirror`] and
+    /// [`Event::WindowMirror`], scaled to the area of `dst`, whenever it moves or `src`
+    /// switches workspaces. The windows of the workspaces of `dst` are hidden until it
+    /// stops mirroring with [`Tiler::clear_display_mirror`].
+    pub fn set_display_mirror(&mut self, src: u32, dst: u32, t: &mut TCellOwner<T>) {
+        if src == dst || !self.displays.contains_key(&dst) {
+            tracing::error!("cannot mirror display {} on display {}", src, dst);
+            return;
+        }
+
+        if !self.displays.contains_key(&src) {
+            tracing::error!("cannot mirror non-existent display {}", src);
+            return;
+        }
+
+        self.display_mirrors.insert(dst, src);
+        self.mirror_visibility_set(dst, false, t);
+        self.dispatch_events(t);
+    }
+
+    /// Stops the display from mirroring another, and shows its own workspace again.
+    pub fn clear_display_mirror(&mut self, dst: u32, t: &mut TCellOwner<T>) {
+        if self.display_mirrors.remove(&dst).is_some() {
+            self.mirror_visibility_set(dst, true, t);
+            self.dispatch_events(t);
+        }
+    }
+
+    /// Hides or shows the windows of the active workspace of a display which starts or
+    /// stops mirroring another. Of the windows in a stack, only the active one is shown.
+    fn mirror_visibility_set(&mut self, display: u32, visible: bool, t: &mut TCellOwner<T>) {
+        let workspace = self.displays.get(&display).and_then(|d| d.ro(t).active);
+        let workspace = ward::ward!(workspace, else { return });
+
+        for (&id, window) in self.windows.iter() {
+            let this = window.ro(t);
+
+            if this.workspace != workspace {
+                continue;
             }
-        } else if fork_.right_is(reference) {
-            tracing::debug!("detaching right branch of fork");
-            fork_.right = None;
 
-            if let Branch::Fork(ref grandchild) = fork_.left.clone() {
-                tracing::debug!("reparenting left branch into parent fork");
-                reparent(
-                    self,
-                    fork_.parent.clone(),
-                    fork.clone(),
-                    grandchild.clone(),
-                    t,
-                );
+            let shown = visible
+                && this
+                    .stack
+                    .as_ref()
+                    .map_or(true, |stack| Rc::ptr_eq(&stack.ro(t).active, window));
+
+            if this.visible != shown {
+                window.rw(t).visible = shown;
+                self.event_queue.windows.entry(id).or_default().visibility = Some(shown);
             }
         }
     }
 
-    /// Removes a display from the tree, and migrates its workspaces to another display
-    pub fn display_detach(&mut self, display_id: u32, t: &mut TCellOwner<T>) {
-        // Get the active display to assign to.
-        let active = self
-            .workspaces
-            .get(&self.active_workspace)
-            .expect("active workspace doesn't exist")
-            .ro(t)
-            .parent
-            .clone();
+    /// Whether moving focus past the last display in a direction wraps around to the
+    /// display at the opposite end.
+    pub fn set_display_focus_wrap(&mut self, wrap: bool) {
+        self.display_focus_wrap = wrap;
+    }
 
-        // Remove the display from the tiler.
-        let display_ptr = ward::ward!(self.displays.remove(&display_id), else {
-            tracing::error!("detach of non-existent display: {}", display_id);
+    /// Whether moving focus past the last window in a direction, where no display lies
+    /// beyond it, wraps around to the window at the opposite edge of the workspace.
+    pub fn set_focus_wrap(&mut self, wrap: bool) {
+        self.focus_wrap = wrap;
+    }
+
+    /// How much events are coalesced before they are retrieved.
+    pub fn set_event_compaction(&mut self, compaction: EventCompaction) {
+        self.event_queue.compaction = compaction;
+    }
+
+    /// How many pixels each dimension of a placement of a window or stack may differ from
+    /// the placement last reported for it before it is reported again, which smooths out
+    /// the jitter of placements rounded to the pixels of scaled displays during resizes.
+    ///
+    /// Every changed placement is reported with `0`, which is the default.
+    pub fn set_placement_epsilon(&mut self, pixels: u32) {
+        self.event_queue.placement_epsilon = pixels;
+    }
+
+    /// Whether window and stack placements carry the rect that they had before, for
+    /// compositors which animate between the two.
+    pub fn set_emit_previous_rect(&mut self, emit: bool) {
+        self.emit_previous_rect = emit;
+    }
+
+    /// Whether focus events carry a serial which increases with each focus change.
+    pub fn set_focus_animation_token(&mut self, enabled: bool) {
+        self.focus_serials = enabled;
+    }
+
+    /// Whether a window which starts demanding attention is focused, switching to its
+    /// workspace if necessary.
+    pub fn set_focus_steals_on_urgent(&mut self, steals: bool) {
+        self.focus_steals_on_urgent = steals;
+    }
+
+    /// Whether a fullscreen window hides the other windows of its workspace. When disabled,
+    /// the window covers its display without hiding the windows beneath it.
+    ///
+    /// Takes effect the next time a window is made fullscreen.
+    pub fn set_fullscreen_hides_siblings(&mut self, hides: bool) {
+        self.fullscreen_hides_siblings = hides;
+    }
+
+    /// Whether focusing another display also makes its workspace the active workspace.
+    ///
+    /// When disabled, only the focused window changes.
+    pub fn set_focus_follows_workspace(&mut self, follows: bool) {
+        self.focus_follows_workspace = follows;
+    }
+
+    /// Whether windows on this workspace are tiled.
+    ///
+    /// Disabling tiling floats every window on the workspace, and windows attached to it
+    /// are floated as well. Enabling it again tiles the windows in the tree they had.
+    pub fn set_tiling_enabled(&mut self, workspace: u32, enabled: bool, t: &mut TCellOwner<T>) {
+        let workspace_ = ward::ward!(self.workspaces.get(&workspace).cloned(), else {
+            tracing::error!("cannot set tiling of non-existent workspace {}", workspace);
             return;
         });
 
-        // Take ownership of its workspaces.
-        let mut workspaces = HashMap::new();
-        std::mem::swap(&mut workspaces, &mut display_ptr.rw(t).workspaces);
+        if enabled {
+            let floating = ward::ward!(self.tiling_disabled.remove(&workspace), else {
+                return;
+            });
 
-        // Migrate workspaces.
-        for workspace in workspaces.into_values() {
-            active.assign_workspace(workspace, t);
+            self.tree_restore(&workspace_, floating.layout, floating.windows, t);
+        } else if !self.tiling_disabled.contains_key(&workspace) {
+            let root = workspace_.fork(t);
+
+            let layout = root.as_ref().map(|root| Layout::capture(root, t));
+            let windows: Vec<WindowPtr<T>> = match root {
+                Some(root) => root.windows(t).collect(),
+                None => Vec::new(),
+            };
+
+            for window in &windows {
+                self.detach_from_tree(window, t);
+            }
+
+            let floating = FloatingWorkspace {
+                layout,
+                windows: Vec::new(),
+            };
+
+            self.tiling_disabled.insert(workspace, floating);
+
+            for window in &windows {
+                self.attach_floating(window, workspace, None, t);
+            }
         }
+
+        self.dispatch_events(t);
     }
 
-    /// Creates or updates a display associated with the tree.
-    pub fn display_update(&mut self, display: u32, area: Rect, t: &mut TCellOwner<T>) {
-        let display = self
-            .displays
-            .entry(display)
-            .or_insert_with(|| DisplayPtr::new(area))
-            .clone();
+    /// Records the tree of a workspace and the windows in it, if the workspace has a tree.
+    pub fn snapshot_workspace(
+        &self,
+        workspace: u32,
+        t: &TCellOwner<T>,
+    ) -> Option<WorkspaceSnapshot> {
+        let root = self.workspaces.get(&workspace)?.fork(t)?;
 
-        display.work_area_update(self, area, t);
+        Some(WorkspaceSnapshot {
+            root: Layout::capture(&root, t),
+        })
     }
 
-    /// Retrieves the latest set of instructions for the window manager to carry out.
-    pub fn events<'a>(&'a mut self, t: &'a mut TCellOwner<T>) -> impl Iterator<Item = Event> + 'a {
-        let focus: Option<Event> = if self.active_changed {
-            self.active_window().map(|a| Event::Focus(a.id(t)))
-        } else {
-            None
+    /// Rebuilds the tree of a workspace in the shape of a snapshot, moving the windows of
+    /// the snapshot into it.
+    ///
+    /// Windows in the snapshot which are not managed by the tiler are skipped, and windows
+    /// of the workspace which are not in the snapshot are attached after.
+    pub fn restore_workspace(
+        &mut self,
+        workspace: u32,
+        snapshot: WorkspaceSnapshot,
+        t: &mut TCellOwner<T>,
+    ) {
+        let workspace_ = ward::ward!(self.workspaces.get(&workspace).cloned(), else {
+            tracing::error!("cannot restore snapshot to non-existent workspace {}", workspace);
+            return;
+        });
+
+        // Each window is placed once, at the first of its positions in the snapshot.
+        let mut seen = BTreeSet::new();
+        let windows = &self.windows;
+        let root = snapshot
+            .root
+            .retain(&mut |id| windows.contains_key(&id) && seen.insert(id));
+
+        let ids = root.as_ref().map_or_else(Vec::new, Layout::window_ids);
+
+        let previous: Vec<WindowPtr<T>> = match workspace_.fork(t) {
+            Some(root) => root.windows(t).collect(),
+            None => Vec::new(),
         };
 
-        self.active_changed = false;
+        for window in &previous {
+            self.detach_from_tree(window, t);
+        }
 
-        let workspace_switch = if self.active_workspace_changed {
-            Some(Event::FocusWorkspace(self.active_workspace))
-        } else {
-            None
+        for id in &ids {
+            if let Some(window) = self.windows.get(id).cloned() {
+                self.detach_from_tree(&window, t);
+            }
+        }
+
+        if let Some(root) = root {
+            let area = workspace_.area(t);
+
+            if let Some(fork) = root.build(self, area, workspace, t) {
+                workspace_.rw(t).fork = Some(fork.clone());
+                fork.work_area_update(self, area, t);
+            }
+        }
+
+        for window in previous {
+            if !ids.contains(&window.id(t)) {
+                self.attach_to_workspace(&window, &workspace_, t);
+            }
+        }
+
+        let windows: Vec<WindowPtr<T>> = match workspace_.fork(t) {
+            Some(root) => root.windows(t).collect(),
+            None => Vec::new(),
         };
 
-        self.event_queue
-            .consume_events()
-            .chain(workspace_switch.into_iter())
-            .chain(focus.into_iter())
+        // Windows are only shown on a shown workspace, and of a stack only its active tab.
+        let shown = self.workspace_shown(workspace, t);
+
+        for window in &windows {
+            let visible = shown
+                && window
+                    .stack(t)
+                    .map_or(true, |stack| Rc::ptr_eq(&stack.ro(t).active, window));
+
+            if window.ro(t).visible != visible {
+                window.rw(t).visible = visible;
+
+                let id = window.id(t);
+                self.event_queue.windows.entry(id).or_default().visibility = Some(visible);
+            }
+        }
+
+        let focus = workspace_.ro(t).focus.clone();
+        if focus.map_or(true, |focus| !windows.iter().any(|w| Rc::ptr_eq(w, &focus))) {
+            workspace_.rw(t).focus = windows.first().cloned();
+        }
+
+        self.dispatch_events(t);
     }
 
-    /// Focus this window in the tree.
-    pub fn focus(&mut self, window: &WindowPtr<T>, t: &mut TCellOwner<T>) {
-        window.focus(self, t);
+    /// Rebuilds the tree of a workspace in the shape of a layout. Windows which do not fit
+    /// in the layout are attached after.
+    fn tree_restore(
+        &mut self,
+        workspace_: &WorkspacePtr<T>,
+        layout: Option<Layout>,
+        mut windows: Vec<WindowPtr<T>>,
+        t: &mut TCellOwner<T>,
+    ) {
+        let workspace = workspace_.id(t);
+
+        // Windows fill the tree they were in, and any others are attached after.
+        let slots = layout.as_ref().map_or(0, Layout::slots);
+        let remaining = windows.split_off(slots.min(windows.len()));
+
+        if let Some(layout) = layout {
+            if !windows.is_empty() {
+                let area = workspace_.area(t);
+                let root = layout.build_template(self, area, workspace, windows, t);
+
+                if let Some(root) = root.as_ref() {
+                    root.work_area_update(self, area, t);
+                }
+
+                workspace_.rw(t).fork = root;
+            }
+        }
+
+        for window in remaining {
+            self.attach_to_workspace(&window, workspace_, t);
+        }
     }
 
-    /// Move focus to the window above the active one.
-    pub fn focus_above(&mut self, t: &mut TCellOwner<T>) {
-        match self.window_in_direction(Rect::distance_upward, Rect::is_below, t) {
-            Some(active) => self.set_active_window(&active, t),
-            None => self.focus_display_above(t),
+    /// Pass events to this callback at the end of every operation, instead of queueing
+    /// them to be retrieved with `events()`.
+    pub fn set_event_sink(&mut self, sink: Box<dyn FnMut(Event)>) {
+        self.event_sink = Some(sink);
+    }
+
+    /// Consult this rule on every attach, to decide where the window is placed.
+    pub fn set_attach_rule(&mut self, rule: Box<dyn Fn(WindowID) -> AttachDecision>) {
+        self.attach_rule = Some(rule);
+    }
+
+    /// Focus the window with this ID on behalf of the window manager, which supplied this
+    /// serial. The focus event carries the serial back, so that the window manager can
+    /// recognise the focus change as its own.
+    pub fn set_active_window_by_id(
+        &mut self,
+        id: WindowID,
+        serial: u64,
+        t: &mut TCellOwner<T>,
+    ) -> Result<(), WindowNotFound> {
+        let window = self.window_by_id(id)?;
+        self.focus_echo = Some(serial);
+        self.focus(&window, t);
+        Ok(())
+    }
+
+    /// Set a new active window, and mark that we should notify the window manager.
+    pub(crate) fn set_active_window(&mut self, window: &WindowPtr<T>, t: &mut TCellOwner<T>) {
+        self.set_active_window_without_switch(window, t);
+
+        let workspace = window.ro(t).workspace;
+
+        if self.active_workspace != workspace {
+            self.workspace_activate(workspace, t);
         }
     }
 
-    /// Move focus to the window below the active one.
-    pub fn focus_below(&mut self, t: &mut TCellOwner<T>) {
-        match self.window_in_direction(Rect::distance_downward, Rect::is_above, t) {
-            Some(active) => self.set_active_window(&active, t),
-            None => self.focus_display_below(t),
+    /// Set a new active window as [`Tiler::set_active_window`] does, but leave the active
+    /// workspace as it is, even if the window is on another.
+    fn set_active_window_without_switch(&mut self, window: &WindowPtr<T>, t: &mut TCellOwner<T>) {
+        self.active = Some(window.clone());
+        self.active_changed = true;
+
+        let id = window.id(t);
+
+        // A window no longer demands attention once it has it.
+        if window.ro(t).urgent {
+            window.rw(t).urgent = false;
+            self.event_queue.windows.entry(id).or_default().urgency = Some(false);
         }
+        self.focus_history.retain(|&recent| recent != id);
+        self.focus_history.insert(0, id);
+        self.focus_history.truncate(self.focus_history_limit);
     }
 
-    /// Move focus to the window left of the active one.
-    pub fn focus_left(&mut self, t: &mut TCellOwner<T>) {
-        self.focus_with_stack(StackPtr::select_left, Self::focus_left_absolute, t);
+    /// Whether to draw a border around this window, regardless of whether it is alone on
+    /// its workspace.
+    pub fn set_window_decoration(
+        &mut self,
+        window: &WindowPtr<T>,
+        has_border: bool,
+        t: &mut TCellOwner<T>,
+    ) {
+        window.rw(t).decoration_override = Some(if has_border {
+            Decoration::Border
+        } else {
+            Decoration::NoBorder
+        });
+
+        self.dispatch_events(t);
     }
 
-    /// Move focus to the left window, even if in a stack.
-    pub fn focus_left_absolute(&mut self, t: &mut TCellOwner<T>) {
-        match self.window_in_direction(Rect::distance_westward, Rect::is_right, t) {
-            Some(active) => self.set_active_window(&active, t),
-            None => self.focus_display_left(t),
+    /// Destroy this stack, and stop tracking it in the tiler.
+    pub(crate) fn stack_destroy(&mut self, stack: &StackPtr<T>, t: &TCellOwner<T>) {
+        self.event_queue.stack_destroy(stack, t);
+        self.stacks.remove(&stack.id(t));
+    }
+
+    /// The area of the windows of a stack with this area, beside the space reserved for
+    /// its tab bar.
+    pub(crate) fn stack_window_area(&self, area: Rect) -> Rect {
+        let tab_bar = self.tab_bar_height.min(area.height);
+        match self.tab_bar_position {
+            TabBarPosition::Top => {
+                Rect::new(area.x, area.y + tab_bar, area.width, area.height - tab_bar)
+            }
+            TabBarPosition::Bottom => Rect::new(area.x, area.y, area.width, area.height - tab_bar),
         }
     }
 
-    /// Move focus to the window right of the active one.
-    pub fn focus_right(&mut self, t: &mut TCellOwner<T>) {
-        self.focus_with_stack(StackPtr::select_right, Self::focus_right_absolute, t);
+    /// Keep track of this stack directly in the tiler, and assign it a unique ID.
+    pub(crate) fn stack_register(&mut self, stack: StackPtr<T>, t: &mut TCellOwner<T>) {
+        let id = self.stack_id_next;
+        self.stack_id_next += 1;
+
+        stack.rw(t).id = id;
+        self.stacks.insert(id, stack);
     }
 
-    /// Move focus to the right window, even if in a stack.
-    pub fn focus_right_absolute(&mut self, t: &mut TCellOwner<T>) {
-        match self.window_in_direction(Rect::distance_eastward, Rect::is_left, t) {
-            Some(active) => self.set_active_window(&active, t),
-            None => self.focus_display_right(t),
+    /// Reorder the tabs of a stack by the stack tab order.
+    fn stack_sort(&mut self, stack: &StackPtr<T>, t: &mut TCellOwner<T>) {
+        for movement in stack.sort(self.stack_tab_order, &self.focus_history, t) {
+            self.event_queue.stack_movement(stack, movement, t);
+        }
+    }
+
+    /// Whether moving focus past the first or last tab of a stack wraps around to the tab
+    /// at the other end, rather than leaving the stack.
+    ///
+    /// Focus leaving the windows at the edge of a workspace is controlled separately, by
+    /// [`Tiler::set_focus_wrap`].
+    pub fn set_stack_cycle_wrap(&mut self, wrap: bool) {
+        self.stack_cycle_wrap = wrap;
+    }
+
+    /// Which tab of a stack is raised when a window is attached to the stack.
+    pub fn set_new_stack_focus(&mut self, focus: NewStackFocus) {
+        self.new_stack_focus = focus;
+    }
+
+    /// Which tab of a stack is raised when its active tab is detached.
+    pub fn set_stack_refocus(&mut self, refocus: StackRefocus) {
+        self.stack_refocus = refocus;
+    }
+
+    /// How tabs are ordered when windows are attached to a stack.
+    pub fn set_stack_tab_order(&mut self, order: StackTabOrder) {
+        self.stack_tab_order = order;
+    }
+
+    /// How much space is reserved for the tab bar of a stack, above or below its windows.
+    pub fn set_tab_bar_height(&mut self, height: u32) {
+        self.tab_bar_height = height;
+    }
+
+    /// Which edge of a stack its tab bar is drawn on, and its windows are inset from.
+    pub fn set_tab_bar_position(&mut self, position: TabBarPosition) {
+        self.tab_bar_position = position;
+    }
+
+    /// Whether activating a tab with [`Tiler::activate_tab`] also focuses its window.
+    pub fn set_tab_activation_raises_focus(&mut self, focus: bool) {
+        self.tab_activation_raises_focus = focus;
+    }
+
+    /// Raises this window in its stack, and focuses it if tab activation raises focus.
+    pub fn activate_tab(&mut self, window: &WindowPtr<T>, t: &mut TCellOwner<T>) {
+        if !self.tab_activation_raises_focus {
+            self.raise_tab_without_focus(window, t);
+            return;
+        }
+
+        if let Some(stack) = window.stack(t) {
+            stack.rw(t).active = window.clone();
+            self.event_queue.stack_raise_window(&stack, window, t);
         }
+
+        self.focus(window, t);
     }
 
-    /// Focus the active window on this display.
-    fn focus_display(&mut self, display: DisplayPtr<T>, t: &mut TCellOwner<T>) {
-        let display = display.ro(t);
+    /// Raises this window in its stack, showing it in place of the other tabs, without
+    /// changing which window is focused.
+    pub fn raise_tab_without_focus(&mut self, window: &WindowPtr<T>, t: &mut TCellOwner<T>) {
+        let stack = ward::ward!(window.stack(t), else {
+            tracing::error!("cannot raise the tab of a window that is not stacked");
+            return;
+        });
 
-        if let Some(active) = display.active {
-            if let Some(workspace) = display.workspaces.get(&active) {
-                if let Some(active) = workspace.ro(t).focus.clone() {
-                    self.set_active_window(&active, t);
-                }
+        stack.rw(t).active = window.clone();
+        self.event_queue.stack_raise_window(&stack, window, t);
+
+        // Tabs are only shown on a shown workspace.
+        let shown = self.workspace_shown(stack.ro(t).workspace, t);
+
+        for this in stack.ro(t).windows.clone() {
+            let visible = shown && Rc::ptr_eq(&this, window);
+
+            if this.ro(t).visible != visible {
+                this.rw(t).visible = visible;
+
+                let id = this.id(t);
+                self.event_queue.windows.entry(id).or_default().visibility = Some(visible);
             }
         }
-    }
 
-    /// Move focus to the workspace on the display to the left of the active one.
-    pub fn focus_display_left(&mut self, t: &mut TCellOwner<T>) {
-        if let Some(display) = self.display_in_direction(Rect::distance_westward, Rect::is_right, t)
-        {
-            self.focus_display(display, t);
-        }
+        self.dispatch_events(t);
     }
 
-    /// Move focus to the workspace on the display to the right of the active one.
-    pub fn focus_display_right(&mut self, t: &mut TCellOwner<T>) {
-        if let Some(display) = self.display_in_direction(Rect::distance_eastward, Rect::is_left, t)
-        {
-            self.focus_display(display, t);
+    /// If a window is stacked, unstack it. If it is not stacked, stack it.
+    pub fn stack_toggle(&mut self, t: &mut TCellOwner<T>) {
+        if let Some(active) = self.active_window().cloned() {
+            active.stack_toggle(self, t);
         }
+        self.dispatch_events(t);
     }
 
-    /// Move focus to the workspace on the display above the active one.
-    pub fn focus_display_above(&mut self, t: &mut TCellOwner<T>) {
-        if let Some(display) = self.display_in_direction(Rect::distance_upward, Rect::is_below, t) {
-            self.focus_display(display, t);
-        }
-    }
+    /// Stacks these windows together at the location of the first, in the order given,
+    /// and returns the ID of the stack. If the first window is already stacked, the others
+    /// join its stack.
+    ///
+    /// Windows which are not tiled are skipped, and the rest must share a workspace.
+    pub fn stack_from_windows(&mut self, ids: &[WindowID], t: &mut TCellOwner<T>) -> Option<usize> {
+        let windows: Vec<WindowPtr<T>> = ids
+            .iter()
+            .filter_map(|id| self.windows.get(id))
+            .filter(|window| window.fork(t).is_some())
+            .cloned()
+            .collect();
 
-    /// Move focus to the workspace on the display below the active one.
-    pub fn focus_display_below(&mut self, t: &mut TCellOwner<T>) {
-        if let Some(display) = self.display_in_direction(Rect::distance_downward, Rect::is_above, t)
+        let (first, rest) = windows.split_first()?;
+        let workspace = first.ro(t).workspace;
+
+        if rest
+            .iter()
+            .any(|window| window.ro(t).workspace != workspace)
         {
-            self.focus_display(display, t);
+            tracing::error!("cannot stack windows from different workspaces");
+            return None;
         }
-    }
 
-    /// Manage focus movements with consideration for in-stack movements.
-    fn focus_with_stack(
-        &mut self,
-        stack_func: fn(&StackPtr<T>, &mut TCellOwner<T>) -> Option<WindowPtr<T>>,
-        focus_func: fn(&mut Self, &mut TCellOwner<T>),
-        t: &mut TCellOwner<T>,
-    ) {
-        let active = ward::ward!(self.active_window(), else { return });
+        if first.stack(t).is_none() {
+            first.stack_toggle(self, t);
+        }
 
-        // If window is in a stack, select the window to the left of the stack.
-        if let Some(stack) = active.stack(t) {
-            if let Some(left) = stack_func(&stack, t) {
-                stack.rw(t).active = left.clone();
-                self.set_active_window(&left, t);
-                return;
+        let stack = first.stack(t)?;
+
+        for window in rest {
+            if Rc::ptr_eq(window, first) {
+                continue;
             }
+
+            self.detach_from_tree(window, t);
+            self.attach_to_window(window, first, t);
         }
 
-        focus_func(self, t);
-    }
+        if let Some(root) = self.workspaces.get(&workspace).and_then(|w| w.fork(t)) {
+            root.work_area_refresh(self, t);
+        }
 
-    /// Keep track of this fork directly in the tiler.
-    fn fork_register(&mut self, fork: ForkPtr<T>, t: &TCellOwner<T>) {
-        self.event_queue.fork_update(&fork, t);
-        self.forks.insert(Rc::as_ptr(&fork) as usize, fork);
-    }
+        self.dispatch_events(t);
 
-    /// Resize a fork with a new split
-    pub fn fork_resize(&mut self, fork: usize, split: u32, t: &mut TCellOwner<T>) {
-        if let Some(fork) = self.forks.get(&fork).cloned() {
-            fork.resize(self, split, t);
-        }
+        Some(stack.id(t))
     }
 
-    /// When moving vertically or horizontally, move active window out of the stack.
-    fn move_from_stack(
-        &mut self,
-        active: &WindowPtr<T>,
-        fork: &ForkPtr<T>,
-        stack: &StackPtr<T>,
-        direction: Direction,
-        t: &mut TCellOwner<T>,
-    ) {
-        let (orientation, stack_on_left) = match direction {
-            Direction::Above => (Orientation::Vertical, false),
-            Direction::Below => (Orientation::Vertical, true),
-            Direction::Left => (Orientation::Horizontal, false),
-            Direction::Right => (Orientation::Horizontal, true),
+    /// Removes every window from this stack, and tiles them in equal parts of the area
+    /// that the stack occupied. The stack is destroyed.
+    pub fn unstack(&mut self, stack: usize, t: &mut TCellOwner<T>) {
+        let stack = ward::ward!(self.stacks.get(&stack).cloned(), else { return });
+
+        let (parent, area, workspace, windows) = {
+            let stack_ = stack.ro(t);
+            let windows = stack_.windows.clone();
+            (
+                stack_.parent.clone(),
+                stack_.area,
+                stack_.workspace,
+                windows,
+            )
         };
 
-        let area = stack.ro(t).area;
-        let workspace = fork.ro(t).workspace;
-        let windows = stack.ro(t).windows.len();
+        // The tabs hidden behind the raised tab are tiled beside it, and shown with it.
+        let shown = self.workspace_shown(workspace, t);
 
-        let branch = ward::ward!(fork.rw(t).branch(BranchRef::Stack(stack)), else {
-            tracing::error!("invalid parent fork association of stacked window");
-            return;
-        });
+        for window in &windows {
+            window.rw(t).stack = None;
+            self.event_queue.stack_detach(&stack, window, t);
 
-        match branch {
-            Either::Left(prev_branch) | Either::Right(prev_branch) => {
-                if windows == 1 {
-                    *prev_branch = Branch::Window(active.clone());
-                    self.event_queue.stack_destroy(stack);
-                } else {
-                    let stack_branch = Branch::Stack(stack.clone());
-                    let window_branch = Branch::Window(active.clone());
+            if shown && !window.ro(t).visible {
+                window.rw(t).visible = true;
+                let id = window.id(t);
+                self.event_queue.windows.entry(id).or_default().visibility = Some(true);
+            }
+        }
 
-                    let (left, right) = if stack_on_left {
-                        (stack_branch, window_branch)
-                    } else {
-                        (window_branch, stack_branch)
-                    };
+        // Each fork holds one window on its left, and the remaining windows on its right.
+        let count = windows.len() as u64;
+        let mut windows = windows.into_iter().map(Branch::Window).collect::<Vec<_>>();
+        let mut branch = ward::ward!(windows.pop(), else { return });
 
-                    let new_fork = ForkPtr::new({
-                        let mut fork = Fork::new(area, left, workspace);
-                        fork.right = Some(right);
-                        fork
-                    });
+        for (position, left) in windows.into_iter().enumerate().rev() {
+            let mut fork = Fork::new(area, left.clone(), workspace);
+            fork.right = Some(branch.clone());
 
-                    *prev_branch = Branch::Fork(new_fork.clone());
+            let (start, length) = match fork.orientation {
+                Orientation::Horizontal => (area.x, area.width),
+                Orientation::Vertical => (area.y, area.height),
+            };
 
-                    new_fork.rw(t).parent = Some(fork.clone());
-                    self.fork_register(new_fork.clone(), t);
-                    new_fork.orientation_set(self, orientation, t);
+            let edge = |position: usize| (u64::from(length) * position as u64 / count) as u32;
+            let offset = edge(position);
+
+            fork.split_handle = edge(position + 1) - offset;
+            fork.area = match fork.orientation {
+                Orientation::Horizontal => {
+                    Rect::new(start + offset, area.y, length - offset, area.height)
                 }
-            }
-        }
+                Orientation::Vertical => {
+                    Rect::new(area.x, start + offset, area.width, length - offset)
+                }
+            };
 
-        fork.work_area_refresh(self, t);
-    }
+            let fork = ForkPtr::new(fork);
+            self.fork_register(fork.clone(), t);
 
-    /// When moving horizontally, check if a window is stacked and can be moved within the stack.
-    fn move_horizontally(
-        &mut self,
-        stack_func: fn(&StackPtr<T>, &mut TCellOwner<T>) -> Option<StackMovement>,
-        else_func: fn(&mut Self, &mut TCellOwner<T>),
-        t: &mut TCellOwner<T>,
-    ) {
-        let active = ward::ward!(self.active_window(), else { return });
+            left.parent_set(&fork, t);
+            branch.parent_set(&fork, t);
+            branch = Branch::Fork(fork);
+        }
 
-        // If window is in a stack, move the tab positioning in the stack
-        if let Some(stack) = active.stack(t) {
-            if let Some(movement) = stack_func(&stack, t) {
-                self.event_queue.stack_movement(&stack, movement);
+        {
+            let parent_ = parent.rw(t);
+            let slot = ward::ward!(parent_.branch(BranchRef::Stack(&stack)), else {
+                tracing::error!("parent fork of stack did not have an association to the stack");
                 return;
-            }
+            });
+
+            let (Either::Left(slot) | Either::Right(slot)) = slot;
+            *slot = branch.clone();
         }
 
-        else_func(self, t);
+        branch.parent_set(&parent, t);
+        self.stack_destroy(&stack, t);
+        parent.work_area_refresh(self, t);
+        self.dispatch_events(t);
     }
 
-    /// Move the active window up in the tree.
-    pub fn move_left(&mut self, t: &mut TCellOwner<T>) {
-        self.move_horizontally(StackPtr::move_left, Self::move_left_absolute, t);
-    }
+    /// Swaps the tree location of this window with another.
+    ///
+    /// Windows on different workspaces, such as those of different displays, trade their
+    /// workspaces along with their locations, and each takes the visibility of the other.
+    pub fn swap(&mut self, from: &WindowPtr<T>, with: &WindowPtr<T>, t: &mut TCellOwner<T>) {
+        let active = self
+            .active_window()
+            .filter(|active| Rc::ptr_eq(active, from) || Rc::ptr_eq(active, with))
+            .map(|active| (active.clone(), active.ro(t).rect, active.ro(t).workspace));
 
-    fn move_in_direction(&mut self, direction: Direction, t: &mut TCellOwner<T>) {
-        let active = ward::ward!(self.active_window().cloned(), else { return });
-        let fork = ward::ward!(active.fork(t), else { return });
+        from.swap_position_with(self, with, t);
 
-        // If in a stack, create a fork and make the window adjacent to the stack.
-        if let Some(stack) = active.stack(t) {
-            self.move_from_stack(&active, &fork, &stack, direction, t);
-            return;
+        if from.ro(t).workspace != with.ro(t).workspace {
+            self.swap_workspace_state(from, with, t);
         }
 
-        // Fetch nearest window in direction
-        let (distance, filter): (DistanceFn, DirectionalConditionFn) = match direction {
-            Direction::Above => (Rect::distance_upward, Rect::is_below),
-            Direction::Below => (Rect::distance_downward, Rect::is_above),
-            Direction::Left => (Rect::distance_westward, Rect::is_right),
-            Direction::Right => (Rect::distance_eastward, Rect::is_left),
-        };
-
-        if let Some(window) = self.window_in_direction(distance, filter, t) {
-            let matched_fork = ward::ward!(window.fork(t), else {
-                tracing::error!("cannot move into window that is forkless");
-                return;
-            });
+        if let Some((active, rect, workspace)) = active {
+            match self.swap_focus_policy {
+                SwapFocusPolicy::FollowWindow => {
+                    if active.ro(t).workspace != workspace {
+                        // The active workspace follows the window to its new workspace.
+                        self.set_active_window(&active, t);
+                    } else if active.ro(t).rect != rect {
+                        // Focus is reported again so that its new position is highlighted.
+                        self.active_changed = true;
+                    }
+                }
 
-            // If the window being attached is in the same fork, swap positions.
-            if Rc::ptr_eq(&fork, &matched_fork) {
-                let fork_ = fork.rw(t);
-                if let Some(right) = fork_.right.as_mut() {
-                    std::mem::swap(right, &mut fork_.left);
-                    fork.work_area_refresh(self, t);
-                    return;
+                SwapFocusPolicy::StayInPlace => {
+                    let occupant = if Rc::ptr_eq(&active, from) {
+                        with
+                    } else {
+                        from
+                    };
+                    self.set_active_window(occupant, t);
                 }
             }
-
-            // Detach and create a fork in new window.
-            self.detach(&active, t);
-            self.attach_to_window_in_fork(&active, &window, &matched_fork, t);
-            self.set_active_window(&active, t);
         }
 
-        // TODO: Move across displays if not found
+        self.dispatch_events(t);
     }
 
-    /// Move the active window to the left, even if it is stacked.
-    pub fn move_left_absolute(&mut self, t: &mut TCellOwner<T>) {
-        self.move_in_direction(Direction::Left, t);
-    }
+    /// Exchanges the visibility of two windows which traded workspaces, and the focus of
+    /// each workspace which had one of them focused.
+    fn swap_workspace_state(&mut self, a: &WindowPtr<T>, b: &WindowPtr<T>, t: &mut TCellOwner<T>) {
+        let a_visible = a.ro(t).visible;
+        let b_visible = b.ro(t).visible;
+
+        for (window, visible) in [(a, b_visible), (b, a_visible)] {
+            if window.ro(t).visible != visible {
+                window.rw(t).visible = visible;
+                let id = window.id(t);
+                self.event_queue.windows.entry(id).or_default().visibility = Some(visible);
+            }
+        }
 
-    /// Move the active window to the right in the tree.
-    pub fn move_right(&mut self, t: &mut TCellOwner<T>) {
-        self.move_horizontally(StackPtr::move_right, Self::move_right_absolute, t);
+        for workspace in self.workspaces.values() {
+            let workspace = workspace.rw(t);
+
+            workspace.focus = match workspace.focus.take() {
+                Some(focus) if Rc::ptr_eq(&focus, a) => Some(b.clone()),
+                Some(focus) if Rc::ptr_eq(&focus, b) => Some(a.clone()),
+                focus => focus,
+            };
+        }
     }
 
-    /// Move the active window to the right, even if it is stacked.
-    pub fn move_right_absolute(&mut self, t: &mut TCellOwner<T>) {
-        self.move_in_direction(Direction::Right, t);
+    /// The rects that the windows with these IDs would occupy if they were swapped, without
+    /// swapping them. As each window takes the slot of the other, these are the current
+    /// rects of the other window.
+    ///
+    /// Returns `None` unless both windows are tiled.
+    pub fn swap_preview(
+        &self,
+        a: WindowID,
+        b: WindowID,
+        t: &TCellOwner<T>,
+    ) -> Option<(Rect, Rect)> {
+        let a = self.windows.get(&a).filter(|a| a.fork(t).is_some())?;
+        let b = self.windows.get(&b).filter(|b| b.fork(t).is_some())?;
+
+        Some((b.ro(t).rect, a.ro(t).rect))
     }
 
-    /// Move the active window above in the tree.
-    pub fn move_above(&mut self, t: &mut TCellOwner<T>) {
-        self.move_in_direction(Direction::Above, t)
+    /// Swaps the tree location of the windows with these IDs.
+    pub fn swap_by_id(
+        &mut self,
+        from: WindowID,
+        with: WindowID,
+        t: &mut TCellOwner<T>,
+    ) -> Result<(), WindowNotFound> {
+        let from = self.window_by_id(from)?;
+        let with = self.window_by_id(with)?;
+        self.swap(&from, &with, t);
+        Ok(())
     }
 
-    /// Move the active window below in the tree.
-    pub fn move_below(&mut self, t: &mut TCellOwner<T>) {
-        self.move_in_direction(Direction::Below, t);
+    /// Swaps the tree location of the active window with the window with this ID.
+    ///
+    /// Returns `false` if there is no active window, or no window with this ID.
+    pub fn swap_active_with_id(&mut self, other: WindowID, t: &mut TCellOwner<T>) -> bool {
+        let active = ward::ward!(self.active_window().cloned(), else { return false });
+        let other = ward::ward!(self.window_by_id(other).ok(), else { return false });
+        self.swap(&active, &other, t);
+        true
     }
 
-    /// Toggle the orientation of the active window.
-    pub fn toggle_orientation(&mut self, t: &mut TCellOwner<T>) {
-        if let Some(active) = self.active_window() {
-            if let Some(fork) = active.fork(t) {
-                fork.toggle_orientation(self, t);
-            }
+    /// Create a new pointer to a window managed by this tiler, or reuse the existing one.
+    pub fn window<I: Into<WindowID>>(&mut self, id: I) -> WindowPtr<T> {
+        let id = id.into();
+
+        if let Some(window) = self.windows.get(&id) {
+            return window.clone();
         }
-    }
 
-    /// Set a new active window, and mark that we should notify the window manager.
-    pub(crate) fn set_active_window(&mut self, window: &WindowPtr<T>, t: &mut TCellOwner<T>) {
-        self.active = Some(window.clone());
-        self.active_changed = true;
+        let window = WindowPtr(Rc::new(TCell::new(Window::new(id))));
 
-        let workspace = window.ro(t).workspace;
+        self.windows.insert(id, window.clone());
 
-        if self.active_workspace != workspace {
-            self.workspace_switch(workspace, t);
+        window
+    }
+
+    /// Create a new pointer to a window managed by this tiler, which must not reuse an ID.
+    pub fn window_new<I: Into<WindowID>>(&mut self, id: I) -> Result<WindowPtr<T>, IdInUse> {
+        let id = id.into();
+
+        if self.windows.contains_key(&id) {
+            return Err(IdInUse(id));
         }
+
+        Ok(self.window(id))
     }
 
-    /// If a window is stacked, unstack it. If it is not stacked, stack it.
-    pub fn stack_toggle(&mut self, t: &mut TCellOwner<T>) {
-        if let Some(active) = self.active_window().cloned() {
-            active.stack_toggle(self, t);
+    /// The nearest window in each direction from a window, ordered as above, below, left,
+    /// and right of it.
+    pub fn window_neighbors(&self, id: WindowID, t: &TCellOwner<T>) -> [Option<WindowID>; 4] {
+        let directions = [
+            Direction::Above,
+            Direction::Below,
+            Direction::Left,
+            Direction::Right,
+        ];
+
+        let mut neighbors = [None; 4];
+
+        let window = ward::ward!(self.windows.get(&id), else { return neighbors });
+
+        let window_ = window.ro(t);
+        let rect = window_.rect;
+
+        let mut lowest_distances = [f64::MAX; 4];
+
+        for other in self.windows.values() {
+            let other_ = other.ro(t);
+
+            if other_.workspace != window_.workspace || Rc::ptr_eq(window, other) {
+                continue;
+            }
+
+            for (position, &direction) in directions.iter().enumerate() {
+                let (distance, beyond) = scoring_beyond(direction);
+
+                if !beyond(&rect, &other_.rect) {
+                    continue;
+                }
+
+                let distance = distance(&rect, &other_.rect);
+                if distance < lowest_distances[position] {
+                    lowest_distances[position] = distance;
+                    neighbors[position] = Some(other_.id);
+                }
+            }
         }
+
+        neighbors
     }
 
-    /// Swaps the tree location of this window with another.
-    pub fn swap(&mut self, from: &WindowPtr<T>, with: &WindowPtr<T>, t: &mut TCellOwner<T>) {
-        from.swap_position_with(self, with, t);
+    /// The window on the active workspace which contains this point.
+    pub fn window_at(&self, point: Point, t: &TCellOwner<T>) -> Option<WindowID> {
+        self.window_at_on_workspace(self.active_workspace, point, t)
+            .map(|window| window.id(t))
     }
 
-    /// Create a new pointer to a window managed by this tiler.
-    pub fn window<I: Into<WindowID>>(&mut self, id: I) -> WindowPtr<T> {
-        let id = id.into();
+    /// The window on a workspace which contains this point. Of the windows in a stack,
+    /// only the active one is considered.
+    fn window_at_on_workspace(
+        &self,
+        workspace: u32,
+        point: Point,
+        t: &TCellOwner<T>,
+    ) -> Option<WindowPtr<T>> {
+        let floating = self.tiling_disabled.get(&workspace);
 
-        if let Some(window) = self.windows.get(&id) {
-            return window.clone();
+        self.windows
+            .values()
+            .find(|window| {
+                let this = window.ro(t);
+
+                if this.workspace != workspace || !this.rect.contains(point) {
+                    return false;
+                }
+
+                if let Some(stack) = this.stack.as_ref() {
+                    return Rc::ptr_eq(&stack.ro(t).active, window);
+                }
+
+                this.fork.is_some()
+                    || self.floated.iter().any(|w| Rc::ptr_eq(w, window))
+                    || floating.map_or(false, |floating| {
+                        floating.windows.iter().any(|w| Rc::ptr_eq(w, window))
+                    })
+            })
+            .cloned()
+    }
+
+    /// The window under the last known cursor position on a workspace, if focus is
+    /// placed there when switching to it.
+    fn window_under_cursor(&self, workspace: u32, t: &TCellOwner<T>) -> Option<WindowPtr<T>> {
+        match (self.workspace_focus_policy, self.cursor) {
+            (WorkspaceFocusPolicy::AtPoint, Some(cursor)) => {
+                self.window_at_on_workspace(workspace, cursor, t)
+            }
+            _ => None,
         }
+    }
 
-        let window = WindowPtr(Rc::new(TCell::new(Window::new(id))));
+    /// Whether a window with this ID is known to the tiler, tiled or not.
+    pub fn window_exists(&self, id: WindowID) -> bool {
+        self.windows.contains_key(&id)
+    }
 
-        self.windows.insert(id, window.clone());
+    /// Whether a window with this ID is known to the tiler and tiled in a fork or stack.
+    pub fn is_managed(&self, id: WindowID, t: &TCellOwner<T>) -> bool {
+        self.windows
+            .get(&id)
+            .map_or(false, |window| window.fork(t).is_some())
+    }
 
-        window
+    /// Fetch the pointer to a window managed by this tiler.
+    fn window_by_id(&self, id: WindowID) -> Result<WindowPtr<T>, WindowNotFound> {
+        self.windows.get(&id).cloned().ok_or(WindowNotFound(id))
     }
 
     /// Locates the display adjacent to the active display.
@@ -749,7 +5180,7 @@ impl<T: 'static> Tiler<T> {
         let active = ward::ward!(self.workspaces.get(&self.active_workspace), else { return None });
 
         let active = &active.ro(t).parent;
-        let active_rect = &active.ro(t).area;
+        let active_rect = &active.area(t);
 
         let mut least_distance = f64::MAX;
         let mut candidate = None;
@@ -759,7 +5190,7 @@ impl<T: 'static> Tiler<T> {
                 continue;
             }
 
-            let this_rect = &display.ro(t).area;
+            let this_rect = &display.area(t);
 
             if filter(active_rect, this_rect) {
                 continue;
@@ -775,6 +5206,70 @@ impl<T: 'static> Tiler<T> {
         candidate
     }
 
+    /// When focus wraps, locates the display furthest from the active display in the
+    /// opposite direction.
+    fn display_wrapped(&self, direction: Direction, t: &TCellOwner<T>) -> Option<DisplayPtr<T>> {
+        if !self.display_focus_wrap {
+            return None;
+        }
+
+        let active = &self.workspaces.get(&self.active_workspace)?.ro(t).parent;
+        let active_rect = active.area(t);
+
+        // Moving right wraps around to the leftmost display, and so on.
+        let position = |rect: Rect| -> i64 {
+            match direction {
+                Direction::Left => -i64::from(rect.x_end()),
+                Direction::Right => i64::from(rect.x),
+                Direction::Above => -i64::from(rect.y_end()),
+                Direction::Below => i64::from(rect.y),
+            }
+        };
+
+        self.displays
+            .values()
+            .filter(|display| position(display.area(t)) < position(active_rect))
+            .min_by_key(|display| position(display.area(t)))
+            .cloned()
+    }
+
+    /// Locates the nearest window on the active window's workspace which lies entirely
+    /// beyond an edge of the region.
+    fn window_beyond(
+        &self,
+        active: &WindowPtr<T>,
+        region: Rect,
+        direction: Direction,
+        t: &TCellOwner<T>,
+    ) -> Option<WindowPtr<T>> {
+        let (distance, beyond) = scoring_beyond(direction);
+
+        let workspace = active.ro(t).workspace;
+
+        let mut lowest_distance = f64::MAX;
+        let mut candidate = None;
+
+        for window in self.windows.values() {
+            let this = window.ro(t);
+
+            if this.workspace != workspace || Rc::ptr_eq(active, window) {
+                continue;
+            }
+
+            if !beyond(&region, &this.rect) {
+                continue;
+            }
+
+            let distance = distance(&region, &this.rect);
+            if distance < lowest_distance {
+                candidate = Some(window.clone());
+                lowest_distance = distance;
+            }
+        }
+
+        candidate
+    }
+
     /// Locates the window adjacent to the active window in the active workspace that has
     /// the lowest distance for a given distance function. Ignores windows windows in the
     /// same stack.
@@ -830,6 +5325,50 @@ impl<T: 'static> Tiler<T> {
         candidate
     }
 
+    /// When focus wraps, locates the window furthest from the active window in the opposite
+    /// direction, preferring the one most in line with it.
+    fn window_wrapped(&self, direction: Direction, t: &TCellOwner<T>) -> Option<WindowPtr<T>> {
+        if !self.focus_wrap {
+            return None;
+        }
+
+        let active = self.active_window()?;
+        let active_ = active.ro(t);
+        let stack = active_.stack.as_ref();
+        let active_rect = active_.rect;
+
+        // Moving right wraps around to the leftmost window, and so on.
+        let position = |rect: Rect| -> (i64, i64) {
+            let (x, y) = (i64::from(rect.x_center()), i64::from(rect.y_center()));
+            let (active_x, active_y) = (
+                i64::from(active_rect.x_center()),
+                i64::from(active_rect.y_center()),
+            );
+
+            match direction {
+                Direction::Left => (-i64::from(rect.x_end()), (y - active_y).abs()),
+                Direction::Right => (i64::from(rect.x), (y - active_y).abs()),
+                Direction::Above => (-i64::from(rect.y_end()), (x - active_x).abs()),
+                Direction::Below => (i64::from(rect.y), (x - active_x).abs()),
+            }
+        };
+
+        self.windows
+            .values()
+            .filter(|window| {
+                let this = window.ro(t);
+
+                this.workspace == active_.workspace
+                    && !Rc::ptr_eq(window, active)
+                    && !stack
+                        .zip(this.stack.as_ref())
+                        .map_or(false, |(active, this)| Rc::ptr_eq(active, this))
+                    && position(this.rect).0 < position(active_rect).0
+            })
+            .min_by_key(|window| position(window.ro(t).rect))
+            .cloned()
+    }
+
     /// Detaches a workspace from the tree.
     fn workspace_detach(&mut self, workspace: u32, t: &mut TCellOwner<T>) {
         let workspace = ward::ward!(self.workspaces.remove(&workspace), else {
@@ -844,46 +5383,377 @@ impl<T: 'static> Tiler<T> {
             .remove_association(workspace, t);
     }
 
-    /// Switching the workspace will hide all windows on other workspaces, show all
-    /// visible windows for the given workspace, and focus the active window on the
-    /// given workspace.
-    pub fn workspace_switch(&mut self, workspace: u32, t: &mut TCellOwner<T>) {
-        if self.active_workspace == workspace {
-            return;
+    /// How many forks the tiler is tracking, across every workspace.
+    pub fn count_forks(&self) -> usize {
+        self.forks.len()
+    }
+
+    /// How many stacks the tiler is tracking, across every workspace.
+    pub fn count_stacks(&self) -> usize {
+        self.stacks.len()
+    }
+
+    /// Checks the trees of every workspace for inconsistencies.
+    pub fn validate(&self, t: &TCellOwner<T>) -> Vec<TreeInconsistency> {
+        let mut issues = Vec::new();
+
+        for workspace in self.workspaces.values() {
+            let root = match workspace.fork(t) {
+                Some(root) => root,
+                None => continue,
+            };
+
+            if root.ro(t).parent.is_some() {
+                issues.push(TreeInconsistency::StaleParent(ForkChild::Fork(root.id(t))));
+            }
+
+            let mut forks = vec![root];
+
+            while let Some(fork) = forks.pop() {
+                let fork_ = fork.ro(t);
+
+                // Only a root fork may hold a single branch, and only if it is not a fork.
+                if fork_.right.is_none()
+                    && (fork_.parent.is_some() || matches!(fork_.left, Branch::Fork(_)))
+                {
+                    issues.push(TreeInconsistency::SingleBranchFork(fork_.id));
+                }
+
+                for branch in std::iter::once(&fork_.left).chain(fork_.right.as_ref()) {
+                    match branch {
+                        Branch::Fork(child) => {
+                            let parent = child.ro(t).parent.as_ref();
+                            if !parent.map_or(false, |parent| Rc::ptr_eq(parent, &fork)) {
+                                let child = ForkChild::Fork(child.id(t));
+                                issues.push(TreeInconsistency::StaleParent(child));
+                            }
+
+                            forks.push(child.clone());
+                        }
+
+                        Branch::Stack(stack) => {
+                            if !Rc::ptr_eq(&stack.ro(t).parent, &fork) {
+                                let child = ForkChild::Stack(stack.id(t));
+                                issues.push(TreeInconsistency::StaleParent(child));
+                            }
+
+                            for window in stack.ro(t).windows.iter() {
+                                let window_ = window.ro(t);
+                                let in_fork = window_
+                                    .fork
+                                    .as_ref()
+                                    .map_or(false, |parent| Rc::ptr_eq(parent, &fork));
+                                let in_stack = window_
+                                    .stack
+                                    .as_ref()
+                                    .map_or(false, |parent| Rc::ptr_eq(parent, stack));
+
+                                if !in_fork || !in_stack {
+                                    let child = ForkChild::Window(window_.id);
+                                    issues.push(TreeInconsistency::StaleParent(child));
+                                }
+                            }
+                        }
+
+                        Branch::Window(window) => {
+                            let window_ = window.ro(t);
+                            let in_fork = window_
+                                .fork
+                                .as_ref()
+                                .map_or(false, |parent| Rc::ptr_eq(parent, &fork));
+
+                            if !in_fork || window_.stack.is_some() {
+                                let child = ForkChild::Window(window_.id);
+                                issues.push(TreeInconsistency::StaleParent(child));
+                            }
+                        }
+                    }
+                }
+            }
         }
 
-        self.active_workspace = workspace;
-        self.active_workspace_changed = true;
+        for (id, window) in self.windows.iter() {
+            let window_ = window.ro(t);
+
+            let fork_missing = window_.fork.as_ref().map_or(false, |fork| {
+                !self
+                    .forks
+                    .get(&fork.id(t))
+                    .map_or(false, |known| Rc::ptr_eq(known, fork))
+            });
+
+            let stack_missing = window_.stack.as_ref().map_or(false, |stack| {
+                !self
+                    .stacks
+                    .get(&stack.id(t))
+                    .map_or(false, |known| Rc::ptr_eq(known, stack))
+            });
+
+            if fork_missing || stack_missing {
+                issues.push(TreeInconsistency::DanglingWindow(*id));
+            }
+        }
+
+        issues
+    }
+
+    /// Checks the trees of every workspace for inconsistencies, and repairs them.
+    ///
+    /// Parent associations are restored from the trees, forks holding a single branch
+    /// are replaced by that branch, and windows associated with a fork or stack that no
+    /// longer exists are attached to the tree of their workspace again. Returns each
+    /// inconsistency that was found, and whether it was repaired.
+    pub fn validate_and_repair(&mut self, t: &mut TCellOwner<T>) -> Vec<(TreeInconsistency, bool)> {
+        let issues = self.validate(t);
+
+        if issues.is_empty() {
+            return Vec::new();
+        }
+
+        // Parent associations are restored first, since the other repairs depend on them.
+        self.parents_repair(t);
+
+        let mut repaired = Vec::with_capacity(issues.len());
+
+        for issue in issues {
+            let fixed = match issue {
+                TreeInconsistency::StaleParent(_) => true,
+                TreeInconsistency::SingleBranchFork(id) => self.single_branch_fork_repair(id, t),
+                TreeInconsistency::DanglingWindow(id) => self.dangling_window_repair(id, t),
+            };
+
+            repaired.push((issue, fixed));
+        }
+
+        for workspace in self.workspaces.values().cloned().collect::<Vec<_>>() {
+            if let Some(root) = workspace.fork(t) {
+                root.work_area_update(self, workspace.area(t), t);
+            }
+        }
+
+        self.dispatch_events(t);
+
+        repaired
+    }
+
+    /// Points every branch in the trees of the tiler at the fork or stack which holds it.
+    fn parents_repair(&mut self, t: &mut TCellOwner<T>) {
+        for workspace in self.workspaces.values() {
+            let root = match workspace.fork(t) {
+                Some(root) => root,
+                None => continue,
+            };
+
+            root.rw(t).parent = None;
+
+            let mut forks = vec![root];
+
+            while let Some(fork) = forks.pop() {
+                let fork_ = fork.ro(t);
+                let branches: Vec<Branch<T>> = std::iter::once(fork_.left.clone())
+                    .chain(fork_.right.clone())
+                    .collect();
+
+                for branch in branches {
+                    branch.parent_set(&fork, t);
+
+                    match branch {
+                        Branch::Fork(child) => forks.push(child),
+                        Branch::Stack(stack) => {
+                            for window in stack.ro(t).windows.clone() {
+                                window.rw(t).stack = Some(stack.clone());
+                            }
+                        }
+                        Branch::Window(window) => window.rw(t).stack = None,
+                    }
+                }
+            }
+        }
+    }
+
+    /// Replaces a fork holding a single branch with that branch.
+    fn single_branch_fork_repair(&mut self, id: usize, t: &mut TCellOwner<T>) -> bool {
+        let fork = ward::ward!(self.forks.get(&id).cloned(), else { return false });
+
+        if fork.ro(t).right.is_some() {
+            return false;
+        }
+
+        let child = fork.ro(t).left.clone();
+
+        match fork.ro(t).parent.clone() {
+            Some(parent) => {
+                match parent.rw(t).branch(BranchRef::Fork(&fork)) {
+                    Some(Either::Left(branch)) | Some(Either::Right(branch)) => {
+                        *branch = child.clone()
+                    }
+                    None => return false,
+                }
+
+                child.parent_set(&parent, t);
+            }
+
+            None => {
+                let child = match child {
+                    Branch::Fork(child) => child,
+                    _ => return false,
+                };
+
+                let workspace = ward::ward!(self.workspaces.get(&fork.ro(t).workspace).cloned(), else {
+                    return false;
+                });
+
+                child.rw(t).parent = None;
+                workspace.rw(t).fork = Some(child);
+            }
+        }
+
+        fork.rw(t).parent = None;
+        self.fork_destroy(&fork, t);
+
+        true
+    }
+
+    /// Drops the associations of a window with a fork or stack that no longer exists, and
+    /// attaches it to the tree of its workspace again.
+    fn dangling_window_repair(&mut self, id: WindowID, t: &mut TCellOwner<T>) -> bool {
+        let window = ward::ward!(self.windows.get(&id).cloned(), else { return false });
+
+        window.rw(t).fork = None;
+        window.rw(t).stack = None;
+
+        let workspace = window.ro(t).workspace;
+
+        if self.tiling_disabled.contains_key(&workspace) {
+            return true;
+        }
+
+        let workspace = ward::ward!(self.workspaces.get(&workspace).cloned(), else {
+            return false;
+        });
+
+        self.attach_to_workspace(&window, &workspace, t);
+
+        true
+    }
+
+    /// Computes which windows would be hidden and shown by switching to this workspace,
+    /// without switching to it.
+    ///
+    /// Returns the windows to hide, followed by the windows to show.
+    pub fn preview_workspace_switch(
+        &self,
+        workspace: u32,
+        t: &TCellOwner<T>,
+    ) -> (Vec<WindowID>, Vec<WindowID>) {
+        let mut to_hide = Vec::new();
+        let mut to_show = Vec::new();
+
+        if self.active_workspace != workspace {
+            for (id, visible) in self.visibility_changes(workspace, t) {
+                if visible {
+                    to_show.push(id);
+                } else {
+                    to_hide.push(id);
+                }
+            }
+        }
+
+        (to_hide, to_show)
+    }
+
+    /// Whether the windows of this workspace are shown, as the active workspace of its
+    /// display.
+    fn workspace_shown(&self, workspace: u32, t: &TCellOwner<T>) -> bool {
+        let shown = workspace == self.active_workspace
+            || self.workspaces.get(&workspace).map_or(false, |workspace_| {
+                workspace_.ro(t).parent.ro(t).active == Some(workspace)
+            });
+
+        shown && !self.workspace_mirroring(workspace, t)
+    }
+
+    /// Whether this workspace is on a display which shows another display in its place.
+    fn workspace_mirroring(&self, workspace: u32, t: &TCellOwner<T>) -> bool {
+        let workspace = ward::ward!(self.workspaces.get(&workspace), else { return false });
+        let parent = &workspace.ro(t).parent;
+
+        self.display_mirrors
+            .keys()
+            .filter_map(|dst| self.displays.get(dst))
+            .any(|dst| Rc::ptr_eq(dst, parent))
+    }
+
+    /// Whether the window is hidden beneath another fullscreen window of its workspace.
+    fn fullscreen_covered(&self, window: &WindowPtr<T>, t: &TCellOwner<T>) -> bool {
+        if !self.fullscreen_hides_siblings {
+            return false;
+        }
+
+        let workspace = window.ro(t).workspace;
+
+        self.windows.values().any(|other| {
+            !Rc::ptr_eq(other, window)
+                && other.ro(t).fullscreen
+                && other.ro(t).workspace == workspace
+        })
+    }
 
-        let mut window_events = HashMap::new();
+    /// The changes in visibility of each window when switching to this workspace.
+    fn visibility_changes(&self, workspace: u32, t: &TCellOwner<T>) -> Vec<(WindowID, bool)> {
+        let mut changes = Vec::new();
 
-        std::mem::swap(&mut self.event_queue.windows, &mut window_events);
+        let display = self
+            .workspaces
+            .get(&workspace)
+            .map(|workspace| workspace.ro(t).parent.clone());
+
+        // The active workspaces of the other displays remain shown, unless their displays
+        // mirror another.
+        let shown = |id: u32| {
+            let shown = id == workspace
+                || self.workspaces.get(&id).map_or(false, |other| {
+                    let parent = &other.ro(t).parent;
+                    parent.ro(t).active == Some(id)
+                        && display
+                            .as_ref()
+                            .map_or(true, |display| !Rc::ptr_eq(display, parent))
+                });
+
+            shown && !self.workspace_mirroring(id, t)
+        };
 
         for (id, window) in self.windows.iter() {
-            let this = window.rw(t);
+            let this = window.ro(t);
 
             let is_visible = this.visible;
 
-            // If window's workspace is not the same as what is being switched to.
-            if this.workspace != workspace {
+            // If window's workspace is not shown after switching.
+            if !shown(this.workspace) {
+                if is_visible {
+                    changes.push((*id, false));
+                }
+
+                continue;
+            }
+
+            // Windows beneath a fullscreen window stay hidden.
+            if self.fullscreen_covered(window, t) {
                 if is_visible {
-                    this.visible = false;
-                    window_events.entry(*id).or_default().visibility = Some(false);
+                    changes.push((*id, false));
                 }
 
                 continue;
             }
 
             // If window on switched workspace is the active window in a stack
-            if let Some(stack) = this.stack.clone() {
+            if let Some(stack) = this.stack.as_ref() {
                 if Rc::ptr_eq(&stack.ro(t).active, window) {
                     if !is_visible {
-                        window.rw(t).visible = true;
-                        window_events.entry(*id).or_default().visibility = Some(true);
+                        changes.push((*id, true));
                     }
                 } else if is_visible {
-                    window.rw(t).visible = false;
-                    window_events.entry(*id).or_default().visibility = Some(false);
+                    changes.push((*id, false));
                 }
 
                 continue;
@@ -891,20 +5761,89 @@ impl<T: 'static> Tiler<T> {
 
             // All other windows not in a stack
             if !is_visible {
-                window.rw(t).visible = true;
-                window_events.entry(*id).or_default().visibility = Some(true);
+                changes.push((*id, true));
             }
         }
 
-        std::mem::swap(&mut self.event_queue.windows, &mut window_events);
+        changes
+    }
+
+    /// The IDs of the workspaces assigned to a display, in ascending order.
+    pub fn workspaces_on_display(&self, display: u32, t: &TCellOwner<T>) -> Vec<u32> {
+        let mut workspaces: Vec<u32> = match self.displays.get(&display) {
+            Some(display) => display.ro(t).workspaces.keys().copied().collect(),
+            None => Vec::new(),
+        };
+
+        workspaces.sort_unstable();
+        workspaces
+    }
+
+    /// The area that windows are tiled within on a workspace.
+    pub fn workspace_area(&self, workspace: u32, t: &TCellOwner<T>) -> Option<Rect> {
+        self.workspaces
+            .get(&workspace)
+            .map(|workspace| workspace.area(t))
+    }
+
+    /// The rect of each tiled window on a workspace, whether or not the workspace is shown,
+    /// for drawing a miniature of its layout. Stacked windows share the rect of their stack.
+    pub fn workspace_thumbnail_rects(
+        &self,
+        workspace: u32,
+        t: &TCellOwner<T>,
+    ) -> Vec<(WindowID, Rect)> {
+        self.windows
+            .iter()
+            .map(|(&id, window)| (id, window.ro(t)))
+            .filter(|(_, window)| window.workspace == workspace && window.fork.is_some())
+            .map(|(id, window)| (id, window.rect))
+            .collect()
+    }
+
+    /// Switching the workspace will hide all windows on other workspaces of its display,
+    /// show all visible windows for the given workspace, and focus the active window on
+    /// the given workspace. It becomes the active workspace of its display.
+    ///
+    /// The active workspaces of other displays remain shown.
+    pub fn workspace_switch(&mut self, workspace: u32, t: &mut TCellOwner<T>) {
+        self.workspace_activate(workspace, t);
+        self.dispatch_events(t);
+    }
+
+    /// Switches to the workspace as part of a larger operation, which dispatches the
+    /// events of the switch once it is complete.
+    fn workspace_activate(&mut self, workspace: u32, t: &mut TCellOwner<T>) {
+        if self.workspace_switched && self.active_workspace == workspace {
+            return;
+        }
+
+        self.active_workspace = workspace;
+        self.active_workspace_changed = true;
+        self.workspace_switched = true;
+
+        for (id, visible) in self.visibility_changes(workspace, t) {
+            if let Some(window) = self.windows.get(&id) {
+                window.rw(t).visible = visible;
+            }
+
+            self.event_queue.windows.entry(id).or_default().visibility = Some(visible);
+        }
 
-        let workspace = self
+        let workspace_ = self
             .workspaces
-            .get_mut(&workspace)
+            .get(&workspace)
             .expect("no workspace assigned")
-            .rw(t);
+            .clone();
+
+        let display = workspace_.ro(t).parent.clone();
+        display.rw(t).active = Some(workspace);
 
-        if let Some(active) = workspace.focus.clone() {
+        let focus = self
+            .window_under_cursor(workspace, t)
+            .or_else(|| workspace_.ro(t).focus.clone());
+
+        if let Some(active) = focus {
             self.set_active_window(&active, t);
         }
     }
@@ -925,6 +5864,7 @@ impl<T: 'static> Tiler<T> {
                     .insert(workspace, display_.create_workspace(workspace, t));
             }
         }
+        self.dispatch_events(t);
     }
 
     pub fn debug<'a>(&'a self, t: &'a TCellOwner<T>) -> TilerDisplay<'a, T> {
@@ -932,6 +5872,194 @@ impl<T: 'static> Tiler<T> {
     }
 }
 
+/// Measures the distance to rects in a direction, and tests whether a rect lies behind
+/// in that direction, to be passed over.
+fn direction_fns(direction: Direction) -> (DistanceFn, DirectionalConditionFn) {
+    match direction {
+        Direction::Above => (Rect::distance_upward, Rect::is_below),
+        Direction::Below => (Rect::distance_downward, Rect::is_above),
+        Direction::Left => (Rect::distance_westward, Rect::is_right),
+        Direction::Right => (Rect::distance_eastward, Rect::is_left),
+    }
+}
+
+/// Measures the distance to windows lying entirely beyond a region in a direction, and
+/// tests whether a window lies there.
+fn scoring_beyond(direction: Direction) -> (DistanceFn, DirectionalConditionFn) {
+    match direction {
+        Direction::Above => (Rect::distance_upward, |region, rect| {
+            rect.y_end() <= region.y
+        }),
+        Direction::Below => (Rect::distance_downward, |region, rect| {
+            rect.y >= region.y_end()
+        }),
+        Direction::Left => (Rect::distance_westward, |region, rect| {
+            rect.x_end() <= region.x
+        }),
+        Direction::Right => (Rect::distance_eastward, |region, rect| {
+            rect.x >= region.x_end()
+        }),
+    }
+}
+
+/// Moves the area of a placement so that part of it is visible on its workspace's display.
+fn keep_visible(event: Event, displays: &BTreeMap<u32, Rect>, min: u32) -> Event {
+    let clamp = |mut placement: Placement| {
+        if let Some(&display) = displays.get(&placement.workspace) {
+            placement.area = placement.area.keep_visible(display, min);
+        }
+
+        placement
+    };
+
+    match event {
+        Event::StackPlace(id, placement, tab_bar) => {
+            Event::StackPlace(id, clamp(placement), tab_bar)
+        }
+        Event::WindowPlace(id, placement) => Event::WindowPlace(id, clamp(placement)),
+        event => event,
+    }
+}
+
+/// Scales a rect on the `src` display to where it is shown on the `dst` display.
+fn mirror_rect(rect: Rect, src: Rect, dst: Rect) -> Rect {
+    let scale = |value: u32, from: u32, to: u32| {
+        (u64::from(value) * u64::from(to) / u64::from(from.max(1))) as u32
+    };
+
+    Rect::new(
+        dst.x + scale(rect.x.saturating_sub(src.x), src.width, dst.width),
+        dst.y + scale(rect.y.saturating_sub(src.y), src.height, dst.height),
+        scale(rect.width, src.width, dst.width),
+        scale(rect.height, src.height, dst.height),
+    )
+}
+
+/// Aligns the area of a placement to the pixel grid of its workspace's display.
+fn align_placement(event: Event, rounding: &BTreeMap<u32, (u32, RoundingMode)>) -> Event {
+    let align = |mut placement: Placement| {
+        if let Some(&(grid, mode)) = rounding.get(&placement.workspace) {
+            placement.area = placement.area.align_to_grid(grid, mode);
+            placement.from = placement.from.map(|from| from.align_to_grid(grid, mode));
+        }
+
+        placement
+    };
+
+    match event {
+        Event::StackPlace(id, placement, tab_bar) => {
+            Event::StackPlace(id, align(placement), tab_bar)
+        }
+        Event::WindowPlace(id, placement) => Event::WindowPlace(id, align(placement)),
+        event => event,
+    }
+}
+
+/// The area that a branch of a fork would take if it were replaced, where `gap` gives the
+/// gap between the replacement and the other branch.
+fn branch_area<T: 'static>(
+    fork: &ForkPtr<T>,
+    branch: &Branch<T>,
+    gap: impl Fn(&Branch<T>) -> u32,
+    t: &TCellOwner<T>,
+) -> Rect {
+    let fork_ = fork.ro(t);
+
+    let right = match fork_.right.as_ref() {
+        Some(right) => right,
+        None => return fork_.area,
+    };
+
+    let (other, left) = if fork_.left == *branch {
+        (right, true)
+    } else {
+        (&fork_.left, false)
+    };
+
+    let (left_rect, right_rect) = split_rect(
+        fork_.area,
+        fork_.orientation,
+        fork_.split_handle,
+        gap(other),
+    );
+
+    if left {
+        left_rect
+    } else {
+        right_rect
+    }
+}
+
+/// Leaves out the rect that a placement moved from, unless previous rects are emitted or
+/// the placement is a gap transition.
+fn previous_rect(event: Event, emit: bool) -> Event {
+    let strip = |mut placement: Placement| {
+        if !emit && !placement.gap_transition {
+            placement.from = None;
+        }
+
+        placement
+    };
+
+    match event {
+        Event::StackPlace(id, placement, tab_bar) => {
+            Event::StackPlace(id, strip(placement), tab_bar)
+        }
+        Event::WindowPlace(id, placement) => Event::WindowPlace(id, strip(placement)),
+        event => event,
+    }
+}
+
+/// The split forks of this orientation beneath and including this fork, which are not
+/// separated by a fork of another orientation, in the order of their splits.
+fn row_forks<T>(
+    fork: &ForkPtr<T>,
+    orientation: Orientation,
+    out: &mut Vec<ForkPtr<T>>,
+    t: &TCellOwner<T>,
+) {
+    let fork_ = fork.ro(t);
+
+    if fork_.orientation != orientation {
+        return;
+    }
+
+    if let Branch::Fork(left) = &fork_.left {
+        row_forks(left, orientation, out, t);
+    }
+
+    if let Some(right) = fork_.right.as_ref() {
+        out.push(fork.clone());
+
+        if let Branch::Fork(right) = right {
+            row_forks(right, orientation, out, t);
+        }
+    }
+}
+
+/// The branches in a row of nested forks split in this orientation, in order, along with
+/// the fork holding each of them.
+fn row_slots<T>(
+    fork: &ForkPtr<T>,
+    orientation: Orientation,
+    t: &TCellOwner<T>,
+) -> Vec<(ForkPtr<T>, Branch<T>)> {
+    let fork_ = fork.ro(t);
+    let mut slots = Vec::new();
+
+    for branch in std::iter::once(&fork_.left).chain(fork_.right.as_ref()) {
+        match branch {
+            Branch::Fork(child) if child.ro(t).orientation == orientation => {
+                slots.extend(row_slots(child, orientation, t));
+            }
+
+            branch => slots.push((fork.clone(), branch.clone())),
+        }
+    }
+
+    slots
+}
+
 pub struct TilerDisplay<'a, T: 'static> {
     pub tiler: &'a Tiler<T>,
     pub t: &'a TCellOwner<T>,
@@ -5,7 +5,7 @@ use crate::branch::{Branch, BranchRef};
 use crate::fork::ForkPtr;
 use crate::stack::StackPtr;
 use crate::tiler::Tiler;
-use crate::{Placement, Rect};
+use crate::{EdgeSet, Event, EventCompaction, Insets, Orientation, Placement, Rect};
 use either::Either;
 use qcell::{TCell, TCellOwner};
 use std::fmt::{self, Debug};
@@ -16,6 +16,66 @@ use std::rc::Rc;
 #[derive(Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord, From, Into)]
 pub struct WindowID(pub u32, pub u32);
 
+/// Whether the window manager should draw a border around a window.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Decoration {
+    Border,
+    NoBorder,
+}
+
+impl Default for Decoration {
+    fn default() -> Self {
+        Decoration::Border
+    }
+}
+
+/// Which side of the window it is attached beside that a window is attached on.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Gravity {
+    /// Whichever side the tiler would choose.
+    Center,
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl Default for Gravity {
+    fn default() -> Self {
+        Gravity::Center
+    }
+}
+
+impl Gravity {
+    /// The orientation of the split that a window with this gravity is attached at, and
+    /// whether the window takes the left branch of it.
+    pub(crate) fn split_side(self) -> Option<(Orientation, bool)> {
+        match self {
+            Gravity::Center => None,
+            Gravity::Left => Some((Orientation::Horizontal, true)),
+            Gravity::Right => Some((Orientation::Horizontal, false)),
+            Gravity::Up => Some((Orientation::Vertical, true)),
+            Gravity::Down => Some((Orientation::Vertical, false)),
+        }
+    }
+}
+
+/// A window ID was given which is not managed by the tiler.
+#[derive(Copy, Clone, Debug, Display, PartialEq, Eq)]
+#[display(fmt = "{:?} is not managed by the tiler", _0)]
+pub struct WindowNotFound(pub WindowID);
+
+impl std::error::Error for WindowNotFound {}
+
+/// A window ID was given which already belongs to a window managed by the tiler.
+#[derive(Copy, Clone, Debug, Display, PartialEq, Eq)]
+#[display(fmt = "{:?} is already in use by another window", _0)]
+pub struct IdInUse(pub WindowID);
+
+impl std::error::Error for IdInUse {}
+
 /// Pointer to reference-counted window managed by a `TCell`.
 #[derive(Deref, DerefMut)]
 pub struct WindowPtr<T: 'static>(pub(crate) Rc<TCell<T, Window<T>>>);
@@ -46,6 +106,10 @@ impl<T: 'static> WindowPtr<T> {
             }
 
             for (id, show) in visibility {
+                if let Some(window) = tiler.windows.get(&id) {
+                    window.rw(t).visible = show;
+                }
+
                 tiler.event_queue.windows.entry(id).or_default().visibility = Some(show);
             }
         }
@@ -94,7 +158,7 @@ impl<T: 'static> WindowPtr<T> {
 
                 let (Either::Left(branch) | Either::Right(branch)) = branch;
                 *branch = Branch::Window(self.clone());
-                tiler.event_queue.stack_destroy(&stack);
+                tiler.stack_destroy(&stack, t);
             }
 
             return;
@@ -106,6 +170,7 @@ impl<T: 'static> WindowPtr<T> {
         });
 
         let stack = StackPtr::new(self, fork.clone(), t);
+        tiler.stack_register(stack.clone(), t);
 
         let branch = ward::ward!(fork.rw(t).branch(BranchRef::Window(self)), else {
             tracing::error!("cannot stack because window has invalid parent fork");
@@ -116,10 +181,57 @@ impl<T: 'static> WindowPtr<T> {
         let (Either::Left(branch) | Either::Right(branch)) = branch;
         *branch = Branch::Stack(stack.clone());
 
-        tiler.event_queue.stack_update(&stack, t);
+        // The gap beside the branch may differ now that it is a stack.
+        fork.work_area_refresh(tiler, t);
         tiler.event_queue.stack_assign(&stack, self, t);
     }
 
+    /// Locates the position of this window within its stack or fork.
+    fn slot(&self, t: &TCellOwner<T>) -> Slot<T> {
+        if let Some(stack) = self.stack(t) {
+            let stack_ = stack.ro(t);
+            if let Some(pos) = stack_.windows.iter().position(|w| Rc::ptr_eq(w, self)) {
+                return Slot::Stack(stack.clone(), pos);
+            }
+        } else if let Some(fork) = self.fork(t) {
+            let fork_ = fork.ro(t);
+            if fork_.left_is(BranchRef::Window(self)) {
+                return Slot::Fork(fork.clone(), true);
+            } else if fork_.right_is(BranchRef::Window(self)) {
+                return Slot::Fork(fork.clone(), false);
+            }
+        }
+
+        Slot::Detached
+    }
+
+    /// Places this window into a slot that was occupied by the `previous` window.
+    fn slot_assign(&self, slot: &Slot<T>, previous: &WindowPtr<T>, t: &mut TCellOwner<T>) {
+        match slot {
+            Slot::Stack(stack, pos) => {
+                let stack_ = stack.rw(t);
+                stack_.windows[*pos] = self.clone();
+
+                if Rc::ptr_eq(&stack_.active, previous) {
+                    stack_.active = self.clone();
+                }
+            }
+
+            Slot::Fork(fork, left) => {
+                let fork_ = fork.rw(t);
+                let branch = Branch::Window(self.clone());
+
+                if *left {
+                    fork_.left = branch;
+                } else {
+                    fork_.right = Some(branch);
+                }
+            }
+
+            Slot::Detached => (),
+        }
+    }
+
     /// Swaps the tree location of this window with another.
     pub(crate) fn swap_position_with(
         &self,
@@ -127,26 +239,110 @@ impl<T: 'static> WindowPtr<T> {
         other: &WindowPtr<T>,
         t: &mut TCellOwner<T>,
     ) {
-        if let Some(stack) = self.stack(t) {
-            stack.swap(self, other, t);
-            stack.work_area_refresh(tiler, t);
-        } else if let Some(fork) = self.fork(t) {
-            fork.swap(self, other, t);
-            fork.work_area_refresh(tiler, t);
+        if Rc::ptr_eq(self, other) {
+            return;
         }
 
-        if let Some(stack) = other.stack(t) {
-            stack.swap(other, self, t);
-            stack.work_area_refresh(tiler, t)
-        } else if let Some(fork) = other.fork(t) {
-            fork.swap(other, self, t);
-            fork.work_area_refresh(tiler, t);
+        // Both positions are located before either is modified, in case they share a parent.
+        let our_slot = self.slot(t);
+        let their_slot = other.slot(t);
+
+        other.slot_assign(&our_slot, self, t);
+        self.slot_assign(&their_slot, other, t);
+
+        // Exchange the parent associations of each window.
+        let ours = {
+            let this = self.rw(t);
+            (this.fork.take(), this.stack.take(), this.workspace)
+        };
+
+        let theirs = {
+            let this = other.rw(t);
+            (this.fork.take(), this.stack.take(), this.workspace)
+        };
+
+        {
+            let this = self.rw(t);
+            this.fork = theirs.0;
+            this.stack = theirs.1;
+            this.workspace = theirs.2;
+        }
+
+        {
+            let this = other.rw(t);
+            this.fork = ours.0;
+            this.stack = ours.1;
+            this.workspace = ours.2;
+        }
+
+        let same_stack = match (&our_slot, &their_slot) {
+            (Slot::Stack(a, _), Slot::Stack(b, _)) => Rc::ptr_eq(a, b),
+            _ => false,
+        };
+
+        for (slot, leaving, entering) in [(&our_slot, self, other), (&their_slot, other, self)] {
+            match slot {
+                Slot::Stack(stack, _) => {
+                    if !same_stack {
+                        tiler.event_queue.stack_detach(stack, leaving, t);
+                        tiler.event_queue.stack_assign(stack, entering, t);
+                    }
+
+                    if Rc::ptr_eq(&stack.ro(t).active, entering) {
+                        tiler.event_queue.stack_raise_window(stack, entering, t);
+                    }
+
+                    stack.work_area_refresh(tiler, t);
+                }
+
+                Slot::Fork(fork, _) => fork.work_area_refresh(tiler, t),
+
+                Slot::Detached => (),
+            }
         }
     }
 
     /// Update the position and dimensions of this window.
     pub(crate) fn work_area_update(&self, tiler: &mut Tiler<T>, area: Rect, t: &mut TCellOwner<T>) {
+        let area = if self.ro(t).fullscreen {
+            let workspace = tiler.workspaces.get(&self.ro(t).workspace);
+            workspace.map_or(area, |workspace| workspace.ro(t).parent.area(t))
+        } else {
+            area
+        };
+
         let this = self.rw(t);
+
+        // The window draws within its padding, without changing the area given to others.
+        let area = area.inset(this.padding);
+
+        // The window is sized down to a whole number of increments, and centered in its area.
+        let area = match this.resize_increment {
+            Some((width, height)) => {
+                let snapped = |length: u32, increment: u32| {
+                    if increment == 0 {
+                        length
+                    } else {
+                        length - length % increment
+                    }
+                };
+
+                let w = snapped(area.width, width);
+                let h = snapped(area.height, height);
+
+                Rect::new(
+                    area.x + (area.width - w) / 2,
+                    area.y + (area.height - h) / 2,
+                    w,
+                    h,
+                )
+            }
+
+            None => area,
+        };
+
+        let previous = this.rect;
+
         if this.rect != area {
             this.rect = area;
         }
@@ -154,26 +350,92 @@ impl<T: 'static> WindowPtr<T> {
         let id = this.id;
         let workspace = this.workspace;
 
-        tiler.event_queue.windows.entry(id).or_default().place = Some(Placement { area, workspace })
+        let events = tiler.event_queue.windows.entry(id).or_default();
+
+        if tiler.event_queue.compaction == EventCompaction::None {
+            if let Some(placement) = events.place {
+                tiler
+                    .event_queue
+                    .superseded
+                    .push(Event::WindowPlace(id, placement));
+            }
+        }
+
+        events.place = Some(Placement::superseding(
+            events.place.as_ref(),
+            area,
+            workspace,
+            previous,
+            tiler.gap_transition,
+        ))
     }
 }
 
+/// The position of a window in the tree.
+enum Slot<T: 'static> {
+    /// Position of the window in a stack.
+    Stack(StackPtr<T>, usize),
+    /// Whether the window is the left or right branch of a fork.
+    Fork(ForkPtr<T>, bool),
+    /// The window is not attached to the tree.
+    Detached,
+}
+
 pub struct Window<T: 'static> {
+    /// The edges of the border last reported to the window manager, if any were.
+    pub(crate) borders: Option<EdgeSet>,
+    /// The decoration last reported to the window manager.
+    pub(crate) decoration: Decoration,
+    /// A decoration set explicitly, which takes precedence over the automatic one.
+    pub(crate) decoration_override: Option<Decoration>,
     pub(crate) fork: Option<ForkPtr<T>>,
+    /// Whether the window covers the display of its workspace, rather than its branch.
+    pub(crate) fullscreen: bool,
+    /// Which side of the window it is attached beside that the window is attached on.
+    pub(crate) gravity: Gravity,
     pub(crate) id: WindowID,
     pub(crate) rect: Rect,
+    /// The width and height that the window is sized in multiples of.
+    pub(crate) resize_increment: Option<(u32, u32)>,
+    /// The least width and height that the window asks for.
+    pub(crate) min_size: Option<(u32, u32)>,
+    /// Insets of the window's placement from the area that it is given.
+    pub(crate) padding: Insets,
     pub(crate) stack: Option<StackPtr<T>>,
+    /// Whether the window is demanding attention.
+    pub(crate) urgent: bool,
     pub(crate) workspace: u32,
     pub(crate) visible: bool,
 }
 
 impl<T: 'static> Window<T> {
+    /// The area that the window was given, before it was inset by its padding.
+    pub(crate) fn outer_rect(&self) -> Rect {
+        let padding = self.padding;
+
+        Rect::new(
+            self.rect.x.saturating_sub(padding.left),
+            self.rect.y.saturating_sub(padding.top),
+            self.rect.width + padding.left + padding.right,
+            self.rect.height + padding.top + padding.bottom,
+        )
+    }
+
     pub(crate) fn new<I: Into<WindowID>>(id: I) -> Self {
         Self {
+            borders: None,
+            decoration: Decoration::default(),
+            decoration_override: None,
             fork: None::<ForkPtr<T>>,
+            fullscreen: false,
+            gravity: Gravity::default(),
             id: id.into(),
             rect: Rect::new(1, 1, 1, 1),
+            resize_increment: None,
+            padding: Insets::default(),
+            min_size: None,
             stack: None,
+            urgent: false,
             workspace: 0,
             visible: true,
         }
@@ -192,12 +454,12 @@ impl<T: 'static> Drop for Window<T> {
 
 pub struct WindowDebug<'a, T: 'static> {
     window: &'a Window<T>,
-    _t: &'a TCellOwner<T>,
+    t: &'a TCellOwner<T>,
 }
 
 impl<'a, T> WindowDebug<'a, T> {
     fn new(window: &'a Window<T>, t: &'a TCellOwner<T>) -> Self {
-        Self { window, _t: t }
+        Self { window, t }
     }
 }
 
@@ -205,8 +467,8 @@ impl<'a, T> Debug for WindowDebug<'a, T> {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         fmt.debug_struct("Window")
             .field("id", &self.window.id)
-            .field("fork", &self.window.fork.as_ref().map(|p| Rc::as_ptr(p)))
-            .field("stack", &self.window.stack.as_ref().map(|p| Rc::as_ptr(p)))
+            .field("fork", &self.window.fork.as_ref().map(|p| p.id(self.t)))
+            .field("stack", &self.window.stack.as_ref().map(|p| p.id(self.t)))
             .field("workspace", &self.window.workspace)
             .field("rect", &self.window.rect)
             .finish()
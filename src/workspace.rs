@@ -4,6 +4,7 @@
 use crate::display::DisplayPtr;
 use crate::fork::ForkPtr;
 use crate::geom::Rect;
+use crate::tiler::GapConfig;
 use crate::window::WindowPtr;
 use qcell::{TCell, TCellOwner};
 use std::fmt::{self, Debug};
@@ -24,12 +25,23 @@ impl<T: 'static> WorkspacePtr<T> {
             id,
             focus: None,
             fork: None,
+            gaps: GapConfig::default(),
             parent,
         })))
     }
 
+    /// The area of the display, less the outer gap.
     pub fn area(&self, t: &TCellOwner<T>) -> Rect {
-        self.ro(t).parent.area(t)
+        let this = self.ro(t);
+        let area = this.parent.area(t);
+        let outer = this.gaps.outer.min(area.width / 2).min(area.height / 2);
+
+        Rect::new(
+            area.x + outer,
+            area.y + outer,
+            area.width - outer * 2,
+            area.height - outer * 2,
+        )
     }
 
     pub fn fork(&self, t: &TCellOwner<T>) -> Option<ForkPtr<T>> {
@@ -46,6 +58,8 @@ pub(crate) struct Workspace<T: 'static> {
     pub id: u32,
     pub focus: Option<WindowPtr<T>>,
     pub fork: Option<ForkPtr<T>>,
+    /// The gaps around and between tiles, as of the last refresh.
+    pub gaps: GapConfig,
     pub parent: DisplayPtr<T>,
 }
 
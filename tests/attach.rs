@@ -0,0 +1,139 @@
+// Copyright 2021 System76 <info@system76.com>
+// SPDX-License-Identifier: MPL-2.0
+
+mod common;
+
+use pop_tiler::*;
+
+#[test]
+fn attach_beside_anchor() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    let windows = common::attach(&mut tiler, &[0, 1, 2], t);
+    let before = common::rects(&mut tiler, t);
+    let anchor = windows[0].id(t);
+
+    // Attaches are made beside the anchor rather than the focused window.
+    tiler.set_attach_anchor(Some(&windows[0]), t);
+    tiler.focus(&windows[2], t);
+    let new = tiler.window((0, 3));
+    tiler.attach(&new, t);
+
+    let after = common::rects(&mut tiler, t);
+    let [anchor_before, anchor_after, new] = [before[&anchor], after[&anchor], after[&new.id(t)]];
+    assert!(anchor_after.area() < anchor_before.area());
+    assert_eq!(anchor_before.area(), anchor_after.area() + new.area());
+    assert!(new.x >= anchor_before.x && new.x_end() <= anchor_before.x_end());
+    assert!(new.y >= anchor_before.y && new.y_end() <= anchor_before.y_end());
+
+    // Without the anchor, attaches are made beside the focused window again.
+    tiler.set_attach_anchor(None, t);
+    let focused = windows[2].id(t);
+    let focused_before = after[&focused];
+    let new = tiler.window((0, 4));
+    tiler.attach(&new, t);
+
+    let last = common::rects(&mut tiler, t);
+    assert_eq!(last[&anchor], anchor_after);
+    assert!(last[&focused].area() < focused_before.area());
+}
+
+#[test]
+fn attach_at_point_splits_at_cursor() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    // Dropped 30% from the left of a window alone in its fork.
+    let windows = common::attach(&mut tiler, &[0], t);
+    let dropped = tiler.window((0, 1));
+    tiler.attach_at_point(&dropped, &windows[0], Point::new(300, 400), t);
+
+    let rects = common::rects(&mut tiler, t);
+    assert_eq!(rects[&WindowID(0, 1)], Rect::new(0, 0, 300, 800));
+    assert_eq!(rects[&WindowID(0, 0)], Rect::new(300, 0, 700, 800));
+
+    // Dropped 70% from the left of a window beside another.
+    let dropped = tiler.window((0, 2));
+    tiler.attach_at_point(&dropped, &windows[0], Point::new(790, 400), t);
+
+    let rects = common::rects(&mut tiler, t);
+    assert_eq!(rects[&WindowID(0, 0)], Rect::new(300, 0, 490, 800));
+    assert_eq!(rects[&WindowID(0, 2)], Rect::new(790, 0, 210, 800));
+}
+
+#[test]
+fn down_gravity_attaches_below() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    common::attach(&mut tiler, &[0], t);
+
+    let window = tiler.window((0, 1));
+    tiler.set_gravity(&window, Gravity::Down, t);
+    tiler.attach(&window, t);
+
+    let forks = common::forks(&tiler, t);
+    assert_eq!(
+        forks
+            .values()
+            .map(|fork| fork.orientation)
+            .collect::<Vec<_>>(),
+        [Orientation::Vertical]
+    );
+
+    let rects = common::rects(&mut tiler, t);
+    assert_eq!(rects[&WindowID(0, 0)], Rect::new(0, 0, 1000, 400));
+    assert_eq!(rects[&WindowID(0, 1)], Rect::new(0, 400, 1000, 400));
+}
+
+#[test]
+fn attach_feedback_matches_attach() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    common::attach(&mut tiler, &[0, 1, 2], t);
+    let _ = common::events(&mut tiler, t);
+
+    let feedback = tiler.begin_attach_feedback(WindowID(0, 3), t);
+    assert!(common::events(&mut tiler, t).is_empty());
+
+    common::attach(&mut tiler, &[3], t);
+    let placed = common::placements(&common::events(&mut tiler, t));
+
+    match feedback {
+        Some(Event::InsertPreview(rect)) => assert_eq!(Some(&rect), placed.get(&WindowID(0, 3))),
+        feedback => panic!("expected an insert preview: {:?}", feedback),
+    }
+}
+
+#[test]
+fn attach_rule_floats_one_window() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    let float = Rect::new(100, 100, 300, 200);
+    tiler.set_attach_rule(Box::new(move |id| {
+        if id == WindowID(0, 1) {
+            AttachDecision::Float(float)
+        } else {
+            AttachDecision::Tile
+        }
+    }));
+
+    common::attach(&mut tiler, &[0, 1, 2], t);
+    let rects = common::rects(&mut tiler, t);
+
+    assert_eq!(rects[&WindowID(0, 1)], float);
+    assert!(!tiler.is_managed(WindowID(0, 1), t));
+
+    assert!(tiler.is_managed(WindowID(0, 0), t));
+    assert!(tiler.is_managed(WindowID(0, 2), t));
+    assert_eq!(rects[&WindowID(0, 0)], Rect::new(0, 0, 500, 800));
+    assert_eq!(rects[&WindowID(0, 2)], Rect::new(500, 0, 500, 800));
+}
@@ -0,0 +1,112 @@
+// Copyright 2021 System76 <info@system76.com>
+// SPDX-License-Identifier: MPL-2.0
+
+#![allow(dead_code)]
+
+use pop_tiler::*;
+use std::collections::BTreeMap;
+
+/// A tiler with a 1000x800 display showing workspace 0.
+pub fn tiler<T: 'static>(t: &mut TCellOwner<T>) -> Tiler<T> {
+    let mut tiler = Tiler::default();
+    tiler.display_update(0, Rect::new(0, 0, 1000, 800), t);
+    tiler.workspace_update(0, 0, t);
+    tiler.workspace_switch(0, t);
+    tiler
+}
+
+/// Attaches and focuses a window for each ID, in order.
+pub fn attach<T: 'static>(
+    tiler: &mut Tiler<T>,
+    ids: &[u32],
+    t: &mut TCellOwner<T>,
+) -> Vec<WindowPtr<T>> {
+    ids.iter()
+        .map(|&id| {
+            let window = tiler.window((0, id));
+            tiler.attach(&window, t);
+            tiler.focus(&window, t);
+            window
+        })
+        .collect()
+}
+
+/// The events of the tiler since they were last retrieved.
+pub fn events<T: 'static>(tiler: &mut Tiler<T>, t: &mut TCellOwner<T>) -> Vec<Event> {
+    tiler.events(t).collect()
+}
+
+/// The last placement of each window among these events.
+pub fn placements(events: &[Event]) -> BTreeMap<WindowID, Rect> {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            Event::WindowPlace(id, placement) => Some((*id, placement.area)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Where each window of the tiler is placed, from the events it reports.
+pub fn rects<T: 'static>(tiler: &mut Tiler<T>, t: &mut TCellOwner<T>) -> BTreeMap<WindowID, Rect> {
+    let _ = events(tiler, t);
+    placements(&tiler.emit_initial_state(t))
+}
+
+/// The current state of each fork of the tiler.
+pub fn forks<T: 'static>(tiler: &Tiler<T>, t: &TCellOwner<T>) -> BTreeMap<usize, ForkUpdate> {
+    tiler
+        .emit_initial_state(t)
+        .into_iter()
+        .filter_map(|event| match event {
+            Event::Fork(id, update) => Some((id, update)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// A fork of a layout, split at `split` pixels along its orientation.
+pub fn fork(
+    area: Rect,
+    orientation: Orientation,
+    split: u32,
+    left: Layout,
+    right: Layout,
+) -> Layout {
+    Layout::Fork(Box::new(ForkLayout {
+        area,
+        orientation,
+        split_handle: split,
+        left,
+        right: Some(right),
+    }))
+}
+
+/// A window of a layout.
+pub fn window(id: u32) -> Layout {
+    Layout::Window(WindowID(0, id))
+}
+
+/// Arranges new windows on workspace 0 in the shape of this layout.
+pub fn arrange<T: 'static>(tiler: &mut Tiler<T>, layout: Layout, t: &mut TCellOwner<T>) {
+    let mut layouts = vec![&layout];
+
+    while let Some(next) = layouts.pop() {
+        match next {
+            Layout::Window(id) => {
+                tiler.window(*id);
+            }
+            Layout::Stack { windows, .. } => {
+                for &id in windows {
+                    tiler.window(id);
+                }
+            }
+            Layout::Fork(fork) => {
+                layouts.push(&fork.left);
+                layouts.extend(fork.right.as_ref());
+            }
+        }
+    }
+
+    tiler.restore_workspace(0, WorkspaceSnapshot { root: layout }, t);
+}
@@ -0,0 +1,384 @@
+// Copyright 2021 System76 <info@system76.com>
+// SPDX-License-Identifier: MPL-2.0
+
+mod common;
+
+use pop_tiler::*;
+use std::collections::BTreeMap;
+
+/// A tiler with this many 1000x800 displays in a row, each showing a workspace of the same
+/// ID with one window of that ID. The rightmost display is focused.
+fn side_by_side<T: 'static>(count: u32, t: &mut TCellOwner<T>) -> Tiler<T> {
+    let mut tiler = Tiler::default();
+
+    for display in 0..count {
+        tiler.display_update(display, Rect::new(display * 1000, 0, 1000, 800), t);
+        tiler.workspace_update(display, display, t);
+        tiler.workspace_switch(display, t);
+        common::attach(&mut tiler, &[display], t);
+    }
+
+    let _ = common::events(&mut tiler, t);
+    tiler
+}
+
+/// The workspace and window focused by these events.
+fn focused(events: &[Event]) -> (Option<u32>, Option<WindowID>) {
+    let workspace = events.iter().find_map(|event| match event {
+        Event::FocusWorkspace(workspace) => Some(*workspace),
+        _ => None,
+    });
+
+    let window = events.iter().find_map(|event| match event {
+        Event::Focus(window, _) => Some(*window),
+        _ => None,
+    });
+
+    (workspace, window)
+}
+
+#[test]
+fn display_focus_wraps_to_leftmost() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = side_by_side(3, t);
+
+    tiler.focus_display_right(t);
+    assert_eq!(focused(&common::events(&mut tiler, t)).1, None);
+
+    tiler.set_display_focus_wrap(true);
+    tiler.focus_display_right(t);
+    assert_eq!(
+        focused(&common::events(&mut tiler, t)),
+        (Some(0), Some(WindowID(0, 0)))
+    );
+}
+
+#[test]
+fn display_focus_without_workspace_switch() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = side_by_side(2, t);
+
+    tiler.set_focus_follows_workspace(false);
+    tiler.focus_display_left(t);
+    assert_eq!(
+        focused(&common::events(&mut tiler, t)),
+        (None, Some(WindowID(0, 0)))
+    );
+
+    // The workspace of the display is switched to when focus follows workspaces again.
+    tiler.set_focus_follows_workspace(true);
+    tiler.focus_display_left(t);
+    assert_eq!(
+        focused(&common::events(&mut tiler, t)),
+        (Some(0), Some(WindowID(0, 0)))
+    );
+}
+
+#[test]
+fn workspace_area_within_outer_gaps() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    tiler.set_gap_config(
+        GapConfig {
+            outer: 20,
+            ..GapConfig::default()
+        },
+        t,
+    );
+
+    assert_eq!(tiler.display_area(0, t), Some(Rect::new(0, 0, 1000, 800)));
+    assert_eq!(
+        tiler.workspace_area(0, t),
+        Some(Rect::new(20, 20, 960, 760))
+    );
+    assert_eq!(tiler.display_area(1, t), None);
+    assert_eq!(tiler.workspace_area(1, t), None);
+}
+
+#[test]
+fn attach_falls_back_to_primary_display() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = Tiler::default();
+
+    for display in 0..2 {
+        tiler.display_update(display, Rect::new(display * 1000, 0, 1000, 800), t);
+        tiler.workspace_update(display, display, t);
+    }
+
+    tiler.set_primary_display(1);
+    let window = tiler.window((0, 0));
+    tiler.attach(&window, t);
+
+    let placed = common::events(&mut tiler, t)
+        .into_iter()
+        .find_map(|event| match event {
+            Event::WindowPlace(WindowID(0, 0), placement) => Some(placement),
+            _ => None,
+        })
+        .map(|placement| (placement.workspace, placement.area));
+
+    assert_eq!(placed, Some((1, Rect::new(1000, 0, 1000, 800))));
+    assert!(tiler.is_managed(WindowID(0, 0), t));
+}
+
+#[test]
+fn workspaces_on_each_display() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = Tiler::default();
+
+    tiler.display_update(0, Rect::new(0, 0, 1000, 800), t);
+    tiler.display_update(1, Rect::new(1000, 0, 1000, 800), t);
+    tiler.workspace_update(2, 0, t);
+    tiler.workspace_update(1, 1, t);
+    tiler.workspace_update(0, 0, t);
+
+    assert_eq!(tiler.workspaces_on_display(0, t), [0, 2]);
+    assert_eq!(tiler.workspaces_on_display(1, t), [1]);
+    assert!(tiler.workspaces_on_display(2, t).is_empty());
+}
+
+#[test]
+fn rotated_display_tiles_in_portrait() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    common::attach(&mut tiler, &[0, 1], t);
+    tiler.set_output_transform(0, Transform::Rotate90, t);
+
+    let rects = common::rects(&mut tiler, t);
+    assert_eq!(
+        rects.values().copied().collect::<Vec<_>>(),
+        vec![Rect::new(0, 0, 400, 1000), Rect::new(400, 0, 400, 1000)]
+    );
+}
+
+#[test]
+fn move_active_to_display_on_left() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = side_by_side(2, t);
+
+    tiler.move_active_to_display_direction(Direction::Left, t);
+    let events = common::events(&mut tiler, t);
+
+    assert_eq!(focused(&events), (Some(0), Some(WindowID(0, 1))));
+
+    // The window enters from the right edge of the display.
+    let rects = common::rects(&mut tiler, t);
+    assert_eq!(rects[&WindowID(0, 0)], Rect::new(0, 0, 500, 800));
+    assert_eq!(rects[&WindowID(0, 1)], Rect::new(500, 0, 500, 800));
+}
+
+#[test]
+fn fullscreen_leaves_other_display_visible() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = side_by_side(2, t);
+
+    let windows = common::attach(&mut tiler, &[2], t);
+    let _ = common::events(&mut tiler, t);
+
+    tiler.toggle_fullscreen(t);
+    let events = common::events(&mut tiler, t);
+
+    let hidden: Vec<WindowID> = events
+        .iter()
+        .filter_map(|event| match event {
+            Event::WindowVisibility(id, false) => Some(*id),
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(hidden, [WindowID(0, 1)]);
+    assert_eq!(
+        common::placements(&events).get(&windows[0].id(t)),
+        Some(&Rect::new(1000, 0, 1000, 800))
+    );
+}
+
+#[test]
+fn display_isolation_keeps_focus_on_display() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = side_by_side(2, t);
+
+    let window = tiler.windows[&WindowID(0, 0)].clone();
+    tiler.focus(&window, t);
+    let _ = common::events(&mut tiler, t);
+
+    tiler.set_display_isolation(true);
+    tiler.focus_right(t);
+    assert_eq!(focused(&common::events(&mut tiler, t)), (None, None));
+    assert_eq!(
+        tiler.active_window().map(|active| active.id(t)),
+        Some(WindowID(0, 0))
+    );
+
+    // Without isolation, focus moves on to the display on the right.
+    tiler.set_display_isolation(false);
+    tiler.focus_right(t);
+    assert_eq!(
+        focused(&common::events(&mut tiler, t)),
+        (Some(1), Some(WindowID(0, 1)))
+    );
+}
+
+/// The display that each workspace is assigned to.
+fn assignments<T: 'static>(tiler: &Tiler<T>, t: &TCellOwner<T>) -> Vec<(u32, u32)> {
+    let mut assigned: Vec<(u32, u32)> = tiler
+        .emit_initial_state(t)
+        .into_iter()
+        .filter_map(|event| match event {
+            Event::WorkspaceAssign { workspace, display } => Some((workspace, display)),
+            _ => None,
+        })
+        .collect();
+    assigned.sort_unstable();
+    assigned
+}
+
+#[test]
+fn workspaces_return_to_affine_display() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    tiler.display_update(1, Rect::new(1000, 0, 1000, 800), t);
+    tiler.workspace_update(1, 1, t);
+    tiler.workspace_update(2, 1, t);
+    tiler.set_workspace_affinity(1, 1);
+    tiler.set_workspace_affinity(2, 1);
+
+    tiler.workspace_switch(1, t);
+    common::attach(&mut tiler, &[1], t);
+    tiler.workspace_switch(0, t);
+
+    tiler.display_detach(1, t);
+    assert_eq!(assignments(&tiler, t), [(0, 0), (1, 0), (2, 0)]);
+
+    tiler.display_update(1, Rect::new(1000, 0, 1000, 800), t);
+    assert_eq!(assignments(&tiler, t), [(0, 0), (1, 1), (2, 1)]);
+
+    let rects = common::rects(&mut tiler, t);
+    assert_eq!(rects[&WindowID(0, 1)], Rect::new(1000, 0, 1000, 800));
+}
+
+#[test]
+fn swap_windows_across_displays() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = side_by_side(2, t);
+
+    let first = tiler.windows[&WindowID(0, 0)].clone();
+    let second = tiler.windows[&WindowID(0, 1)].clone();
+    tiler.swap(&first, &second, t);
+
+    let events = common::events(&mut tiler, t);
+    let workspaces: Vec<(WindowID, u32)> = events
+        .iter()
+        .filter_map(|event| match event {
+            Event::WindowPlace(id, placement) => Some((*id, placement.workspace)),
+            _ => None,
+        })
+        .collect();
+
+    assert!(workspaces.contains(&(WindowID(0, 0), 1)));
+    assert!(workspaces.contains(&(WindowID(0, 1), 0)));
+    assert!(!events
+        .iter()
+        .any(|event| matches!(event, Event::WindowVisibility(_, false))));
+
+    let rects = common::rects(&mut tiler, t);
+    assert_eq!(rects[&WindowID(0, 0)], Rect::new(1000, 0, 1000, 800));
+    assert_eq!(rects[&WindowID(0, 1)], Rect::new(0, 0, 1000, 800));
+
+    let visible = tiler
+        .emit_initial_state(t)
+        .into_iter()
+        .filter(|event| matches!(event, Event::WindowVisibility(_, true)))
+        .count();
+    assert_eq!(visible, 2);
+}
+
+#[test]
+fn translate_all_displays_shifts_windows_uniformly() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = side_by_side(2, t);
+    tiler.set_attach_rule(Box::new(|id| {
+        if id == WindowID(0, 3) {
+            AttachDecision::Float(Rect::new(100, 100, 300, 200))
+        } else {
+            AttachDecision::Tile
+        }
+    }));
+
+    // Floating windows move with the displays, though no tree places them.
+    common::attach(&mut tiler, &[2, 3], t);
+    let before = common::rects(&mut tiler, t);
+    assert!(before.contains_key(&WindowID(0, 3)));
+
+    tiler.translate_all_displays(100, 50, t).unwrap();
+    let after = common::rects(&mut tiler, t);
+
+    let shifted: BTreeMap<WindowID, Rect> = before
+        .iter()
+        .map(|(&id, &rect)| {
+            (
+                id,
+                Rect::new(rect.x + 100, rect.y + 50, rect.width, rect.height),
+            )
+        })
+        .collect();
+
+    assert_eq!(after, shifted);
+
+    // Moving past the origin is rejected, and leaves every window where it was.
+    assert_eq!(
+        tiler.translate_all_displays(-101, 0, t),
+        Err(NegativeCoordinate(-101, 0))
+    );
+    assert!(common::events(&mut tiler, t).is_empty());
+
+    tiler.translate_all_displays(-100, -50, t).unwrap();
+    assert_eq!(common::rects(&mut tiler, t), before);
+}
+
+#[test]
+fn mirrored_display_scales_placements() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+    tiler.display_update(1, Rect::new(1000, 0, 500, 400), t);
+    tiler.workspace_update(1, 1, t);
+
+    common::attach(&mut tiler, &[0, 1], t);
+    let placed = common::rects(&mut tiler, t);
+
+    tiler.set_display_mirror(0, 1, t);
+    let mut mirrored: Vec<(WindowID, u32, Rect)> = common::events(&mut tiler, t)
+        .into_iter()
+        .filter_map(|event| match event {
+            Event::WindowMirror(id, display, rect) => Some((id, display, rect)),
+            _ => None,
+        })
+        .collect();
+    mirrored.sort_by_key(|&(id, display, _)| (id, display));
+
+    assert_eq!(placed[&WindowID(0, 0)], Rect::new(0, 0, 500, 800));
+    assert_eq!(placed[&WindowID(0, 1)], Rect::new(500, 0, 500, 800));
+    assert_eq!(
+        mirrored,
+        [
+            (WindowID(0, 0), 1, Rect::new(1000, 0, 250, 400)),
+            (WindowID(0, 1), 1, Rect::new(1250, 0, 250, 400)),
+        ]
+    );
+}
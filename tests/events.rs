@@ -0,0 +1,333 @@
+// Copyright 2021 System76 <info@system76.com>
+// SPDX-License-Identifier: MPL-2.0
+
+mod common;
+
+use pop_tiler::*;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::rc::Rc;
+
+/// Attaches, focuses, and detaches windows, calling `step` after each operation with the
+/// events that the operation produced, in no particular order.
+fn operate<T: 'static>(
+    tiler: &mut Tiler<T>,
+    t: &mut TCellOwner<T>,
+    mut step: impl FnMut(&mut Tiler<T>, &mut TCellOwner<T>) -> Vec<Event>,
+) -> Vec<Vec<String>> {
+    let mut steps = Vec::new();
+    let mut record = |events: Vec<Event>| {
+        let mut events: Vec<String> = events.iter().map(|event| format!("{:?}", event)).collect();
+        events.sort();
+        steps.push(events);
+    };
+
+    let windows: Vec<WindowPtr<T>> = (0..3).map(|id| tiler.window((0, id))).collect();
+
+    for window in &windows {
+        tiler.attach(window, t);
+        record(step(tiler, t));
+        tiler.focus(window, t);
+        record(step(tiler, t));
+    }
+
+    tiler.detach(&windows[1], t);
+    record(step(tiler, t));
+
+    steps
+}
+
+#[test]
+fn event_sink_receives_pulled_events() {
+    struct Pull;
+    let t = &mut TCellOwner::<Pull>::new();
+    let mut tiler = common::tiler(t);
+    let _ = common::events(&mut tiler, t);
+    let pulled = operate(&mut tiler, t, common::events);
+
+    struct Push;
+    let t = &mut TCellOwner::<Push>::new();
+    let mut tiler = common::tiler(t);
+    let _ = common::events(&mut tiler, t);
+
+    let sunk = Rc::new(RefCell::new(Vec::new()));
+    let sink = sunk.clone();
+    tiler.set_event_sink(Box::new(move |event| sink.borrow_mut().push(event)));
+
+    let pushed = operate(&mut tiler, t, |tiler, t| {
+        assert_eq!(tiler.events(t).count(), 0);
+        sunk.borrow_mut().drain(..).collect()
+    });
+
+    assert!(pulled.iter().any(|events| !events.is_empty()));
+    assert_eq!(pulled, pushed);
+}
+
+#[test]
+fn placements_snap_to_physical_pixels() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+    tiler.display_update(0, Rect::new(0, 0, 1001, 801), t);
+    tiler.set_placement_rounding(0, 1.5, RoundingMode::SnapToGrid, t);
+
+    common::attach(&mut tiler, &[0, 1, 2], t);
+    let root = tiler.active_window_path(t)[0];
+    tiler.fork_resize(root, 333, t);
+
+    let placements = common::placements(&common::events(&mut tiler, t));
+    assert_eq!(placements.len(), 3);
+
+    // Every edge lies on a whole physical pixel at a scale of 1.5.
+    let physical = |logical: u32| (logical * 3) % 2 == 0;
+    for rect in placements.values() {
+        assert!(physical(rect.x) && physical(rect.y), "{:?}", rect);
+        assert!(
+            physical(rect.x_end()) && physical(rect.y_end()),
+            "{:?}",
+            rect
+        );
+    }
+
+    // Edges are moved inward, within the tiles they were allotted.
+    assert_eq!(placements[&WindowID(0, 0)], Rect::new(0, 0, 332, 800));
+}
+
+#[test]
+fn batch_places_each_window_once() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = Tiler::default();
+    tiler.set_event_compaction(EventCompaction::None);
+
+    let mut commands = vec![
+        Command::DisplayUpdate {
+            display: 0,
+            dimensions: Rect::new(0, 0, 1000, 800),
+        },
+        Command::WorkspaceUpdate {
+            workspace: 0,
+            display: 0,
+        },
+        Command::WorkspaceSwitch(0),
+    ];
+
+    for id in 0..5 {
+        commands.push(Command::Attach(WindowID(0, id)));
+        commands.push(Command::Focus(WindowID(0, id)));
+    }
+
+    let events = tiler.apply_batch(&commands, t);
+    let mut placed: Vec<WindowID> = events
+        .iter()
+        .filter_map(|event| match event {
+            Event::WindowPlace(id, _) => Some(*id),
+            _ => None,
+        })
+        .collect();
+
+    placed.sort();
+    assert_eq!(placed, (0..5).map(|id| WindowID(0, id)).collect::<Vec<_>>());
+    assert_eq!(common::placements(&events), common::rects(&mut tiler, t));
+}
+
+#[test]
+fn describe_fork_event_lists_windows() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    common::attach(&mut tiler, &[0, 1, 2], t);
+    let inner = tiler.active_window_path(t)[1];
+
+    let event = common::events(&mut tiler, t)
+        .into_iter()
+        .find(|event| matches!(event, Event::Fork(id, _) if *id == inner))
+        .expect("no event for the inner fork");
+
+    let description = tiler.describe_event(&event, t);
+    let windows = format!("{:?}", [WindowID(0, 1), WindowID(0, 2)]);
+    assert!(description.contains(&windows), "{}", description);
+
+    let destroyed = tiler.describe_event(&Event::ForkDestroy(inner + 100), t);
+    assert!(destroyed.contains("does not exist"), "{}", destroyed);
+}
+
+#[test]
+fn placement_carries_previous_rect() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+    tiler.set_emit_previous_rect(true);
+
+    let windows = common::attach(&mut tiler, &[0, 1], t);
+    let _ = common::events(&mut tiler, t);
+
+    tiler.swap(&windows[0], &windows[1], t);
+    let placement = common::events(&mut tiler, t)
+        .into_iter()
+        .find_map(|event| match event {
+            Event::WindowPlace(WindowID(0, 0), placement) => Some(placement),
+            _ => None,
+        })
+        .expect("the window was not placed");
+
+    assert_eq!(placement.area, Rect::new(500, 0, 500, 800));
+    assert_eq!(placement.from, Some(Rect::new(0, 0, 500, 800)));
+}
+
+/// How many events are retrieved after rotating three windows back to where they were,
+/// with this compaction of events.
+fn events_after_full_rotation<T: 'static>(
+    compaction: EventCompaction,
+    t: &mut TCellOwner<T>,
+) -> usize {
+    let mut tiler = common::tiler(t);
+    tiler.set_event_compaction(compaction);
+
+    common::attach(&mut tiler, &[0, 1, 2], t);
+    let _ = common::events(&mut tiler, t);
+
+    for _ in 0..3 {
+        tiler.rotate_windows(true, t);
+    }
+
+    common::events(&mut tiler, t).len()
+}
+
+#[test]
+fn fewer_events_with_more_compaction() {
+    struct All;
+    struct PerFrame;
+    struct NetState;
+
+    let all = events_after_full_rotation(EventCompaction::None, &mut TCellOwner::<All>::new());
+    let per_frame = events_after_full_rotation(
+        EventCompaction::PerFrame,
+        &mut TCellOwner::<PerFrame>::new(),
+    );
+    let net_state = events_after_full_rotation(
+        EventCompaction::NetState,
+        &mut TCellOwner::<NetState>::new(),
+    );
+
+    assert!(all > per_frame, "{} <= {}", all, per_frame);
+    assert!(per_frame > net_state, "{} <= {}", per_frame, net_state);
+}
+
+/// The state of a tiler as a window manager knows it from the events it has applied.
+#[derive(Debug, Default, PartialEq)]
+struct Model {
+    workspaces: BTreeMap<u32, u32>,
+    forks: BTreeMap<usize, ForkUpdate>,
+    stacks: BTreeMap<usize, (Rect, BTreeSet<WindowID>, Option<WindowID>)>,
+    windows: BTreeMap<WindowID, (Rect, bool)>,
+    workspace: Option<u32>,
+    focus: Option<WindowID>,
+}
+
+impl Model {
+    fn apply(&mut self, events: impl IntoIterator<Item = Event>) {
+        for event in events {
+            match event {
+                Event::WorkspaceAssign { workspace, display } => {
+                    self.workspaces.insert(workspace, display);
+                }
+                Event::Fork(id, update) => {
+                    self.forks.insert(id, update);
+                }
+                Event::ForkDestroy(id) => {
+                    self.forks.remove(&id);
+                }
+                Event::StackPlace(id, placement, _) => {
+                    self.stacks.entry(id).or_default().0 = placement.area;
+                }
+                Event::StackAssign(id, window) => {
+                    self.stacks.entry(id).or_default().1.insert(window);
+                }
+                Event::StackDetach(id, window) => {
+                    if let Some(stack) = self.stacks.get_mut(&id) {
+                        stack.1.remove(&window);
+                    }
+                }
+                Event::StackRaise(id, window) => {
+                    self.stacks.entry(id).or_default().2 = Some(window);
+                }
+                Event::StackDestroy(id) => {
+                    self.stacks.remove(&id);
+                }
+                Event::WindowPlace(id, placement) => {
+                    self.windows.entry(id).or_insert((placement.area, true)).0 = placement.area;
+                }
+                Event::WindowVisibility(id, visible) => {
+                    if let Some(window) = self.windows.get_mut(&id) {
+                        window.1 = visible;
+                    }
+                }
+                Event::FocusWorkspace(workspace) => self.workspace = Some(workspace),
+                Event::Focus(id, _) => self.focus = Some(id),
+                _ => (),
+            }
+        }
+    }
+}
+
+#[test]
+fn initial_state_reproduces_tiler() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = Tiler::default();
+    let mut model = Model::default();
+
+    tiler.display_update(0, Rect::new(0, 0, 1000, 800), t);
+    tiler.workspace_update(0, 0, t);
+    tiler.workspace_update(1, 0, t);
+    tiler.workspace_switch(1, t);
+    common::attach(&mut tiler, &[5], t);
+    tiler.workspace_switch(0, t);
+    model.apply(common::events(&mut tiler, t));
+
+    // The window manager assigned the workspaces itself, so they were not reported to it.
+    model.workspaces = [(0, 0), (1, 0)].iter().copied().collect();
+
+    let windows = common::attach(&mut tiler, &[0, 1, 2], t);
+    model.apply(common::events(&mut tiler, t));
+    tiler.stack_toggle(t);
+    common::attach(&mut tiler, &[3], t);
+    model.apply(common::events(&mut tiler, t));
+    tiler.focus(&windows[1], t);
+    tiler.toggle_orientation(t);
+    common::attach(&mut tiler, &[4], t);
+    model.apply(common::events(&mut tiler, t));
+
+    let mut initial = Model::default();
+    initial.apply(tiler.emit_initial_state(t));
+
+    assert_eq!(initial, model);
+    assert_eq!(initial.windows.len(), 6);
+    assert_eq!(initial.stacks.len(), 1);
+    assert_eq!(initial.windows[&WindowID(0, 5)].1, false);
+}
+
+#[test]
+fn reflow_reports_every_placement_again() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+    tiler.set_event_compaction(EventCompaction::NetState);
+
+    common::attach(&mut tiler, &[0, 1, 2], t);
+    let expected = common::rects(&mut tiler, t);
+
+    // The window manager lost track of its windows, which the tiler has already placed.
+    tiler.reflow(t);
+    let events = common::events(&mut tiler, t);
+
+    assert_eq!(common::placements(&events), expected);
+
+    let visible = events
+        .iter()
+        .filter(|event| matches!(event, Event::WindowVisibility(_, true)))
+        .count();
+    assert_eq!(visible, 3);
+}
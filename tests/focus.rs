@@ -0,0 +1,473 @@
+// Copyright 2021 System76 <info@system76.com>
+// SPDX-License-Identifier: MPL-2.0
+
+mod common;
+
+use pop_tiler::*;
+
+#[test]
+fn focus_rect_of_focused_window() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    let windows = common::attach(&mut tiler, &[0, 1, 2], t);
+    let rects = common::rects(&mut tiler, t);
+
+    tiler.focus(&windows[1], t);
+    let events = common::events(&mut tiler, t);
+
+    let focus_rect = events.iter().find_map(|event| match event {
+        Event::FocusRect(rect) => Some(*rect),
+        _ => None,
+    });
+
+    assert_eq!(focus_rect, Some(rects[&windows[1].id(t)]));
+    assert_ne!(rects[&windows[1].id(t)], rects[&windows[2].id(t)]);
+}
+
+#[test]
+fn active_window_is_stacked_follows_stack_toggle() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    common::attach(&mut tiler, &[0, 1], t);
+    assert!(!tiler.active_window_is_stacked(t));
+
+    tiler.stack_toggle(t);
+    assert!(tiler.active_window_is_stacked(t));
+
+    tiler.stack_toggle(t);
+    assert!(!tiler.active_window_is_stacked(t));
+}
+
+#[test]
+fn active_window_is_floating_follows_attach_rule() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+    tiler.set_attach_rule(Box::new(|id| {
+        if id == WindowID(0, 1) {
+            AttachDecision::Float(Rect::new(100, 100, 300, 200))
+        } else {
+            AttachDecision::Tile
+        }
+    }));
+
+    let windows = common::attach(&mut tiler, &[0, 1], t);
+    assert!(tiler.active_window_is_floating(t));
+
+    tiler.focus(&windows[0], t);
+    assert!(!tiler.active_window_is_floating(t));
+}
+
+#[test]
+fn active_window_is_fullscreen_follows_toggle_fullscreen() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    common::attach(&mut tiler, &[0, 1], t);
+    assert!(!tiler.active_window_is_fullscreen(t));
+
+    tiler.toggle_fullscreen(t);
+    assert!(tiler.active_window_is_fullscreen(t));
+
+    tiler.toggle_fullscreen(t);
+    assert!(!tiler.active_window_is_fullscreen(t));
+}
+
+/// The window focused by these events.
+fn focused(events: &[Event]) -> Option<WindowID> {
+    events.iter().find_map(|event| match event {
+        Event::Focus(window, _) => Some(*window),
+        _ => None,
+    })
+}
+
+#[test]
+fn focus_direction_reaches_cousin() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    // The first window is split below by a fork of the third and fourth.
+    let windows = common::attach(&mut tiler, &[0, 1], t);
+    tiler.focus(&windows[0], t);
+    common::attach(&mut tiler, &[2, 3], t);
+    assert_eq!(tiler.active_window_path(t).len(), 3);
+    let _ = common::events(&mut tiler, t);
+
+    tiler.focus_direction_or_parent(Direction::Right, t);
+    assert_eq!(
+        focused(&common::events(&mut tiler, t)),
+        Some(WindowID(0, 1))
+    );
+
+    tiler.focus_direction_or_parent(Direction::Right, t);
+    assert_eq!(focused(&common::events(&mut tiler, t)), None);
+}
+
+#[test]
+fn neighbors_of_center_of_plus() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+    tiler.display_update(0, Rect::new(0, 0, 900, 900), t);
+
+    // A column of the first three windows, between the fourth on the left and the fifth
+    // on the right. The second window is at the center.
+    let column = common::fork(
+        Rect::new(300, 0, 300, 900),
+        Orientation::Vertical,
+        300,
+        common::window(0),
+        common::fork(
+            Rect::new(300, 300, 300, 600),
+            Orientation::Vertical,
+            300,
+            common::window(1),
+            common::window(2),
+        ),
+    );
+
+    let layout = common::fork(
+        Rect::new(0, 0, 900, 900),
+        Orientation::Horizontal,
+        300,
+        common::window(3),
+        common::fork(
+            Rect::new(300, 0, 600, 900),
+            Orientation::Horizontal,
+            300,
+            column,
+            common::window(4),
+        ),
+    );
+
+    common::arrange(&mut tiler, layout, t);
+    let rects = common::rects(&mut tiler, t);
+    assert_eq!(rects[&WindowID(0, 1)], Rect::new(300, 300, 300, 300));
+
+    assert_eq!(
+        tiler.window_neighbors(WindowID(0, 1), t),
+        [
+            Some(WindowID(0, 0)),
+            Some(WindowID(0, 2)),
+            Some(WindowID(0, 3)),
+            Some(WindowID(0, 4))
+        ]
+    );
+}
+
+/// The window focused after swapping the active window with another, by this policy.
+fn focus_after_swap<T: 'static>(
+    policy: SwapFocusPolicy,
+    t: &mut TCellOwner<T>,
+) -> Option<WindowID> {
+    let mut tiler = common::tiler(t);
+    tiler.set_swap_focus_policy(policy);
+
+    let windows = common::attach(&mut tiler, &[0, 1], t);
+    tiler.focus(&windows[0], t);
+    tiler.swap(&windows[0], &windows[1], t);
+
+    tiler.active_window().map(|window| window.id(t))
+}
+
+#[test]
+fn swap_focus_follows_window() {
+    struct Marker;
+    let focus = focus_after_swap(
+        SwapFocusPolicy::FollowWindow,
+        &mut TCellOwner::<Marker>::new(),
+    );
+    assert_eq!(focus, Some(WindowID(0, 0)));
+}
+
+#[test]
+fn swap_focus_stays_in_place() {
+    struct Marker;
+    let focus = focus_after_swap(
+        SwapFocusPolicy::StayInPlace,
+        &mut TCellOwner::<Marker>::new(),
+    );
+    assert_eq!(focus, Some(WindowID(0, 1)));
+}
+
+/// The window focused after the unfocused of two windows demands attention.
+fn focus_after_urgency<T: 'static>(steals: bool, t: &mut TCellOwner<T>) -> Option<WindowID> {
+    let mut tiler = common::tiler(t);
+    tiler.set_focus_steals_on_urgent(steals);
+
+    let windows = common::attach(&mut tiler, &[0, 1], t);
+    let _ = common::events(&mut tiler, t);
+    tiler.set_urgent(&windows[0], true, t);
+
+    let urgency = common::events(&mut tiler, t)
+        .iter()
+        .any(|event| matches!(event, Event::WindowUrgency(WindowID(0, 0), true)));
+
+    // A window that takes focus no longer demands attention.
+    assert_eq!(urgency, !steals);
+    tiler.active_window().map(|window| window.id(t))
+}
+
+#[test]
+fn urgent_window_keeps_focus_away() {
+    struct Marker;
+    let focus = focus_after_urgency(false, &mut TCellOwner::<Marker>::new());
+    assert_eq!(focus, Some(WindowID(0, 1)));
+}
+
+#[test]
+fn urgent_window_steals_focus() {
+    struct Marker;
+    let focus = focus_after_urgency(true, &mut TCellOwner::<Marker>::new());
+    assert_eq!(focus, Some(WindowID(0, 0)));
+}
+
+/// The serial of the focus event among the events of the tiler.
+fn focus_serial<T: 'static>(tiler: &mut Tiler<T>, t: &mut TCellOwner<T>) -> u64 {
+    common::events(tiler, t)
+        .into_iter()
+        .find_map(|event| match event {
+            Event::Focus(_, serial) => serial,
+            _ => None,
+        })
+        .expect("a focus event with a serial")
+}
+
+#[test]
+fn focus_serials_increase_and_echo() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+    tiler.set_focus_animation_token(true);
+
+    let windows = common::attach(&mut tiler, &[0, 1, 2], t);
+    let _ = common::events(&mut tiler, t);
+
+    tiler.focus(&windows[0], t);
+    let first = focus_serial(&mut tiler, t);
+    tiler.focus(&windows[1], t);
+    let second = focus_serial(&mut tiler, t);
+    assert!(second > first);
+
+    // A focus change which the window manager initiated carries its serial.
+    tiler
+        .set_active_window_by_id(WindowID(0, 2), second + 100, t)
+        .unwrap();
+    assert_eq!(focus_serial(&mut tiler, t), second + 100);
+
+    tiler.focus(&windows[0], t);
+    assert!(focus_serial(&mut tiler, t) > second + 100);
+}
+
+#[test]
+fn focus_history_prunes_detached_windows() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    let windows = common::attach(&mut tiler, &[0, 1, 2], t);
+    tiler.detach(&windows[1], t);
+
+    assert_eq!(tiler.focus_history(), [WindowID(0, 2), WindowID(0, 0)]);
+}
+
+/// The window focused by moving focus right from the last tab of a stack, on the right of
+/// two displays, with stack and display wrapping enabled as given.
+fn focus_right_of_last_tab<T: 'static>(
+    stack_wrap: bool,
+    display_wrap: bool,
+    t: &mut TCellOwner<T>,
+) -> Option<WindowID> {
+    let mut tiler = common::tiler(t);
+    tiler.display_update(1, Rect::new(1000, 0, 1000, 800), t);
+    tiler.workspace_update(1, 1, t);
+    common::attach(&mut tiler, &[0], t);
+
+    tiler.workspace_switch(1, t);
+    let tabs = common::attach(&mut tiler, &[1], t);
+    tiler.stack_toggle(t);
+    let tabs = [tabs, common::attach(&mut tiler, &[2], t)].concat();
+
+    tiler.set_stack_cycle_wrap(stack_wrap);
+    tiler.set_display_focus_wrap(display_wrap);
+    tiler.focus(&tabs[1], t);
+    let _ = common::events(&mut tiler, t);
+
+    tiler.focus_right(t);
+    focused(&common::events(&mut tiler, t))
+}
+
+#[test]
+fn stack_wrap_without_display_wrap() {
+    struct Wrap;
+    struct Stop;
+
+    let wrapped = focus_right_of_last_tab(true, false, &mut TCellOwner::<Wrap>::new());
+    assert_eq!(wrapped, Some(WindowID(0, 1)));
+
+    let stopped = focus_right_of_last_tab(false, false, &mut TCellOwner::<Stop>::new());
+    assert_eq!(stopped, None);
+}
+
+#[test]
+fn display_wrap_without_stack_wrap() {
+    struct Marker;
+    let focus = focus_right_of_last_tab(false, true, &mut TCellOwner::<Marker>::new());
+    assert_eq!(focus, Some(WindowID(0, 0)));
+}
+
+/// The window focused after detaching the active window at the top right of three, having
+/// focused the window on the left, then the one below, then the active window.
+fn focus_after_detach<T: 'static>(
+    refocus: DetachRefocus,
+    t: &mut TCellOwner<T>,
+) -> Option<WindowID> {
+    let mut tiler = common::tiler(t);
+    tiler.set_detach_refocus(refocus);
+
+    let windows = common::attach(&mut tiler, &[0, 1, 2], t);
+    tiler.focus(&windows[0], t);
+    tiler.focus(&windows[2], t);
+    tiler.focus(&windows[1], t);
+
+    tiler.detach(&windows[1], t);
+    tiler.active_window().map(|active| active.id(t))
+}
+
+#[test]
+fn detach_refocus_left_neighbor() {
+    struct Marker;
+    let focus = focus_after_detach(
+        DetachRefocus::Direction(Direction::Left),
+        &mut TCellOwner::<Marker>::new(),
+    );
+    assert_eq!(focus, Some(WindowID(0, 0)));
+}
+
+#[test]
+fn detach_refocus_previous_focus() {
+    struct Marker;
+    let focus = focus_after_detach(
+        DetachRefocus::PreviousFocus,
+        &mut TCellOwner::<Marker>::new(),
+    );
+    assert_eq!(focus, Some(WindowID(0, 2)));
+}
+
+#[test]
+fn focus_biggest_other_window() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    let area = Rect::new(0, 0, 1000, 800);
+    let right = common::fork(
+        area,
+        Orientation::Vertical,
+        500,
+        common::window(1),
+        common::window(2),
+    );
+    let layout = common::fork(area, Orientation::Horizontal, 600, common::window(0), right);
+    common::arrange(&mut tiler, layout, t);
+
+    let largest = tiler.windows[&WindowID(0, 0)].clone();
+    tiler.focus(&largest, t);
+    let _ = common::events(&mut tiler, t);
+
+    tiler.focus_biggest_unfocused(t);
+    assert_eq!(
+        focused(&common::events(&mut tiler, t)),
+        Some(WindowID(0, 1))
+    );
+
+    tiler.focus_biggest_unfocused(t);
+    assert_eq!(
+        focused(&common::events(&mut tiler, t)),
+        Some(WindowID(0, 0))
+    );
+}
+
+#[test]
+fn hover_focuses_only_on_commit() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    common::attach(&mut tiler, &[0, 1], t);
+    let _ = common::events(&mut tiler, t);
+
+    tiler.hover(Some(WindowID(0, 0)));
+    assert_eq!(focused(&common::events(&mut tiler, t)), None);
+
+    tiler.commit_hover(t);
+    assert_eq!(
+        focused(&common::events(&mut tiler, t)),
+        Some(WindowID(0, 0))
+    );
+
+    // The hover is forgotten once committed.
+    tiler.commit_hover(t);
+    assert_eq!(focused(&common::events(&mut tiler, t)), None);
+}
+
+#[test]
+fn focus_history_keeps_three_most_recent() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+    tiler.set_focus_history_limit(3);
+
+    common::attach(&mut tiler, &[0, 1, 2, 3, 4], t);
+
+    assert_eq!(
+        tiler.focus_history(),
+        [WindowID(0, 4), WindowID(0, 3), WindowID(0, 2)]
+    );
+}
+
+/// The window focused by moving focus right from the last tab of a stack, beside a window
+/// on its left on one display, with stack and spatial wrapping enabled as given.
+fn focus_right_of_stack<T: 'static>(
+    stack_wrap: bool,
+    focus_wrap: bool,
+    t: &mut TCellOwner<T>,
+) -> Option<WindowID> {
+    let mut tiler = common::tiler(t);
+    common::attach(&mut tiler, &[0, 1], t);
+    tiler.stack_toggle(t);
+    let tabs = common::attach(&mut tiler, &[2], t);
+
+    tiler.set_stack_cycle_wrap(stack_wrap);
+    tiler.set_focus_wrap(focus_wrap);
+    tiler.focus(&tabs[0], t);
+    let _ = common::events(&mut tiler, t);
+
+    tiler.focus_right(t);
+    focused(&common::events(&mut tiler, t))
+}
+
+#[test]
+fn stack_wrap_without_focus_wrap() {
+    struct Marker;
+    let focus = focus_right_of_stack(true, false, &mut TCellOwner::<Marker>::new());
+    assert_eq!(focus, Some(WindowID(0, 1)));
+}
+
+#[test]
+fn focus_wrap_without_stack_wrap() {
+    struct Wrap;
+    struct Stop;
+
+    let wrapped = focus_right_of_stack(false, true, &mut TCellOwner::<Wrap>::new());
+    assert_eq!(wrapped, Some(WindowID(0, 0)));
+
+    let stopped = focus_right_of_stack(false, false, &mut TCellOwner::<Stop>::new());
+    assert_eq!(stopped, None);
+}
@@ -0,0 +1,110 @@
+// Copyright 2021 System76 <info@system76.com>
+// SPDX-License-Identifier: MPL-2.0
+
+mod common;
+
+use pop_tiler::*;
+use std::collections::BTreeMap;
+
+/// The gap between the first window and the windows to the right of it.
+fn gap_right_of_first(rects: &BTreeMap<WindowID, Rect>) -> Option<u32> {
+    let first = rects[&WindowID(0, 0)];
+    let right = rects
+        .values()
+        .map(|rect| rect.x)
+        .filter(|&x| x > first.x)
+        .min()?;
+    Some(right - first.x_end())
+}
+
+#[test]
+fn adaptive_gaps_shrink_to_minimum() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+    tiler.set_adaptive_gaps(20, 4, 8, t);
+
+    let gaps: Vec<u32> = (0..6)
+        .filter_map(|id| {
+            common::attach(&mut tiler, &[id], t);
+            gap_right_of_first(&common::rects(&mut tiler, t))
+        })
+        .collect();
+
+    assert_eq!(gaps, [16, 12, 8, 8, 8]);
+}
+
+#[test]
+fn gap_config_by_branch_kind() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    let stack = Layout::Stack {
+        windows: vec![WindowID(0, 2), WindowID(0, 3)],
+        active: WindowID(0, 2),
+    };
+
+    let area = Rect::new(0, 0, 1000, 800);
+    let left = common::fork(
+        area,
+        Orientation::Vertical,
+        400,
+        common::window(0),
+        common::window(1),
+    );
+    let right = common::fork(area, Orientation::Vertical, 400, stack, common::window(4));
+    common::arrange(
+        &mut tiler,
+        common::fork(area, Orientation::Horizontal, 500, left, right),
+        t,
+    );
+
+    tiler.set_gap_config(
+        GapConfig {
+            outer: 5,
+            inner_fork: 10,
+            inner_window: 20,
+            stack: 30,
+        },
+        t,
+    );
+
+    let rects = common::rects(&mut tiler, t);
+    let rect = |id| rects[&WindowID(0, id)];
+
+    // Around the tree, and between the forks at the root.
+    assert_eq!((rect(0).x, rect(0).y), (5, 5));
+    assert_eq!(rect(4).x_end(), 995);
+    assert_eq!(rect(2).x - rect(0).x_end(), 10);
+
+    // Between two windows, and beside a stack.
+    assert_eq!(rect(1).y - rect(0).y_end(), 20);
+    assert_eq!(rect(4).y - rect(2).y_end(), 30);
+}
+
+#[test]
+fn shrink_of_solo_window_is_gap_transition() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+    tiler.set_adaptive_gaps(20, 4, 8, t);
+    tiler.set_default_gap_animation(true);
+
+    common::attach(&mut tiler, &[0], t);
+    let solo = common::rects(&mut tiler, t)[&WindowID(0, 0)];
+
+    common::attach(&mut tiler, &[1], t);
+    let placement = common::events(&mut tiler, t)
+        .into_iter()
+        .find_map(|event| match event {
+            Event::WindowPlace(WindowID(0, 0), placement) => Some(placement),
+            _ => None,
+        })
+        .expect("the solo window to be placed again");
+
+    assert!(placement.gap_transition);
+    assert_eq!(placement.from, Some(solo));
+    // Half of the display, less half of the gap of 16 between two windows.
+    assert_eq!(placement.area, Rect::new(0, 0, 492, 800));
+}
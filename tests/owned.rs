@@ -0,0 +1,42 @@
+// Copyright 2021 System76 <info@system76.com>
+// SPDX-License-Identifier: MPL-2.0
+
+mod common;
+
+use pop_tiler::*;
+
+#[test]
+fn owned_tiler_attach_focus_detach() {
+    struct Marker;
+    let mut tiler = OwnedTiler::<Marker>::new();
+
+    tiler.display_update(0, Rect::new(0, 0, 1000, 800));
+    tiler.workspace_update(0, 0);
+    tiler.workspace_switch(0);
+
+    tiler.attach(WindowID(0, 0));
+    tiler.attach(WindowID(0, 1));
+    let events: Vec<Event> = tiler.events().collect();
+
+    let placed = common::placements(&events);
+    assert_eq!(placed[&WindowID(0, 0)], Rect::new(0, 0, 500, 800));
+    assert_eq!(placed[&WindowID(0, 1)], Rect::new(500, 0, 500, 800));
+
+    tiler.focus(WindowID(0, 1)).unwrap();
+    tiler.focus(WindowID(0, 0)).unwrap();
+    assert_eq!(tiler.active_window(), Some(WindowID(0, 0)));
+
+    // The previously focused window is focused in place of the detached window.
+    tiler.detach(WindowID(0, 0)).unwrap();
+    let events: Vec<Event> = tiler.events().collect();
+
+    assert_eq!(tiler.active_window(), Some(WindowID(0, 1)));
+    assert_eq!(
+        common::placements(&events).get(&WindowID(0, 1)),
+        Some(&Rect::new(0, 0, 1000, 800))
+    );
+    assert_eq!(
+        tiler.detach(WindowID(0, 0)),
+        Err(WindowNotFound(WindowID(0, 0)))
+    );
+}
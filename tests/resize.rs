@@ -0,0 +1,218 @@
+// Copyright 2021 System76 <info@system76.com>
+// SPDX-License-Identifier: MPL-2.0
+
+mod common;
+
+use pop_tiler::*;
+
+#[test]
+fn resize_container_moves_outer_split() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    // A wide display splits both the root and the fork within it horizontally.
+    tiler.display_update(0, Rect::new(0, 0, 2000, 800), t);
+    common::attach(&mut tiler, &[0, 1, 2], t);
+
+    let path = tiler.active_window_path(t);
+    let (outer, inner) = (path[0], path[1]);
+    let before = common::forks(&tiler, t);
+    assert_eq!(before[&outer].orientation, Orientation::Horizontal);
+    assert_eq!(before[&inner].orientation, Orientation::Horizontal);
+
+    tiler.resize_active(Direction::Left, 10, t);
+    let resized = common::forks(&tiler, t);
+    assert_eq!(resized[&outer].handle, before[&outer].handle);
+    assert!(resized[&inner].handle < before[&inner].handle);
+
+    tiler.resize_container(Direction::Left, 10, t);
+    let after = common::forks(&tiler, t);
+    assert!(after[&outer].handle < resized[&outer].handle);
+}
+
+#[test]
+fn split_kept_above_pixel_floor() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+    tiler.set_min_split_pixels(300);
+
+    common::attach(&mut tiler, &[0, 1], t);
+    let root = tiler.active_window_path(t)[0];
+
+    tiler.fork_resize(root, 50, t);
+    assert_eq!(common::forks(&tiler, t)[&root].handle, 300);
+
+    tiler.fork_resize(root, 950, t);
+    assert_eq!(common::forks(&tiler, t)[&root].handle, 700);
+
+    // A fork too narrow for the floor on both sides is split evenly.
+    tiler.display_update(0, Rect::new(0, 0, 500, 800), t);
+    tiler.fork_resize(root, 50, t);
+    assert_eq!(common::forks(&tiler, t)[&root].handle, 250);
+}
+
+#[test]
+fn fork_fits_minimum_width() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    let windows = common::attach(&mut tiler, &[0, 1], t);
+    tiler.set_min_size(&windows[0], 300, 200, t);
+
+    let root = tiler.active_window_path(t)[0];
+    tiler.resize_fork_to_fit(root, t);
+
+    let rects = common::rects(&mut tiler, t);
+    assert_eq!(rects[&WindowID(0, 0)], Rect::new(0, 0, 300, 800));
+    assert_eq!(rects[&WindowID(0, 1)], Rect::new(300, 0, 700, 800));
+}
+
+/// The split of a fork which was split at 47.3%, after its ratio is normalized to this
+/// percentage.
+fn normalized_split<T: 'static>(snap: u32, t: &mut TCellOwner<T>) -> u32 {
+    let mut tiler = common::tiler(t);
+    tiler.set_ratio_snap(snap);
+
+    let area = Rect::new(0, 0, 1000, 800);
+    let layout = common::fork(
+        area,
+        Orientation::Horizontal,
+        473,
+        common::window(0),
+        common::window(1),
+    );
+    common::arrange(&mut tiler, layout, t);
+
+    tiler.normalize_ratios(t);
+    let forks = common::forks(&tiler, t);
+    assert_eq!(forks.len(), 1);
+    forks.values().next().unwrap().handle
+}
+
+#[test]
+fn normalize_ratio_to_nearest_five_percent() {
+    struct Marker;
+    assert_eq!(normalized_split(5, &mut TCellOwner::<Marker>::new()), 450);
+}
+
+#[test]
+fn normalize_ratio_to_nearest_ten_percent() {
+    struct Marker;
+    assert_eq!(normalized_split(10, &mut TCellOwner::<Marker>::new()), 500);
+}
+
+#[test]
+fn placement_epsilon_suppresses_pixel_resizes() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+    tiler.set_placement_epsilon(2);
+
+    common::attach(&mut tiler, &[0, 1], t);
+    let root = tiler.active_window_path(t)[0];
+    let _ = common::events(&mut tiler, t);
+
+    let mut placed = 0;
+    for split in 501..=510 {
+        tiler.fork_resize(root, split, t);
+        placed += common::events(&mut tiler, t)
+            .iter()
+            .filter(|event| matches!(event, Event::WindowPlace(..)))
+            .count();
+    }
+
+    // Of twenty placements, each window is placed only once it has moved by more than two
+    // pixels since it was last placed.
+    assert_eq!(placed, 6);
+}
+
+/// The columns of three 300, 300, and 400 pixels wide, after the first split is dragged
+/// 100 pixels right in this resize mode.
+fn columns_after_drag<T: 'static>(mode: ResizeMode, t: &mut TCellOwner<T>) -> Vec<Rect> {
+    let mut tiler = common::tiler(t);
+    tiler.set_resize_mode(mode);
+
+    let area = Rect::new(0, 0, 1000, 800);
+    let right = Rect::new(300, 0, 700, 800);
+    let right = common::fork(
+        right,
+        Orientation::Horizontal,
+        300,
+        common::window(1),
+        common::window(2),
+    );
+    let layout = common::fork(area, Orientation::Horizontal, 300, common::window(0), right);
+    common::arrange(&mut tiler, layout, t);
+
+    let first = tiler.windows[&WindowID(0, 0)].clone();
+    tiler.focus(&first, t);
+    let root = tiler.active_window_path(t)[0];
+    tiler.fork_resize(root, 400, t);
+
+    let rects = common::rects(&mut tiler, t);
+    (0..3).map(|id| rects[&WindowID(0, id)]).collect()
+}
+
+#[test]
+fn chained_resize_shifts_third_column() {
+    struct Marker;
+    let columns = columns_after_drag(ResizeMode::Chained, &mut TCellOwner::<Marker>::new());
+    assert_eq!(
+        columns,
+        [
+            Rect::new(0, 0, 400, 800),
+            Rect::new(400, 0, 300, 800),
+            Rect::new(700, 0, 300, 800),
+        ]
+    );
+}
+
+#[test]
+fn isolated_resize_keeps_third_column() {
+    struct Marker;
+    let columns = columns_after_drag(ResizeMode::Isolated, &mut TCellOwner::<Marker>::new());
+    assert_eq!(
+        columns,
+        [
+            Rect::new(0, 0, 400, 800),
+            Rect::new(400, 0, 200, 800),
+            Rect::new(600, 0, 400, 800),
+        ]
+    );
+}
+
+#[test]
+fn resize_throttle_batches_pixel_resizes() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+    tiler.set_resize_throttle(10);
+
+    common::attach(&mut tiler, &[0, 1], t);
+    let root = tiler.active_window_path(t)[0];
+    let _ = common::events(&mut tiler, t);
+
+    // The splits at which a resize placed the windows.
+    let mut placed_at = Vec::new();
+    for split in 501..=525 {
+        tiler.fork_resize(root, split, t);
+        let events = common::events(&mut tiler, t);
+        if events
+            .iter()
+            .any(|event| matches!(event, Event::WindowPlace(..)))
+        {
+            placed_at.push(split);
+        }
+    }
+
+    assert_eq!(placed_at, [510, 520]);
+
+    // Committing moves the split to exactly where it was last dragged.
+    tiler.fork_resize_commit(root, t);
+    let placed = common::placements(&common::events(&mut tiler, t));
+    assert_eq!(placed[&WindowID(0, 0)], Rect::new(0, 0, 525, 800));
+    assert_eq!(placed[&WindowID(0, 1)], Rect::new(525, 0, 475, 800));
+}
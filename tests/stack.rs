@@ -0,0 +1,429 @@
+// Copyright 2021 System76 <info@system76.com>
+// SPDX-License-Identifier: MPL-2.0
+
+mod common;
+
+use pop_tiler::*;
+use std::collections::BTreeMap;
+
+/// The ID of the stack that each window is assigned to by these events.
+fn assigned(events: &[Event]) -> Vec<(usize, WindowID)> {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            Event::StackAssign(stack, window) => Some((*stack, *window)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The tabs of each stack in a layout, in order.
+fn tabs(layout: &Layout) -> Vec<Vec<WindowID>> {
+    match layout {
+        Layout::Window(_) => Vec::new(),
+        Layout::Stack { windows, .. } => vec![windows.clone()],
+        Layout::Fork(fork) => {
+            let mut stacks = tabs(&fork.left);
+            if let Some(right) = fork.right.as_ref() {
+                stacks.extend(tabs(right));
+            }
+            stacks
+        }
+    }
+}
+
+/// The tabs of each stack on workspace 0, in order.
+fn workspace_tabs<T: 'static>(tiler: &Tiler<T>, t: &TCellOwner<T>) -> Vec<Vec<WindowID>> {
+    tiler
+        .snapshot_workspace(0, t)
+        .map_or_else(Vec::new, |snapshot| tabs(&snapshot.root))
+}
+
+/// The IDs of the stacks created by toggling three windows into stacks, in turn.
+fn stack_ids<T: 'static>(t: &mut TCellOwner<T>) -> Vec<usize> {
+    let mut tiler = common::tiler(t);
+    let windows = common::attach(&mut tiler, &[0, 1, 2], t);
+
+    windows
+        .iter()
+        .filter_map(|window| {
+            tiler.focus(window, t);
+            tiler.stack_toggle(t);
+            assigned(&common::events(&mut tiler, t))
+                .first()
+                .map(|&(stack, _)| stack)
+        })
+        .collect()
+}
+
+#[test]
+fn stack_ids_are_reproducible_and_monotonic() {
+    struct First;
+    let first = stack_ids(&mut TCellOwner::<First>::new());
+
+    struct Second;
+    let second = stack_ids(&mut TCellOwner::<Second>::new());
+
+    assert_eq!(first.len(), 3);
+    assert!(first.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!(first, second);
+}
+
+#[test]
+fn stack_tabs_in_window_id_order() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+    tiler.set_stack_tab_order(StackTabOrder::WindowId);
+
+    common::attach(&mut tiler, &[5], t);
+    tiler.stack_toggle(t);
+    common::attach(&mut tiler, &[3, 4], t);
+
+    let moved = common::events(&mut tiler, t)
+        .iter()
+        .any(|event| matches!(event, Event::StackMovement(_, StackMovement::To(..))));
+
+    assert!(moved);
+    assert_eq!(
+        workspace_tabs(&tiler, t),
+        [[WindowID(0, 3), WindowID(0, 4), WindowID(0, 5)]]
+    );
+}
+
+#[test]
+fn tab_bar_at_bottom_insets_windows_from_bottom() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+    tiler.set_tab_bar_height(30);
+    tiler.set_tab_bar_position(TabBarPosition::Bottom);
+
+    common::attach(&mut tiler, &[0, 1], t);
+    tiler.stack_toggle(t);
+    let events = common::events(&mut tiler, t);
+
+    let stack = events.iter().find_map(|event| match event {
+        Event::StackPlace(_, placement, position) => Some((placement.area, *position)),
+        _ => None,
+    });
+
+    let stack_area = Rect::new(500, 0, 500, 800);
+    assert_eq!(stack, Some((stack_area, TabBarPosition::Bottom)));
+    assert_eq!(
+        common::placements(&events)[&WindowID(0, 1)],
+        Rect::new(500, 0, 500, 770)
+    );
+}
+
+#[test]
+fn max_tree_depth_stacks_fourth_window() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+    tiler.set_max_tree_depth(Some(2));
+
+    common::attach(&mut tiler, &[0, 1, 2], t);
+    assert_eq!(tiler.fork_tree_depth(0, t), 2);
+    assert!(workspace_tabs(&tiler, t).is_empty());
+
+    common::attach(&mut tiler, &[3], t);
+    assert_eq!(tiler.fork_tree_depth(0, t), 2);
+    assert_eq!(
+        workspace_tabs(&tiler, t),
+        [[WindowID(0, 2), WindowID(0, 3)]]
+    );
+}
+
+#[test]
+fn tabbed_workspace_collapses_and_restores() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    let windows = common::attach(&mut tiler, &[0, 1, 2], t);
+    tiler.focus(&windows[1], t);
+    let tiled = common::rects(&mut tiler, t);
+
+    tiler.toggle_tabbed_workspace(t);
+    let events = common::events(&mut tiler, t);
+
+    let stacks: Vec<Rect> = events
+        .iter()
+        .filter_map(|event| match event {
+            Event::StackPlace(_, placement, _) => Some(placement.area),
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(stacks, [Rect::new(0, 0, 1000, 800)]);
+    assert_eq!(workspace_tabs(&tiler, t).len(), 1);
+    assert!(tiler.active_window_is_stacked(t));
+
+    let placements = common::placements(&events);
+    let active = placements[&WindowID(0, 1)];
+    assert_eq!((active.x, active.width), (0, 1000));
+
+    let shown: BTreeMap<WindowID, bool> = events
+        .iter()
+        .filter_map(|event| match event {
+            Event::WindowVisibility(id, visible) => Some((*id, *visible)),
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(shown.get(&WindowID(0, 0)), Some(&false));
+    assert_eq!(shown.get(&WindowID(0, 2)), Some(&false));
+    assert_ne!(shown.get(&WindowID(0, 1)), Some(&false));
+
+    tiler.toggle_tabbed_workspace(t);
+    assert_eq!(common::rects(&mut tiler, t), tiled);
+    assert!(workspace_tabs(&tiler, t).is_empty());
+}
+
+/// The tab raised after detaching the raised tab of a stack of three, having focused the
+/// first tab and then the last, and then a window beside the stack.
+fn raised_after_detach<T: 'static>(refocus: StackRefocus, t: &mut TCellOwner<T>) -> WindowID {
+    let mut tiler = common::tiler(t);
+    tiler.set_stack_refocus(refocus);
+
+    let beside = common::attach(&mut tiler, &[3, 0], t);
+    tiler.stack_toggle(t);
+    let tabs = common::attach(&mut tiler, &[1, 2], t);
+
+    tiler.focus(&beside[1], t);
+    tiler.focus(&tabs[1], t);
+    tiler.focus(&beside[0], t);
+    tiler.detach(&tabs[1], t);
+
+    let root = tiler.snapshot_workspace(0, t).unwrap().root;
+    match root {
+        Layout::Fork(fork) => match fork.right {
+            Some(Layout::Stack { active, .. }) => active,
+            right => panic!("expected a stack beside the window: {:?}", right),
+        },
+        root => panic!("expected a fork: {:?}", root),
+    }
+}
+
+#[test]
+fn stack_refocus_most_recent() {
+    struct Marker;
+    let raised = raised_after_detach(StackRefocus::MostRecent, &mut TCellOwner::<Marker>::new());
+    assert_eq!(raised, WindowID(0, 0));
+}
+
+#[test]
+fn stack_refocus_positional() {
+    struct Marker;
+    let raised = raised_after_detach(StackRefocus::Positional, &mut TCellOwner::<Marker>::new());
+    assert_eq!(raised, WindowID(0, 1));
+}
+
+#[test]
+fn stack_from_three_windows() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    common::attach(&mut tiler, &[0, 1, 2, 3], t);
+    let ids = [WindowID(0, 3), WindowID(0, 1), WindowID(0, 2)];
+
+    let stack = tiler.stack_from_windows(&ids, t);
+    let events = common::events(&mut tiler, t);
+
+    assert!(stack.is_some());
+    assert!(assigned(&events).iter().all(|&(id, _)| Some(id) == stack));
+    assert_eq!(workspace_tabs(&tiler, t), vec![ids.to_vec()]);
+    assert!(tiler.validate(t).is_empty());
+}
+
+#[test]
+fn unstack_into_three_equal_tiles() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    let stack = Layout::Stack {
+        windows: vec![WindowID(0, 1), WindowID(0, 2), WindowID(0, 3)],
+        active: WindowID(0, 1),
+    };
+
+    let area = Rect::new(0, 0, 1000, 800);
+    let layout = common::fork(area, Orientation::Vertical, 200, common::window(0), stack);
+    common::arrange(&mut tiler, layout, t);
+
+    let stack = assigned(&common::events(&mut tiler, t))[0].0;
+    tiler.unstack(stack, t);
+    let events = common::events(&mut tiler, t);
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, Event::StackDestroy(id) if *id == stack)));
+
+    let rects = common::rects(&mut tiler, t);
+    let tiles: Vec<Rect> = (1..=3).map(|id| rects[&WindowID(0, id)]).collect();
+    assert_eq!(
+        tiles,
+        [
+            Rect::new(0, 200, 333, 600),
+            Rect::new(333, 200, 333, 600),
+            Rect::new(666, 200, 334, 600),
+        ]
+    );
+    assert!(workspace_tabs(&tiler, t).is_empty());
+}
+
+/// The raised tab of a stack, and the focused window, after a window is attached to the
+/// stack while its only tab is focused.
+fn raised_after_attach<T: 'static>(
+    focus: NewStackFocus,
+    t: &mut TCellOwner<T>,
+) -> (Option<WindowID>, Option<WindowID>) {
+    let mut tiler = common::tiler(t);
+    tiler.set_new_stack_focus(focus);
+
+    common::attach(&mut tiler, &[0], t);
+    tiler.stack_toggle(t);
+
+    let window = tiler.window((0, 1));
+    tiler.attach(&window, t);
+
+    let raised = match tiler.snapshot_workspace(0, t).unwrap().root {
+        Layout::Fork(fork) => match fork.left {
+            Layout::Stack { active, .. } => Some(active),
+            _ => None,
+        },
+        _ => None,
+    };
+
+    (raised, tiler.active_window().map(|active| active.id(t)))
+}
+
+#[test]
+fn new_stack_focus_raises_new_tab() {
+    struct Marker;
+    let raised = raised_after_attach(NewStackFocus::New, &mut TCellOwner::<Marker>::new());
+    assert_eq!(raised, (Some(WindowID(0, 1)), Some(WindowID(0, 1))));
+}
+
+#[test]
+fn new_stack_focus_keeps_existing_tab() {
+    struct Marker;
+    let raised = raised_after_attach(NewStackFocus::Existing, &mut TCellOwner::<Marker>::new());
+    assert_eq!(raised, (Some(WindowID(0, 0)), Some(WindowID(0, 0))));
+}
+
+#[test]
+fn raise_tab_without_focus_keeps_focus() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    let beside = common::attach(&mut tiler, &[3, 0], t);
+    tiler.stack_toggle(t);
+    let tabs = common::attach(&mut tiler, &[1, 2], t);
+    tiler.focus(&beside[0], t);
+    let _ = common::events(&mut tiler, t);
+
+    tiler.raise_tab_without_focus(&tabs[0], t);
+    let events = common::events(&mut tiler, t);
+
+    let mut visibility: Vec<(WindowID, bool)> = events
+        .iter()
+        .filter_map(|event| match event {
+            Event::WindowVisibility(id, visible) => Some((*id, *visible)),
+            _ => None,
+        })
+        .collect();
+    visibility.sort();
+
+    assert_eq!(
+        visibility,
+        [(WindowID(0, 1), true), (WindowID(0, 2), false)]
+    );
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, Event::StackRaise(_, id) if *id == WindowID(0, 1))));
+    assert!(!events.iter().any(|event| matches!(event, Event::Focus(..))));
+    assert_eq!(
+        tiler.active_window().map(|active| active.id(t)),
+        Some(WindowID(0, 3))
+    );
+}
+
+#[test]
+fn raise_tab_without_focus_on_other_display() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+    tiler.display_update(1, Rect::new(1000, 0, 1000, 800), t);
+    tiler.workspace_update(1, 1, t);
+
+    tiler.workspace_switch(1, t);
+    common::attach(&mut tiler, &[0], t);
+    tiler.stack_toggle(t);
+    let tabs = common::attach(&mut tiler, &[1, 2], t);
+
+    tiler.workspace_switch(0, t);
+    common::attach(&mut tiler, &[3], t);
+    let _ = common::events(&mut tiler, t);
+
+    // The stack is on the workspace shown on the other display, so the raised tab is shown.
+    tiler.raise_tab_without_focus(&tabs[0], t);
+
+    let mut visibility: Vec<(WindowID, bool)> = common::events(&mut tiler, t)
+        .iter()
+        .filter_map(|event| match event {
+            Event::WindowVisibility(id, visible) => Some((*id, *visible)),
+            _ => None,
+        })
+        .collect();
+    visibility.sort();
+
+    assert_eq!(
+        visibility,
+        [(WindowID(0, 1), true), (WindowID(0, 2), false)]
+    );
+}
+
+#[test]
+fn fifth_window_auto_stacks_on_small_display() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+    tiler.display_update(0, Rect::new(0, 0, 400, 300), t);
+
+    // Five tiles would average 24000 pixels, and four 30000.
+    tiler.set_auto_stack_threshold(Some(25_000));
+
+    common::attach(&mut tiler, &[0, 1, 2, 3], t);
+    assert_eq!(tiler.count_stacks(), 0);
+
+    common::attach(&mut tiler, &[4], t);
+    assert_eq!(
+        workspace_tabs(&tiler, t),
+        [[WindowID(0, 3), WindowID(0, 4)]]
+    );
+}
+
+#[test]
+fn auto_stack_windows_of_same_client() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+    tiler.set_auto_stack_same_client(true);
+
+    // Two windows of client 0 stack, and a window of client 1 is tiled beside them.
+    common::attach(&mut tiler, &[0, 1], t);
+    let other = tiler.window((1, 0));
+    tiler.attach(&other, t);
+
+    assert_eq!(
+        workspace_tabs(&tiler, t),
+        [[WindowID(0, 0), WindowID(0, 1)]]
+    );
+
+    let rects = common::rects(&mut tiler, t);
+    assert_eq!(rects[&WindowID(1, 0)], Rect::new(500, 0, 500, 800));
+    assert_eq!(tiler.count_forks(), 1);
+}
@@ -0,0 +1,549 @@
+// Copyright 2021 System76 <info@system76.com>
+// SPDX-License-Identifier: MPL-2.0
+
+mod common;
+
+use pop_tiler::*;
+use std::collections::BTreeMap;
+
+#[test]
+fn rotate_windows_forward() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    let windows = common::attach(&mut tiler, &[0, 1, 2], t);
+    let ids: Vec<WindowID> = windows.iter().map(|window| window.id(t)).collect();
+    let before = common::rects(&mut tiler, t);
+
+    tiler.rotate_windows(true, t);
+    let after = common::rects(&mut tiler, t);
+
+    for (slot, id) in ids.iter().enumerate() {
+        let next = ids[(slot + 1) % ids.len()];
+        assert_eq!(after[id], before[&next]);
+    }
+}
+
+#[test]
+fn fork_children_of_root_and_inner_fork() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    // The third window splits the second, in a fork beside the first.
+    common::attach(&mut tiler, &[0, 1, 2], t);
+    let path = tiler.active_window_path(t);
+    let (root, inner) = (path[0], path[1]);
+
+    assert_eq!(
+        tiler.fork_children(root, t),
+        Some((
+            ForkChild::Window(WindowID(0, 0)),
+            Some(ForkChild::Fork(inner))
+        ))
+    );
+
+    assert_eq!(
+        tiler.fork_children(inner, t),
+        Some((
+            ForkChild::Window(WindowID(0, 1)),
+            Some(ForkChild::Window(WindowID(0, 2)))
+        ))
+    );
+
+    assert_eq!(tiler.fork_children(root + inner + 1, t), None);
+}
+
+/// The IDs of the forks created and destroyed by a sequence of operations.
+fn fork_ids<T: 'static>(t: &mut TCellOwner<T>) -> (Vec<usize>, Vec<usize>) {
+    let mut tiler = common::tiler(t);
+    let windows = common::attach(&mut tiler, &[0, 1, 2, 3], t);
+    tiler.detach(&windows[2], t);
+    common::attach(&mut tiler, &[4], t);
+
+    let (mut created, mut destroyed) = (Vec::new(), Vec::new());
+    for event in common::events(&mut tiler, t) {
+        match event {
+            Event::Fork(id, _) => created.push(id),
+            Event::ForkDestroy(id) => destroyed.push(id),
+            _ => (),
+        }
+    }
+
+    created.sort_unstable();
+    created.dedup();
+    destroyed.sort_unstable();
+    (created, destroyed)
+}
+
+#[test]
+fn fork_ids_are_reproducible() {
+    struct First;
+    let first = fork_ids(&mut TCellOwner::<First>::new());
+
+    struct Second;
+    let second = fork_ids(&mut TCellOwner::<Second>::new());
+
+    assert!(!first.0.is_empty() && !first.1.is_empty());
+    assert_eq!(first, second);
+}
+
+/// The windows placed at these rects, from left to right.
+fn left_to_right(rects: &BTreeMap<WindowID, Rect>) -> Vec<WindowID> {
+    let mut windows: Vec<(u32, WindowID)> = rects.iter().map(|(&id, rect)| (rect.x, id)).collect();
+    windows.sort();
+    windows.into_iter().map(|(_, id)| id).collect()
+}
+
+#[test]
+fn move_window_before_leftmost() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    // A wide display arranges the windows in a row.
+    tiler.display_update(0, Rect::new(0, 0, 3000, 800), t);
+    let windows = common::attach(&mut tiler, &[0, 1, 2], t);
+    let order = left_to_right(&common::rects(&mut tiler, t));
+    assert_eq!(order, [WindowID(0, 0), WindowID(0, 1), WindowID(0, 2)]);
+
+    tiler.move_window_before(&windows[2], &windows[0], t);
+
+    let rects = common::rects(&mut tiler, t);
+    assert!(rects.values().all(|rect| rect.y == 0 && rect.height == 800));
+    let order = left_to_right(&rects);
+    assert_eq!(order, [WindowID(0, 2), WindowID(0, 0), WindowID(0, 1)]);
+}
+
+#[test]
+fn attach_to_fork_branch_empty_and_occupied() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    common::attach(&mut tiler, &[0], t);
+    let root = tiler.active_window_path(t)[0];
+
+    // The empty right branch is assigned the window.
+    assert_eq!(
+        tiler.attach_to_fork_branch(WindowID(0, 1), root, Side::Right, t),
+        Ok(())
+    );
+    assert_eq!(
+        tiler.fork_children(root, t),
+        Some((
+            ForkChild::Window(WindowID(0, 0)),
+            Some(ForkChild::Window(WindowID(0, 1)))
+        ))
+    );
+
+    // The occupied left branch is split, with the window on the right of the new fork.
+    assert_eq!(
+        tiler.attach_to_fork_branch(WindowID(0, 2), root, Side::Left, t),
+        Ok(())
+    );
+    let inner = match tiler.fork_children(root, t) {
+        Some((ForkChild::Fork(inner), Some(ForkChild::Window(WindowID(0, 1))))) => inner,
+        children => panic!("unexpected children of the root: {:?}", children),
+    };
+
+    assert_eq!(
+        tiler.fork_children(inner, t),
+        Some((
+            ForkChild::Window(WindowID(0, 0)),
+            Some(ForkChild::Window(WindowID(0, 2)))
+        ))
+    );
+
+    // A window which cannot be placed is not made known to the tiler.
+    let unknown = root + inner + 1;
+    assert_eq!(
+        tiler.attach_to_fork_branch(WindowID(0, 3), unknown, Side::Left, t),
+        Err(PlacementError::ForkNotFound(unknown))
+    );
+    assert!(!tiler.window_exists(WindowID(0, 3)));
+
+    assert_eq!(
+        tiler.attach_to_fork_branch(WindowID(0, 2), root, Side::Right, t),
+        Err(PlacementError::WindowInTree(WindowID(0, 2)))
+    );
+
+    assert!(tiler.validate(t).is_empty());
+}
+
+#[test]
+fn swap_active_with_id() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    common::attach(&mut tiler, &[0, 1], t);
+    let before = common::rects(&mut tiler, t);
+
+    assert!(tiler.swap_active_with_id(WindowID(0, 0), t));
+    let after = common::rects(&mut tiler, t);
+    assert_eq!(after[&WindowID(0, 0)], before[&WindowID(0, 1)]);
+    assert_eq!(after[&WindowID(0, 1)], before[&WindowID(0, 0)]);
+}
+
+#[test]
+fn swap_active_with_missing_id() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    common::attach(&mut tiler, &[0, 1], t);
+    let before = common::rects(&mut tiler, t);
+
+    assert!(!tiler.swap_active_with_id(WindowID(0, 2), t));
+    assert_eq!(common::rects(&mut tiler, t), before);
+}
+
+#[test]
+fn swap_active_with_id_without_active_window() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    tiler.window((0, 0));
+    assert!(tiler.active_window().is_none());
+    assert!(!tiler.swap_active_with_id(WindowID(0, 0), t));
+}
+
+/// Five windows, of which three are then detached either by a reap or one at a time.
+/// Returns the placements made by the detaches, and where the remaining windows are.
+fn detach_three<T: 'static>(
+    reap: bool,
+    t: &mut TCellOwner<T>,
+) -> (usize, BTreeMap<WindowID, Rect>) {
+    let mut tiler = common::tiler(t);
+    let windows = common::attach(&mut tiler, &[0, 1, 2, 3, 4], t);
+    let _ = common::events(&mut tiler, t);
+
+    let mut placements = 0;
+    let mut count = |events: Vec<Event>| {
+        placements += events
+            .iter()
+            .filter(|event| matches!(event, Event::WindowPlace(..)))
+            .count();
+    };
+
+    if reap {
+        let ids: Vec<WindowID> = windows[1..4].iter().map(|window| window.id(t)).collect();
+        tiler.reap(&ids, t);
+        count(common::events(&mut tiler, t));
+    } else {
+        for window in &windows[1..4] {
+            tiler.detach(window, t);
+            count(common::events(&mut tiler, t));
+        }
+    }
+
+    (placements, common::rects(&mut tiler, t))
+}
+
+#[test]
+fn reap_places_remaining_windows_once() {
+    struct Reap;
+    let (reaped, reaped_rects) = detach_three(true, &mut TCellOwner::<Reap>::new());
+
+    struct Detach;
+    let (detached, detached_rects) = detach_three(false, &mut TCellOwner::<Detach>::new());
+
+    assert_eq!(reaped_rects.len(), 2);
+    assert_eq!(reaped_rects, detached_rects);
+    assert!(reaped <= 2, "{} placements", reaped);
+    assert!(
+        reaped < detached,
+        "{} placements against {}",
+        reaped,
+        detached
+    );
+}
+
+#[test]
+fn arrange_five_equal_columns() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    common::attach(&mut tiler, &[0, 1, 2, 3, 4], t);
+    tiler.arrange_equal_columns(t);
+
+    let rects = common::rects(&mut tiler, t);
+    assert_eq!(rects.len(), 5);
+
+    for rect in rects.values() {
+        assert!((199..=201).contains(&rect.width), "{:?}", rect);
+        assert_eq!((rect.y, rect.height), (0, 800));
+    }
+
+    let width: u32 = rects.values().map(|rect| rect.width).sum();
+    assert_eq!(width, 1000);
+}
+
+#[test]
+fn sibling_and_area_of_inner_fork() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    common::attach(&mut tiler, &[0, 1, 2], t);
+    let path = tiler.active_window_path(t);
+    let (root, inner) = (path[0], path[1]);
+
+    assert_eq!(
+        tiler.fork_sibling(inner, t),
+        Some(ForkChild::Window(WindowID(0, 0)))
+    );
+    assert_eq!(tiler.fork_area(inner, t), Some(Rect::new(500, 0, 500, 800)));
+
+    assert_eq!(tiler.fork_sibling(root, t), None);
+    assert_eq!(tiler.fork_area(root, t), Some(Rect::new(0, 0, 1000, 800)));
+}
+
+#[test]
+fn repair_window_of_another_tree() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+
+    let mut other = common::tiler(t);
+    let stray = common::attach(&mut other, &[1], t).remove(0);
+
+    let mut tiler = common::tiler(t);
+    common::attach(&mut tiler, &[0], t);
+
+    // The window is associated with a fork of the other tiler, which this one lacks.
+    tiler.windows.insert(WindowID(0, 1), stray);
+    assert_eq!(
+        tiler.validate(t),
+        vec![TreeInconsistency::DanglingWindow(WindowID(0, 1))]
+    );
+
+    let repaired = tiler.validate_and_repair(t);
+    assert_eq!(
+        repaired,
+        vec![(TreeInconsistency::DanglingWindow(WindowID(0, 1)), true)]
+    );
+    assert_eq!(tiler.validate(t), Vec::new());
+
+    let rects = common::rects(&mut tiler, t);
+    assert_eq!(rects.len(), 2);
+    assert!(rects.contains_key(&WindowID(0, 1)));
+}
+
+#[test]
+fn subtree_of_inner_fork() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    let area = Rect::new(0, 0, 1000, 800);
+    let stack = Layout::Stack {
+        windows: vec![WindowID(0, 3), WindowID(0, 4)],
+        active: WindowID(0, 3),
+    };
+
+    let pair = common::fork(
+        area,
+        Orientation::Horizontal,
+        250,
+        common::window(1),
+        common::window(2),
+    );
+    let inner = common::fork(area, Orientation::Vertical, 400, pair, stack);
+    let root = common::fork(area, Orientation::Horizontal, 500, common::window(0), inner);
+    common::arrange(&mut tiler, root, t);
+
+    let window = tiler.windows[&WindowID(0, 1)].clone();
+    tiler.focus(&window, t);
+    let path = tiler.active_window_path(t);
+
+    let ids = |ids: &[u32]| ids.iter().map(|&id| WindowID(0, id)).collect::<Vec<_>>();
+    assert_eq!(tiler.subtree_window_ids(path[1], t), ids(&[1, 2, 3, 4]));
+    assert_eq!(tiler.subtree_window_ids(path[2], t), ids(&[1, 2]));
+    assert_eq!(tiler.subtree_window_ids(path[0], t), ids(&[0, 1, 2, 3, 4]));
+}
+
+#[test]
+fn forced_horizontal_root_on_portrait_display() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+    tiler.display_update(0, Rect::new(0, 0, 800, 1000), t);
+    tiler.set_split_on_empty_workspace_orientation(Some(Orientation::Horizontal));
+
+    common::attach(&mut tiler, &[0, 1], t);
+
+    let forks = common::forks(&tiler, t);
+    assert_eq!(forks.len(), 1);
+    assert_eq!(
+        forks.values().next().map(|fork| fork.orientation),
+        Some(Orientation::Horizontal)
+    );
+
+    let rects = common::rects(&mut tiler, t);
+    assert_eq!(rects[&WindowID(0, 0)], Rect::new(0, 0, 400, 1000));
+    assert_eq!(rects[&WindowID(0, 1)], Rect::new(400, 0, 400, 1000));
+}
+
+#[test]
+fn active_window_path_in_nested_layout() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    let area = Rect::new(0, 0, 1000, 800);
+    let inner = common::fork(
+        area,
+        Orientation::Horizontal,
+        250,
+        common::window(2),
+        common::window(3),
+    );
+    let middle = common::fork(area, Orientation::Vertical, 400, common::window(1), inner);
+    let root = common::fork(
+        area,
+        Orientation::Horizontal,
+        500,
+        common::window(0),
+        middle,
+    );
+    common::arrange(&mut tiler, root, t);
+
+    let window = tiler.windows[&WindowID(0, 3)].clone();
+    tiler.focus(&window, t);
+
+    // The forks, from the root down, by the area that each occupies.
+    let by_area: BTreeMap<(u32, u32, u32, u32), usize> = common::forks(&tiler, t)
+        .into_iter()
+        .map(|(id, fork)| {
+            (
+                (fork.rect.x, fork.rect.y, fork.rect.width, fork.rect.height),
+                id,
+            )
+        })
+        .collect();
+
+    let expected: Vec<usize> = [(0, 0, 1000, 800), (500, 0, 500, 800), (500, 400, 500, 400)]
+        .iter()
+        .map(|area| by_area[area])
+        .collect();
+
+    assert_eq!(tiler.active_window_path(t), expected);
+}
+
+#[test]
+fn detaching_every_window_leaves_no_forks_or_stacks() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    let mut windows = common::attach(&mut tiler, &[0, 1, 2], t);
+    tiler.stack_toggle(t);
+    windows.extend(common::attach(&mut tiler, &[3, 4], t));
+
+    assert!(tiler.count_forks() > 0);
+    assert!(tiler.count_stacks() > 0);
+
+    for window in &windows {
+        tiler.detach(window, t);
+    }
+
+    assert_eq!((tiler.count_forks(), tiler.count_stacks()), (0, 0));
+}
+
+#[test]
+fn move_middle_of_row_right() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    let area = Rect::new(0, 0, 1000, 800);
+    let right = common::fork(
+        area,
+        Orientation::Horizontal,
+        333,
+        common::window(1),
+        common::window(2),
+    );
+    let row = common::fork(area, Orientation::Horizontal, 333, common::window(0), right);
+    common::arrange(&mut tiler, row, t);
+
+    let window = tiler.windows[&WindowID(0, 1)].clone();
+    tiler.focus(&window, t);
+    tiler.move_active_edgewise_within_fork(Direction::Right, t);
+
+    let rects = common::rects(&mut tiler, t);
+    assert_eq!(
+        left_to_right(&rects),
+        [WindowID(0, 0), WindowID(0, 2), WindowID(0, 1)]
+    );
+    assert!(rects.values().all(|rect| (rect.y, rect.height) == (0, 800)));
+    assert!(tiler.validate(t).is_empty());
+}
+
+#[test]
+fn swap_preview_matches_swap() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    let windows = common::attach(&mut tiler, &[0, 1, 2], t);
+    let (a, b) = (WindowID(0, 0), WindowID(0, 2));
+
+    let preview = tiler.swap_preview(a, b, t);
+    tiler.swap(&windows[0], &windows[2], t);
+    let rects = common::rects(&mut tiler, t);
+
+    assert_eq!(preview, Some((rects[&a], rects[&b])));
+    assert_ne!(rects[&a], rects[&b]);
+}
+
+#[test]
+fn fork_handle_at_nested_split() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    common::attach(&mut tiler, &[0, 1, 2], t);
+    let path = tiler.active_window_path(t);
+    let (root, inner) = (path[0], path[1]);
+
+    // Where the split of the inner fork meets the split of the root.
+    let corner = Point { x: 500, y: 400 };
+    assert_eq!(tiler.fork_handle_at(corner, 8, t), Some(inner));
+
+    assert_eq!(
+        tiler.fork_handle_at(Point { x: 500, y: 100 }, 8, t),
+        Some(root)
+    );
+    assert_eq!(tiler.fork_handle_at(Point { x: 250, y: 400 }, 8, t), None);
+}
+
+/// The windows of a fork from left to right, after toggling its orientation twice.
+fn order_after_double_toggle<T: 'static>(swap: bool, t: &mut TCellOwner<T>) -> Vec<WindowID> {
+    let mut tiler = common::tiler(t);
+    tiler.set_swap_on_double_toggle(swap);
+
+    common::attach(&mut tiler, &[0, 1], t);
+    tiler.toggle_orientation(t);
+    tiler.toggle_orientation(t);
+
+    left_to_right(&common::rects(&mut tiler, t))
+}
+
+#[test]
+fn double_toggle_keeps_order_without_swap() {
+    struct Marker;
+    let order = order_after_double_toggle(false, &mut TCellOwner::<Marker>::new());
+    assert_eq!(order, [WindowID(0, 0), WindowID(0, 1)]);
+}
+
+#[test]
+fn double_toggle_swaps_branches() {
+    struct Marker;
+    let order = order_after_double_toggle(true, &mut TCellOwner::<Marker>::new());
+    assert_eq!(order, [WindowID(0, 1), WindowID(0, 0)]);
+}
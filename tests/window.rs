@@ -0,0 +1,220 @@
+// Copyright 2021 System76 <info@system76.com>
+// SPDX-License-Identifier: MPL-2.0
+
+mod common;
+
+use pop_tiler::*;
+use std::rc::Rc;
+
+#[test]
+fn window_new_rejects_id_in_use() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    let window = tiler.window_new((0, 0)).unwrap();
+    assert_eq!(window.id(t), WindowID(0, 0));
+
+    assert_eq!(
+        tiler.window_new((0, 0)).err(),
+        Some(IdInUse(WindowID(0, 0)))
+    );
+
+    let existing = tiler.window((0, 0));
+    assert!(Rc::ptr_eq(&existing, &window));
+}
+
+/// The decoration reported for each window by these events.
+fn decorations(events: &[Event]) -> Vec<(WindowID, Decoration)> {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            Event::WindowDecoration(id, decoration) => Some((*id, *decoration)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn lone_window_has_no_border() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+    tiler.set_auto_hide_single_border(true, t);
+
+    common::attach(&mut tiler, &[0], t);
+    assert_eq!(
+        decorations(&common::events(&mut tiler, t)),
+        [(WindowID(0, 0), Decoration::NoBorder)]
+    );
+
+    // The second window has the border that windows have by default.
+    common::attach(&mut tiler, &[1], t);
+    assert_eq!(
+        decorations(&common::events(&mut tiler, t)),
+        [(WindowID(0, 0), Decoration::Border)]
+    );
+}
+
+#[test]
+fn resize_increment_snaps_width_down() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    let windows = common::attach(&mut tiler, &[0, 1], t);
+    let root = tiler.active_window_path(t)[0];
+    tiler.fork_resize(root, 100, t);
+    let _ = common::events(&mut tiler, t);
+
+    // The 4px left over are split evenly on either side.
+    tiler.set_resize_increment(&windows[0], 8, 1, t);
+    let placements = common::placements(&common::events(&mut tiler, t));
+    assert_eq!(placements[&WindowID(0, 0)], Rect::new(2, 0, 96, 800));
+    assert_eq!(placements[&WindowID(0, 1)], Rect::new(100, 0, 900, 800));
+}
+
+#[test]
+fn floating_window_at_default_rect() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+    tiler.display_update(0, Rect::new(200, 0, 1000, 800), t);
+    tiler.set_default_float_rect(Rect::new(100, 100, 400, 300));
+    tiler.set_tiling_enabled(0, false, t);
+
+    common::attach(&mut tiler, &[0], t);
+    let placements = common::placements(&common::events(&mut tiler, t));
+    assert_eq!(placements[&WindowID(0, 0)], Rect::new(300, 100, 400, 300));
+    assert!(!tiler.is_managed(WindowID(0, 0), t));
+}
+
+#[test]
+fn placement_padding_shrinks_only_its_window() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    let windows = common::attach(&mut tiler, &[0, 1], t);
+    let before = common::rects(&mut tiler, t);
+
+    let insets = Insets {
+        top: 10,
+        bottom: 20,
+        left: 30,
+        right: 40,
+    };
+
+    tiler.set_placement_padding(&windows[0], insets, t);
+    let placed = common::placements(&common::events(&mut tiler, t));
+
+    assert_eq!(before[&WindowID(0, 0)], Rect::new(0, 0, 500, 800));
+    assert_eq!(
+        placed.get(&WindowID(0, 0)),
+        Some(&Rect::new(30, 10, 430, 770))
+    );
+
+    let after = common::rects(&mut tiler, t);
+    assert_eq!(after[&WindowID(0, 1)], before[&WindowID(0, 1)]);
+}
+
+#[test]
+fn placement_kept_within_display() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+    tiler.set_minimum_visible_area(50);
+
+    // Floated entirely beyond the bottom right corner of the display.
+    tiler.set_attach_rule(Box::new(|_| {
+        AttachDecision::Float(Rect::new(2000, 900, 400, 300))
+    }));
+
+    common::attach(&mut tiler, &[0], t);
+    let placed = common::placements(&common::events(&mut tiler, t));
+
+    assert_eq!(placed[&WindowID(0, 0)], Rect::new(950, 750, 400, 300));
+}
+
+#[test]
+fn tiled_window_exists_and_is_managed() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    common::attach(&mut tiler, &[0], t);
+    assert!(tiler.window_exists(WindowID(0, 0)));
+    assert!(tiler.is_managed(WindowID(0, 0), t));
+}
+
+#[test]
+fn floating_window_exists_unmanaged() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+    tiler.set_attach_rule(Box::new(|_| {
+        AttachDecision::Float(Rect::new(0, 0, 400, 300))
+    }));
+
+    common::attach(&mut tiler, &[0], t);
+    assert!(tiler.window_exists(WindowID(0, 0)));
+    assert!(!tiler.is_managed(WindowID(0, 0), t));
+}
+
+#[test]
+fn unknown_window_neither_exists_nor_is_managed() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    common::attach(&mut tiler, &[0], t);
+    assert!(!tiler.window_exists(WindowID(0, 1)));
+    assert!(!tiler.is_managed(WindowID(0, 1), t));
+}
+
+#[test]
+fn smart_borders_of_grid_corner() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    let area = Rect::new(0, 0, 1000, 800);
+    let left = common::fork(
+        area,
+        Orientation::Vertical,
+        400,
+        common::window(0),
+        common::window(1),
+    );
+    let right = common::fork(
+        area,
+        Orientation::Vertical,
+        400,
+        common::window(2),
+        common::window(3),
+    );
+    common::arrange(
+        &mut tiler,
+        common::fork(area, Orientation::Horizontal, 500, left, right),
+        t,
+    );
+
+    tiler.set_smart_borders(true, t);
+    let borders: Vec<(WindowID, EdgeSet)> = common::events(&mut tiler, t)
+        .into_iter()
+        .filter_map(|event| match event {
+            Event::WindowBorders(id, edges) => Some((id, edges)),
+            _ => None,
+        })
+        .collect();
+
+    let top_left = EdgeSet {
+        top: false,
+        bottom: true,
+        left: false,
+        right: true,
+    };
+
+    assert_eq!(borders.len(), 4);
+    assert!(borders.contains(&(WindowID(0, 0), top_left)));
+}
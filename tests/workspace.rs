@@ -0,0 +1,389 @@
+// Copyright 2021 System76 <info@system76.com>
+// SPDX-License-Identifier: MPL-2.0
+
+mod common;
+
+use pop_tiler::*;
+
+#[test]
+fn preview_workspace_switch_matches_switch() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+    tiler.workspace_update(1, 0, t);
+
+    common::attach(&mut tiler, &[0, 1], t);
+    tiler.workspace_switch(1, t);
+    common::attach(&mut tiler, &[2, 3], t);
+    tiler.workspace_switch(0, t);
+    let _ = common::events(&mut tiler, t);
+
+    let (mut hide, mut show) = tiler.preview_workspace_switch(1, t);
+    hide.sort();
+    show.sort();
+    assert_eq!(hide, [WindowID(0, 0), WindowID(0, 1)]);
+    assert_eq!(show, [WindowID(0, 2), WindowID(0, 3)]);
+
+    tiler.workspace_switch(1, t);
+    let (mut hidden, mut shown) = (Vec::new(), Vec::new());
+    for event in common::events(&mut tiler, t) {
+        match event {
+            Event::WindowVisibility(id, false) => hidden.push(id),
+            Event::WindowVisibility(id, true) => shown.push(id),
+            _ => (),
+        }
+    }
+
+    hidden.sort();
+    shown.sort();
+    assert_eq!(hide, hidden);
+    assert_eq!(show, shown);
+}
+
+#[test]
+fn clone_layout_fills_template_slots() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+    tiler.workspace_update(1, 0, t);
+
+    common::attach(&mut tiler, &[0, 1], t);
+    let root = tiler.active_window_path(t)[0];
+    tiler.fork_resize(root, 300, t);
+
+    tiler.clone_layout_to_workspace(0, 1, t);
+    tiler.workspace_switch(1, t);
+    common::attach(&mut tiler, &[2, 3], t);
+
+    let rects = common::rects(&mut tiler, t);
+    assert_eq!(rects[&WindowID(0, 2)], Rect::new(0, 0, 300, 800));
+    assert_eq!(rects[&WindowID(0, 3)], Rect::new(300, 0, 700, 800));
+}
+
+#[test]
+fn tiling_disabled_floats_windows() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    common::attach(&mut tiler, &[0, 1], t);
+    tiler.set_tiling_enabled(0, false, t);
+    common::attach(&mut tiler, &[2], t);
+    let floating = common::placements(&common::events(&mut tiler, t));
+
+    // Each window is placed apart from the others, at a cascading position.
+    for id in [WindowID(0, 0), WindowID(0, 1), WindowID(0, 2)] {
+        assert!(tiler.window_exists(id));
+        assert!(!tiler.is_managed(id, t));
+    }
+
+    assert_eq!(floating.len(), 3);
+    assert_ne!(floating[&WindowID(0, 0)], floating[&WindowID(0, 1)]);
+    assert_ne!(floating[&WindowID(0, 1)], floating[&WindowID(0, 2)]);
+
+    // The windows are tiled in the tree they had, and the window attached meanwhile too.
+    tiler.set_tiling_enabled(0, true, t);
+    let retiled = common::rects(&mut tiler, t);
+
+    for id in [WindowID(0, 0), WindowID(0, 1), WindowID(0, 2)] {
+        assert!(tiler.is_managed(id, t));
+    }
+
+    let area: u32 = retiled.values().map(Rect::area).sum();
+    assert_eq!(area, 1000 * 800);
+    assert!(retiled[&WindowID(0, 0)].x < retiled[&WindowID(0, 1)].x);
+    assert!(tiler.validate(t).is_empty());
+}
+
+/// The windows in the tree of a workspace, in depth-first order.
+fn tree_windows<T: 'static>(tiler: &Tiler<T>, workspace: u32, t: &TCellOwner<T>) -> Vec<WindowID> {
+    let mut windows = Vec::new();
+    let mut layouts: Vec<Layout> = tiler
+        .snapshot_workspace(workspace, t)
+        .map(|snapshot| snapshot.root)
+        .into_iter()
+        .collect();
+
+    while let Some(layout) = layouts.pop() {
+        match layout {
+            Layout::Window(id) => windows.push(id),
+            Layout::Stack { windows: tabs, .. } => windows.extend(tabs),
+            Layout::Fork(fork) => {
+                layouts.extend(fork.right);
+                layouts.push(fork.left);
+            }
+        }
+    }
+
+    windows
+}
+
+/// The visibility of each window changed by these events.
+fn visibility(events: &[Event]) -> Vec<(WindowID, bool)> {
+    let mut changes: Vec<(WindowID, bool)> = events
+        .iter()
+        .filter_map(|event| match event {
+            Event::WindowVisibility(id, visible) => Some((*id, *visible)),
+            _ => None,
+        })
+        .collect();
+
+    changes.sort();
+    changes
+}
+
+#[test]
+fn move_fork_to_workspace() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+    tiler.workspace_update(1, 0, t);
+
+    common::attach(&mut tiler, &[0, 1, 2], t);
+    let inner = tiler.active_window_path(t)[1];
+    let _ = common::events(&mut tiler, t);
+
+    tiler.move_fork_to_workspace(inner, 1, t);
+    let events = common::events(&mut tiler, t);
+
+    assert_eq!(tree_windows(&tiler, 0, t), [WindowID(0, 0)]);
+    assert_eq!(tree_windows(&tiler, 1, t), [WindowID(0, 1), WindowID(0, 2)]);
+    assert_eq!(
+        visibility(&events),
+        [(WindowID(0, 1), false), (WindowID(0, 2), false)]
+    );
+
+    // The active window moved away, so focus falls back to the window left behind.
+    assert_eq!(
+        tiler.active_window().map(|window| window.id(t)),
+        Some(WindowID(0, 0))
+    );
+
+    tiler.workspace_switch(1, t);
+    assert_eq!(
+        visibility(&common::events(&mut tiler, t)),
+        [
+            (WindowID(0, 0), false),
+            (WindowID(0, 1), true),
+            (WindowID(0, 2), true)
+        ]
+    );
+}
+
+#[test]
+fn move_fork_to_workspace_of_other_display() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+    tiler.display_update(1, Rect::new(1000, 0, 1000, 800), t);
+    tiler.workspace_update(1, 1, t);
+    tiler.workspace_switch(1, t);
+    tiler.workspace_switch(0, t);
+
+    common::attach(&mut tiler, &[0, 1, 2], t);
+    let inner = tiler.active_window_path(t)[1];
+    let _ = common::events(&mut tiler, t);
+
+    // The target workspace is shown on the other display, so the windows stay visible.
+    tiler.move_fork_to_workspace(inner, 1, t);
+    let events = common::events(&mut tiler, t);
+
+    assert_eq!(tree_windows(&tiler, 1, t), [WindowID(0, 1), WindowID(0, 2)]);
+    assert!(visibility(&events).is_empty());
+}
+
+#[test]
+fn workspace_switch_focuses_window_under_cursor() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+    tiler.workspace_update(1, 0, t);
+    tiler.set_workspace_focus_policy(WorkspaceFocusPolicy::AtPoint);
+
+    common::attach(&mut tiler, &[0], t);
+    tiler.workspace_switch(1, t);
+    common::attach(&mut tiler, &[1, 2], t);
+    tiler.workspace_switch(0, t);
+
+    // The cursor is over the left window of the other workspace, not its focused window.
+    tiler.notify_cursor(Point::new(100, 400));
+    tiler.workspace_switch(1, t);
+    assert_eq!(
+        tiler.active_window().map(|window| window.id(t)),
+        Some(WindowID(0, 1))
+    );
+}
+
+#[test]
+fn clear_workspace_detaches_every_window() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    common::attach(&mut tiler, &[0, 1, 2], t);
+    let mut ids = tiler.clear_workspace(0, t);
+    ids.sort();
+
+    assert_eq!(ids, [WindowID(0, 0), WindowID(0, 1), WindowID(0, 2)]);
+    assert!(tiler.snapshot_workspace(0, t).is_none());
+    assert_eq!(tiler.workspace_area(0, t), Some(Rect::new(0, 0, 1000, 800)));
+    assert_eq!(tiler.count_forks(), 0);
+    assert!(ids.iter().all(|&id| !tiler.is_managed(id, t)));
+}
+
+#[test]
+fn move_active_to_new_workspace() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+    tiler.workspace_update(2, 0, t);
+
+    common::attach(&mut tiler, &[0, 1], t);
+    let _ = common::events(&mut tiler, t);
+
+    assert_eq!(tiler.move_active_to_new_workspace(t), Some(1));
+    let events = common::events(&mut tiler, t);
+
+    assert!(events.iter().any(|event| matches!(
+        event,
+        Event::WorkspaceAssign {
+            workspace: 1,
+            display: 0
+        }
+    )));
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, Event::FocusWorkspace(1))));
+
+    assert_eq!(tiler.workspaces_on_display(0, t), [0, 1, 2]);
+    assert_eq!(tree_windows(&tiler, 1, t), [WindowID(0, 1)]);
+    assert_eq!(tree_windows(&tiler, 0, t), [WindowID(0, 0)]);
+    assert_eq!(
+        tiler.active_window().map(|window| window.id(t)),
+        Some(WindowID(0, 1))
+    );
+}
+
+#[test]
+fn snapshot_round_trip() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+
+    let windows = common::attach(&mut tiler, &[0, 1, 2, 3], t);
+    tiler.stack_toggle(t);
+    tiler.focus(&windows[1], t);
+    tiler.toggle_orientation(t);
+
+    let snapshot = tiler.snapshot_workspace(0, t).unwrap();
+    let rects = common::rects(&mut tiler, t);
+
+    // Rearrange the tree before restoring it.
+    tiler.arrange_equal_columns(t);
+    tiler.rotate_windows(true, t);
+    assert_ne!(common::rects(&mut tiler, t), rects);
+
+    tiler.restore_workspace(0, snapshot.clone(), t);
+    assert_eq!(tiler.snapshot_workspace(0, t), Some(snapshot));
+    assert_eq!(common::rects(&mut tiler, t), rects);
+}
+
+#[test]
+fn restore_snapshot_onto_workspace_of_other_display() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+    tiler.display_update(1, Rect::new(1000, 0, 1000, 800), t);
+    tiler.workspace_update(1, 1, t);
+    tiler.workspace_switch(1, t);
+    tiler.workspace_switch(0, t);
+
+    common::attach(&mut tiler, &[0, 1], t);
+    let snapshot = tiler.snapshot_workspace(0, t).unwrap();
+    let _ = common::events(&mut tiler, t);
+
+    // The windows move to the workspace shown on the other display, and stay visible.
+    tiler.restore_workspace(1, snapshot, t);
+    let events = common::events(&mut tiler, t);
+
+    assert_eq!(tree_windows(&tiler, 1, t), [WindowID(0, 0), WindowID(0, 1)]);
+    assert!(visibility(&events).is_empty());
+}
+
+#[test]
+fn move_active_away_without_following() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+    tiler.workspace_update(1, 0, t);
+
+    let windows = common::attach(&mut tiler, &[0, 1], t);
+    let _ = common::events(&mut tiler, t);
+
+    tiler.move_to_workspace(&windows[1], 1, false, t);
+    let events = common::events(&mut tiler, t);
+
+    // The source workspace stays active, with the focus on the window left on it.
+    assert!(!events
+        .iter()
+        .any(|event| matches!(event, Event::FocusWorkspace(_))));
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, Event::Focus(id, _) if *id == WindowID(0, 0))));
+    assert_eq!(visibility(&events), [(WindowID(0, 1), false)]);
+
+    assert_eq!(
+        tiler.active_window().map(|active| active.id(t)),
+        Some(WindowID(0, 0))
+    );
+    assert_eq!(tree_windows(&tiler, 0, t), [WindowID(0, 0)]);
+    assert_eq!(tree_windows(&tiler, 1, t), [WindowID(0, 1)]);
+}
+
+#[test]
+fn thumbnail_rects_of_hidden_workspace() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+    tiler.workspace_update(1, 0, t);
+
+    tiler.workspace_switch(1, t);
+    common::attach(&mut tiler, &[0, 1, 2], t);
+    let shown = common::rects(&mut tiler, t);
+
+    tiler.workspace_switch(0, t);
+    let thumbnails = tiler.workspace_thumbnail_rects(1, t);
+
+    assert_eq!(thumbnails, shown.into_iter().collect::<Vec<_>>());
+    assert_eq!(thumbnails.len(), 3);
+    assert!(tiler.workspace_thumbnail_rects(0, t).is_empty());
+}
+
+#[test]
+fn third_window_overflows_full_workspace() {
+    struct Marker;
+    let t = &mut TCellOwner::<Marker>::new();
+    let mut tiler = common::tiler(t);
+    tiler.set_workspace_capacity(0, Some(2));
+
+    common::attach(&mut tiler, &[0, 1], t);
+    let _ = common::events(&mut tiler, t);
+
+    let window = tiler.window((0, 2));
+    tiler.attach(&window, t);
+    let events = common::events(&mut tiler, t);
+
+    // The overflow workspace is created on the display of the full workspace.
+    assert!(events.iter().any(|event| matches!(
+        event,
+        Event::WorkspaceAssign {
+            workspace: 1,
+            display: 0
+        }
+    )));
+
+    let mut full = tree_windows(&tiler, 0, t);
+    full.sort();
+    assert_eq!(full, [WindowID(0, 0), WindowID(0, 1)]);
+    assert_eq!(tree_windows(&tiler, 1, t), [WindowID(0, 2)]);
+}